@@ -36,7 +36,7 @@ pub fn benchmark_single_block_recent(c: &mut Criterion) {
     });
 
     // Test Palantiri with ultra-fast Reqwest to beat Alloy's 194ms
-    let palantiri_ultra_fast = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_ultra_fast());
+    let palantiri_ultra_fast = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_ultra_fast().unwrap());
 
     group.bench_function("palantiri_ultra_fast", |b| {
         b.iter(|| {
@@ -48,7 +48,7 @@ pub fn benchmark_single_block_recent(c: &mut Criterion) {
     });
 
     // Test Palantiri with optimized Reqwest (connection pooling + HTTP/2)
-    let palantiri_optimized = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_optimized());
+    let palantiri_optimized = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_optimized().unwrap());
     
     group.bench_function("palantiri_optimized", |b| {
         b.iter(|| {
@@ -60,7 +60,7 @@ pub fn benchmark_single_block_recent(c: &mut Criterion) {
     });
 
     // Test standard Reqwest for comparison  
-    let palantiri_reqwest = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest());
+    let palantiri_reqwest = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest().unwrap());
     
     group.bench_function("palantiri_reqwest", |b| {
         b.iter(|| {
@@ -80,7 +80,7 @@ pub fn benchmark_single_block_old(c: &mut Criterion) {
     // Setup clients
     let rpc_url = RPC_URL.parse().unwrap();
     let alloy_provider = ProviderBuilder::new().on_http(rpc_url);
-    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest());
+    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest().unwrap());
 
     let mut group = c.benchmark_group("single_block_old");
     group.sample_size(20);
@@ -117,7 +117,7 @@ pub fn benchmark_multiple_blocks(c: &mut Criterion) {
     let rpc_url = RPC_URL.parse().unwrap();
     let alloy_provider = ProviderBuilder::new().on_http(rpc_url);
 
-    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_benchmark());
+    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_benchmark().unwrap());
 
     let mut group = c.benchmark_group("multiple_blocks");
     group.sample_size(10);
@@ -198,7 +198,7 @@ pub fn benchmark_caching_advantage(c: &mut Criterion) {
     let rpc_url = RPC_URL.parse().unwrap();
     let alloy_provider = ProviderBuilder::new().on_http(rpc_url);
 
-    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_benchmark());
+    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_benchmark().unwrap());
 
     // Pre-warm Palantiri cache
     rt.block_on(async {
@@ -241,9 +241,9 @@ pub fn benchmark_tower_transport(c: &mut Criterion) {
     let rpc_url = RPC_URL.parse().unwrap();
     let alloy_provider = ProviderBuilder::new().on_http(rpc_url);
 
-    let palantiri_hyper = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal());
-    let palantiri_tower = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower());
-    let palantiri_tower_optimized = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower_optimized());
+    let palantiri_hyper = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal().unwrap());
+    let palantiri_tower = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower().unwrap());
+    let palantiri_tower_optimized = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower_optimized().unwrap());
 
     let mut group = c.benchmark_group("tower_transport_comparison");
     group.sample_size(10);
@@ -293,8 +293,8 @@ pub fn benchmark_tower_transport(c: &mut Criterion) {
 pub fn benchmark_tower_batch(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
 
-    let palantiri_hyper = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal());
-    let palantiri_tower_optimized = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower_optimized());
+    let palantiri_hyper = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal().unwrap());
+    let palantiri_tower_optimized = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower_optimized().unwrap());
 
     let mut group = c.benchmark_group("tower_batch_comparison");
     group.sample_size(10);
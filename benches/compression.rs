@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use ::palantiri::{
+    hyper_rpc::RpcClient as HyperRpcClient,
+    transport::{compression::Compression, http::TransportBuilder},
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tokio::runtime::Runtime;
+
+const RECENT_BLOCKS: [u64; 5] = [23326322, 23326321, 23326320, 23326319, 23326318];
+const RPC_URL: &str = "https://mainnet.infura.io/v3/1f2bd7408b1542e89bd4274b688aa6a4";
+
+fn clients(compression: Compression) -> [(&'static str, HyperRpcClient); 2] {
+    let builder = || TransportBuilder::new(RPC_URL).compression(compression);
+    [
+        ("hyper", HyperRpcClient::new(builder().build_http_hyper().unwrap())),
+        ("tower", HyperRpcClient::new(builder().build_tower().unwrap())),
+    ]
+}
+
+/// Full blocks and their receipts are the largest responses we fetch, the
+/// ones compression should pay off on
+pub fn benchmark_block_fetching(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("response_compression");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(60));
+
+    for (mode, compression) in [
+        ("compressed", Compression::default()),
+        ("plain", Compression::DISABLED),
+    ] {
+        for (transport, rpc) in clients(compression) {
+            group.bench_function(format!("{}_full_blocks_{}", transport, mode), |b| {
+                b.iter(|| {
+                    rt.block_on(async {
+                        let mut blocks = Vec::new();
+                        for &block_num in &RECENT_BLOCKS {
+                            blocks.push(rpc.get_block_by_number(block_num, true).await.ok());
+                        }
+                        black_box(blocks)
+                    })
+                });
+            });
+
+            group.bench_function(format!("{}_block_receipts_{}", transport, mode), |b| {
+                b.iter(|| {
+                    rt.block_on(async {
+                        let receipts = rpc.get_block_receipts(RECENT_BLOCKS[0]).await;
+                        black_box(receipts)
+                    })
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_block_fetching);
+criterion_main!(benches);
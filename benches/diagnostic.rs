@@ -19,7 +19,7 @@ pub fn diagnostic_network_vs_parsing(c: &mut Criterion) {
     let rpc_url = RPC_URL.parse().unwrap();
     let alloy_provider = ProviderBuilder::new().on_http(rpc_url);
 
-    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper());
+    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper().unwrap());
 
     let mut group = c.benchmark_group("network_vs_parsing");
     group.sample_size(10);
@@ -93,7 +93,7 @@ pub fn diagnostic_network_vs_parsing(c: &mut Criterion) {
 pub fn diagnostic_parsing_only(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
 
-    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper());
+    let palantiri_rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper().unwrap());
 
     // Pre-fetch some data to test parsing in isolation
     let recent_block_data = rt.block_on(async {
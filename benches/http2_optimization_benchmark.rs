@@ -17,12 +17,12 @@ pub fn benchmark_http2_optimizations(c: &mut Criterion) {
     
     // Original Palantiri client with HTTP/2 enabled
     let palantiri_original = HyperRpcClient::new(
-        TransportBuilder::new(RPC_URL).build_http_hyper(),
+        TransportBuilder::new(RPC_URL).build_http_hyper().unwrap(),
     );
     
     // New minimal Alloy-style client 
     let palantiri_minimal = HyperRpcClient::new(
-        TransportBuilder::new(RPC_URL).build_http_hyper_minimal(),
+        TransportBuilder::new(RPC_URL).build_http_hyper_minimal().unwrap(),
     );
     
     let mut group = c.benchmark_group("http2_optimization");
@@ -78,7 +78,7 @@ pub fn benchmark_single_request_optimization(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
     
     let palantiri_minimal = HyperRpcClient::new(
-        TransportBuilder::new(RPC_URL).build_http_hyper_minimal(),
+        TransportBuilder::new(RPC_URL).build_http_hyper_minimal().unwrap(),
     );
     
     let mut group = c.benchmark_group("single_request_optimization");
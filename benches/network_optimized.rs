@@ -10,7 +10,7 @@ const RPC_URL: &str = "https://mainnet.infura.io/v3/1f2bd7408b1542e89bd4274b688a
 pub fn benchmark_batching(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
 
-    let rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper());
+    let rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper().unwrap());
 
     let mut group = c.benchmark_group("batching_optimization");
     group.sample_size(10);
@@ -48,7 +48,7 @@ pub fn benchmark_batching(c: &mut Criterion) {
 pub fn benchmark_caching(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
 
-    let rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper());
+    let rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper().unwrap());
 
     let mut group = c.benchmark_group("caching_optimization");
     group.sample_size(10);
@@ -52,7 +52,7 @@ pub fn benchmark_alloy(c: &mut Criterion) {
 pub fn benchmark_palantiri(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
 
-    let rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper());
+    let rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper().unwrap());
 
     let block_counter = AtomicU64::new(STARTING_BLOCK);
 
@@ -118,7 +118,7 @@ pub fn benchmark_concurrent_palantiri(c: &mut Criterion) {
     group.bench_function("palantiri_concurrent_10", |b| {
         b.iter(|| {
             rt.block_on(async {
-                let rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper());
+                let rpc = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper().unwrap());
 
                 let mut handles = Vec::new();
                 let start_block = STARTING_BLOCK;
@@ -179,7 +179,7 @@ pub fn benchmark_response_parsing(c: &mut Criterion) {
 //     group.measurement_time(Duration::from_secs(30));
 
 //     // Test with actual RPC client using custom parsing
-//     let client_custom = RpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_optimized());
+//     let client_custom = RpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_optimized().unwrap());
 
 //     group.bench_function("custom_end_to_end", |b| {
 //         b.iter(|| {
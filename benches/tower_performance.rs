@@ -11,9 +11,9 @@ pub fn benchmark_tower_vs_hyper(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
 
     // Setup clients
-    let palantiri_hyper = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal());
-    let palantiri_tower = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower());
-    let palantiri_tower_optimized = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower_optimized());
+    let palantiri_hyper = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal().unwrap());
+    let palantiri_tower = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower().unwrap());
+    let palantiri_tower_optimized = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_tower_optimized().unwrap());
 
     let mut group = c.benchmark_group("tower_vs_hyper");
     group.sample_size(10);
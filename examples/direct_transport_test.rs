@@ -1,4 +1,4 @@
-use palantiri::{transport::http::TransportBuilder, hyper_rpc::Transport};
+use palantiri::transport::{http::TransportBuilder, Transport};
 use std::time::Instant;
 use tokio;
 
@@ -9,7 +9,7 @@ async fn main() {
     println!("Testing DirectTransport at the transport level...");
     
     // Create DirectTransport
-    let direct_transport = TransportBuilder::new(RPC_URL).build_direct();
+    let direct_transport = TransportBuilder::new(RPC_URL).build_direct().unwrap();
     
     // Create a simple JSON-RPC request
     let request = r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#;
@@ -17,10 +17,10 @@ async fn main() {
     println!("Making direct transport request...");
     let start = Instant::now();
     
-    match direct_transport.hyper_execute(request.to_string()).await {
+    match direct_transport.request(request.into()).await {
         Ok(response) => {
             println!("DirectTransport SUCCESS in {:?}", start.elapsed());
-            println!("Response: {}", response);
+            println!("Response: {}", String::from_utf8_lossy(&response));
         },
         Err(e) => {
             println!("DirectTransport FAILED: {}", e);
@@ -29,13 +29,13 @@ async fn main() {
     
     // Test DirectReqwestTransport 
     println!("\n=== Testing DirectReqwestTransport ===");
-    let direct_reqwest_transport = TransportBuilder::new(RPC_URL).build_direct_reqwest();
+    let direct_reqwest_transport = TransportBuilder::new(RPC_URL).build_direct_reqwest().unwrap();
     
     let start = Instant::now();
-    match direct_reqwest_transport.hyper_execute(request.to_string()).await {
+    match direct_reqwest_transport.request(request.into()).await {
         Ok(response) => {
             println!("DirectReqwestTransport SUCCESS in {:?}", start.elapsed());
-            println!("Response: {}", response);
+            println!("Response: {}", String::from_utf8_lossy(&response));
         },
         Err(e) => {
             println!("DirectReqwestTransport FAILED: {}", e);
@@ -44,13 +44,13 @@ async fn main() {
     
     // Test ReqwestTransport for comparison
     println!("\n=== Testing ReqwestTransport for comparison ===");
-    let reqwest_transport = TransportBuilder::new(RPC_URL).build_reqwest();
+    let reqwest_transport = TransportBuilder::new(RPC_URL).build_reqwest().unwrap();
     
     let start = Instant::now();
-    match reqwest_transport.hyper_execute(request.to_string()).await {
+    match reqwest_transport.request(request.into()).await {
         Ok(response) => {
             println!("ReqwestTransport SUCCESS in {:?}", start.elapsed());
-            println!("Response: {}", response);
+            println!("Response: {}", String::from_utf8_lossy(&response));
         },
         Err(e) => {
             println!("ReqwestTransport FAILED: {}", e);
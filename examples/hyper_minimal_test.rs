@@ -11,7 +11,7 @@ async fn main() {
     
     // Test HyperTransport minimal (should work and be fast)
     println!("\n=== Testing HyperTransport Minimal ===");
-    let hyper_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal());
+    let hyper_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal().unwrap());
     
     // Make multiple requests to see connection reuse benefit
     for i in 1..=5 {
@@ -26,7 +26,7 @@ async fn main() {
     println!("\n=== Testing DirectReqwestTransport (fresh clients) ===");
     for i in 1..=3 {
         let start = Instant::now();
-        let fresh_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_direct_reqwest());
+        let fresh_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_direct_reqwest().unwrap());
         match fresh_client.get_block_by_number(TEST_BLOCK, true).await {
             Ok(_) => println!("DirectReqwest request {}: SUCCESS in {:?}", i, start.elapsed()),
             Err(e) => println!("DirectReqwest request {} FAILED: {}", i, e),
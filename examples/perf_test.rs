@@ -11,7 +11,7 @@ async fn main() {
     
     // Test with minimal transport (connection pooling)
     println!("\n=== Testing with minimal transport (connection pooling) ===");
-    let palantiri_minimal = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal());
+    let palantiri_minimal = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal().unwrap());
     
     for i in 1..=5 {
         let start = Instant::now();
@@ -26,7 +26,7 @@ async fn main() {
     
     // Test with benchmark transport (minimal pooling)
     println!("\n=== Testing with benchmark transport (minimal pooling) ===");
-    let palantiri_bench = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_benchmark());
+    let palantiri_bench = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_benchmark().unwrap());
     
     for i in 1..=5 {
         let start = Instant::now();
@@ -43,7 +43,7 @@ async fn main() {
     println!("\n=== Testing with fresh client each time ===");
     for i in 1..=3 {
         let start = Instant::now();
-        let fresh_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest());
+        let fresh_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest().unwrap());
         let result = fresh_client.get_block_by_number(TEST_BLOCK, true).await;
         let duration = start.elapsed();
         
@@ -11,7 +11,7 @@ async fn main() {
     
     // Test optimized Reqwest (HTTP/2 + connection pooling)
     println!("\n=== Testing Optimized ReqwestTransport ===");
-    let optimized_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_optimized());
+    let optimized_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_optimized().unwrap());
     
     // Make multiple requests to see connection reuse benefit
     for i in 1..=5 {
@@ -24,7 +24,7 @@ async fn main() {
     
     // Compare with standard Reqwest
     println!("\n=== Testing Standard ReqwestTransport ===");
-    let standard_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest());
+    let standard_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest().unwrap());
     
     for i in 1..=3 {
         let start = Instant::now();
@@ -11,7 +11,7 @@ async fn main() {
     
     // Test reqwest transport (this should work)
     println!("\n=== Testing ReqwestTransport ===");
-    let reqwest_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest());
+    let reqwest_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest().unwrap());
     let start = Instant::now();
     match reqwest_client.get_block_by_number(TEST_BLOCK, true).await {
         Ok(_) => println!("ReqwestTransport: SUCCESS in {:?}", start.elapsed()),
@@ -20,7 +20,7 @@ async fn main() {
     
     // Test minimal reqwest transport
     println!("\n=== Testing ReqwestTransport Minimal ===");
-    let reqwest_minimal_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_minimal());
+    let reqwest_minimal_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_reqwest_minimal().unwrap());
     let start = Instant::now();
     match reqwest_minimal_client.get_block_by_number(TEST_BLOCK, true).await {
         Ok(_) => println!("ReqwestTransport Minimal: SUCCESS in {:?}", start.elapsed()),
@@ -29,7 +29,7 @@ async fn main() {
     
     // Test hyper transport minimal
     println!("\n=== Testing HyperTransport Minimal ===");
-    let hyper_minimal_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal());
+    let hyper_minimal_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_http_hyper_minimal().unwrap());
     let start = Instant::now();
     match hyper_minimal_client.get_block_by_number(TEST_BLOCK, true).await {
         Ok(_) => println!("HyperTransport Minimal: SUCCESS in {:?}", start.elapsed()),
@@ -38,7 +38,7 @@ async fn main() {
     
     // Test direct transport
     println!("\n=== Testing DirectTransport ===");
-    let direct_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_direct());
+    let direct_client = HyperRpcClient::new(TransportBuilder::new(RPC_URL).build_direct().unwrap());
     let start = Instant::now();
     match direct_client.get_block_by_number(TEST_BLOCK, true).await {
         Ok(_) => println!("DirectTransport: SUCCESS in {:?}", start.elapsed()),
@@ -0,0 +1,294 @@
+//! Follows the head of the chain, reporting reorgs and finality
+
+use std::{collections::VecDeque, time::Duration};
+
+use futures::{stream, Stream, StreamExt};
+
+use crate::{
+    hyper_rpc::RpcClient,
+    parser::types::{Block, BlockHeader},
+    RpcError,
+};
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+pub const DEFAULT_MAX_REORG_DEPTH: usize = 64;
+/// Most blocks fetched in one batch while catching up
+const MAX_BLOCKS_PER_ROUND: u64 = 100;
+
+/// When a block is considered final
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirmation {
+    /// Once this many blocks are built on top of it
+    Depth(u64),
+    /// Once the node reports it as `safe`
+    Safe,
+    /// Once the node reports it as `finalized`
+    Finalized,
+}
+
+#[derive(Debug, Clone)]
+pub enum BlockEvent {
+    /// The block extends the chain followed so far
+    NewBlock(Block),
+    /// The chain switched forks. `dropped` are the headers no longer canonical
+    /// and `added` the blocks replacing them, both ascending from the common
+    /// ancestor. When the fork goes deeper than `max_reorg_depth` the whole
+    /// followed chain is dropped and the stream carries on from `added`
+    Reorg {
+        dropped: Vec<BlockHeader>,
+        added: Vec<Block>,
+    },
+    /// The block can no longer be reorged out, sent once per block
+    Finalized(BlockHeader),
+}
+
+type Heads = Box<dyn Stream<Item = BlockHeader> + Send + Unpin>;
+
+/// New block stream built on `eth_blockNumber` and batched
+/// `eth_getBlockByNumber`. When the transport supports subscriptions,
+/// `newHeads` notifications replace the polling timer.
+#[derive(Debug, Clone)]
+pub struct BlockStream {
+    client: RpcClient,
+    poll_interval: Duration,
+    confirmation: Confirmation,
+    full_transactions: bool,
+    max_reorg_depth: usize,
+    start_block: Option<u64>,
+}
+
+impl BlockStream {
+    pub fn new(client: RpcClient) -> Self {
+        Self {
+            client,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            confirmation: Confirmation::Depth(12),
+            full_transactions: false,
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+            start_block: None,
+        }
+    }
+
+    /// Delay between polls when no subscription is available
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn confirmation(mut self, confirmation: Confirmation) -> Self {
+        self.confirmation = confirmation;
+        self
+    }
+
+    pub fn full_transactions(mut self, full: bool) -> Self {
+        self.full_transactions = full;
+        self
+    }
+
+    /// Most blocks a reorg may replace before the stream stops looking for
+    /// the common ancestor and restarts from the new fork
+    pub fn max_reorg_depth(mut self, depth: usize) -> Self {
+        self.max_reorg_depth = depth.max(1);
+        self
+    }
+
+    /// Replays blocks from `number` on instead of starting at the head
+    pub fn start_block(mut self, number: u64) -> Self {
+        self.start_block = Some(number);
+        self
+    }
+
+    /// Starts following the chain. Errors are yielded without ending the
+    /// stream, the next round retries
+    pub async fn into_stream(self) -> impl Stream<Item = Result<BlockEvent, RpcError>> + Send {
+        let heads = match self.client.subscribe_new_heads().await {
+            Ok(heads) => Some(Box::new(heads) as Heads),
+            Err(_) => None,
+        };
+        let follower = Follower {
+            start_block: self.start_block,
+            config: self,
+            heads,
+            chain: VecDeque::new(),
+            finalized: None,
+            events: VecDeque::new(),
+            first_round: true,
+        };
+
+        stream::unfold(follower, |mut follower| async move {
+            loop {
+                if let Some(event) = follower.events.pop_front() {
+                    return Some((Ok(event), follower));
+                }
+                if let Err(e) = follower.round().await {
+                    return Some((Err(e), follower));
+                }
+            }
+        })
+    }
+}
+
+struct Follower {
+    config: BlockStream,
+    heads: Option<Heads>,
+    start_block: Option<u64>,
+    /// Canonical headers followed so far, ascending and contiguous. The first
+    /// one is the last finalized block once there is one, nothing above it is
+    /// dropped before it got its `Finalized` event
+    chain: VecDeque<BlockHeader>,
+    finalized: Option<u64>,
+    events: VecDeque<BlockEvent>,
+    first_round: bool,
+}
+
+impl Follower {
+    async fn round(&mut self) -> Result<(), RpcError> {
+        let head = self.next_head().await?;
+        self.sync(head).await?;
+        self.finalize(head).await
+    }
+
+    async fn next_head(&mut self) -> Result<u64, RpcError> {
+        if std::mem::take(&mut self.first_round) {
+            return Ok(self.config.client.get_block_number().await?.to::<u64>());
+        }
+
+        if let Some(heads) = &mut self.heads {
+            match heads.next().await {
+                Some(header) => return Ok(header.number),
+                // The subscription is gone for good, fall back to polling
+                None => self.heads = None,
+            }
+        }
+        tokio::time::sleep(self.config.poll_interval).await;
+        Ok(self.config.client.get_block_number().await?.to::<u64>())
+    }
+
+    /// Fetches from the tip (to notice a replaced tip) up to `head`
+    async fn sync(&mut self, head: u64) -> Result<(), RpcError> {
+        let from = match self.chain.back() {
+            Some(tip) => tip.number,
+            None => self.start_block.take().unwrap_or(head),
+        }
+        .min(head);
+        let to = head.min(from + MAX_BLOCKS_PER_ROUND - 1);
+
+        let numbers: Vec<u64> = (from..=to).collect();
+        let blocks = self
+            .config
+            .client
+            .get_blocks_by_numbers(numbers.clone(), self.config.full_transactions)
+            .await?;
+
+        for (number, block) in numbers.into_iter().zip(blocks) {
+            let block =
+                block.ok_or_else(|| RpcError::Response(format!("Block {} not found", number)))?;
+            self.apply(block).await?;
+        }
+        Ok(())
+    }
+
+    fn header_at(&self, number: u64) -> Option<&BlockHeader> {
+        let first = self.chain.front()?.number;
+        self.chain.get(number.checked_sub(first)? as usize)
+    }
+
+    async fn apply(&mut self, block: Block) -> Result<(), RpcError> {
+        let number = block.number.to::<u64>();
+        let extends = match (self.header_at(number), self.chain.back()) {
+            // Already followed
+            (Some(known), _) if known.hash == block.hash => return Ok(()),
+            (Some(_), _) => false,
+            (None, Some(tip)) => number == tip.number + 1 && tip.hash == Some(block.parent_hash),
+            (None, None) => true,
+        };
+
+        if !extends {
+            return self.reorg(block).await;
+        }
+
+        self.chain.push_back(block.header());
+        self.events.push_back(BlockEvent::NewBlock(block));
+        Ok(())
+    }
+
+    /// Walks back from `block` through parent hashes until its ancestry meets
+    /// the followed chain
+    async fn reorg(&mut self, block: Block) -> Result<(), RpcError> {
+        let mut added = VecDeque::from([block]);
+
+        let keep = loop {
+            let oldest = &added[0];
+            let number = oldest.number.to::<u64>();
+            let parent_hash = oldest.parent_hash;
+
+            let known = number.checked_sub(1).and_then(|parent| self.header_at(parent));
+            match known {
+                Some(parent) if parent.hash == Some(parent_hash) => {
+                    break (parent.number - self.chain[0].number + 1) as usize;
+                },
+                Some(_) if added.len() < self.config.max_reorg_depth => {
+                    let parent = self
+                        .config
+                        .client
+                        .get_block_with_hash(parent_hash, self.config.full_transactions)
+                        .await?
+                        .filter(|parent| parent.number.to::<u64>() + 1 == number)
+                        .ok_or_else(|| {
+                            RpcError::Response(format!(
+                                "Parent {} of block {} not found",
+                                parent_hash, number
+                            ))
+                        })?;
+                    added.push_front(parent);
+                },
+                // Lost track of the chain, follow the new fork from here on
+                _ => break 0,
+            }
+        };
+
+        let dropped: Vec<BlockHeader> = self.chain.drain(keep..).collect();
+        self.chain.extend(added.iter().map(Block::header));
+        self.events.push_back(BlockEvent::Reorg {
+            dropped,
+            added: added.into(),
+        });
+        Ok(())
+    }
+
+    async fn finalize(&mut self, head: u64) -> Result<(), RpcError> {
+        let client = &self.config.client;
+        let finalized = match self.config.confirmation {
+            Confirmation::Depth(depth) => head.checked_sub(depth),
+            Confirmation::Safe => client
+                .get_block_header_with_tag("safe", false)
+                .await?
+                .map(|header| header.number),
+            Confirmation::Finalized => client
+                .get_block_header_with_tag("finalized", false)
+                .await?
+                .map(|header| header.number),
+        };
+        // While catching up only the blocks followed so far can be finalized,
+        // the rest get their event once they are fetched
+        let Some(finalized) = finalized.zip(self.chain.back()).map(|(n, tip)| n.min(tip.number))
+        else {
+            return Ok(());
+        };
+
+        let last = self.finalized;
+        for header in &self.chain {
+            if header.number <= finalized && last.is_none_or(|last| header.number > last) {
+                self.events.push_back(BlockEvent::Finalized(header.clone()));
+            }
+        }
+        self.finalized = Some(last.map_or(finalized, |last| last.max(finalized)));
+
+        // Blocks below the finalized one can no longer change, it stays as the
+        // anchor new blocks link to
+        while self.chain.len() > 1 && self.chain[1].number <= finalized {
+            self.chain.pop_front();
+        }
+        Ok(())
+    }
+}
@@ -1,23 +1,27 @@
 use async_trait::async_trait;
+use bytes::Bytes;
 use reqwest::{Client, ClientBuilder};
 use std::time::Duration;
 use tracing::{debug, info};
 
-use crate::{hyper_rpc::Transport, RpcError};
+use crate::{
+    transport::{url::SharedUrl, RpcUrl, Transport},
+    RpcError,
+};
 
 /// Direct Reqwest transport that creates fresh clients - no pooling or caching
 #[derive(Debug, Clone)]
 pub struct DirectReqwestTransport {
-    url: String,
+    url: SharedUrl,
 }
 
 impl DirectReqwestTransport {
-    pub fn new(url: &'static str) -> Self {
+    pub fn new(url: RpcUrl) -> Self {
         info!("Creating DirectReqwestTransport with fresh clients for honest benchmarking");
-        Self { url: url.to_string() }
+        Self { url: SharedUrl::new(url) }
     }
 
-    async fn execute_request(&self, request: &[u8]) -> Result<Vec<u8>, RpcError> {
+    async fn execute_request(&self, request: Bytes) -> Result<Bytes, RpcError> {
         let start = std::time::Instant::now();
         
         debug!("Creating fresh reqwest client for direct transport request");
@@ -29,45 +33,54 @@ impl DirectReqwestTransport {
             .build()
             .map_err(|e| RpcError::Transport(format!("Failed to create reqwest client: {}", e)))?;
 
-        debug!("Making fresh HTTP request to {} (no connection reuse)", &self.url);
+        let url = self.url.get();
+        debug!("Making fresh HTTP request to {} (no connection reuse)", url);
         
-        let response = client
-            .post(&self.url)
-            .header("Content-Type", "application/json")
-            .body(request.to_vec())
+        let mut builder = client
+            .post(url.as_str())
+            .header("Content-Type", "application/json");
+        if let Some(authorization) = url.authorization() {
+            builder = builder.header(reqwest::header::AUTHORIZATION, authorization.clone());
+        }
+        let response = builder
+            .body(request)
             .send()
             .await
-            .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Err(RpcError::Transport(format!("HTTP error {}", response.status())));
-        }
+            .map_err(|e| RpcError::Connection(format!("Request failed: {}", e.without_url())))?;
 
+        let status = response.status();
         let bytes = response
             .bytes()
             .await
-            .map_err(|e| RpcError::Transport(format!("Failed to read response: {}", e)))?;
+            .map_err(|e| {
+                RpcError::Connection(format!("Failed to read response: {}", e.without_url()))
+            })?;
+
+        if !status.is_success() {
+            return Err(RpcError::Http {
+                status: status.as_u16(),
+                body: String::from_utf8_lossy(&bytes).into_owned(),
+            });
+        }
         
         let duration = start.elapsed();
         debug!("Fresh HTTP request completed in {:?} (no cached connections)", duration);
 
-        Ok(bytes.to_vec())
+        Ok(bytes)
     }
 }
 
 #[async_trait]
 impl Transport for DirectReqwestTransport {
-    async fn hyper_execute(&self, request: String) -> Result<String, RpcError> {
-        let response_bytes = self.execute_request(request.as_bytes()).await?;
-        String::from_utf8(response_bytes)
-            .map_err(|e| RpcError::Response(format!("Invalid UTF-8: {}", e)))
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.execute_request(request).await
     }
 
-    async fn hyper_execute_raw(&self, request: &'static [u8]) -> Result<Vec<u8>, RpcError> {
-        self.execute_request(request).await
+    fn endpoint(&self) -> Option<RpcUrl> {
+        Some(RpcUrl::clone(&self.url.get()))
     }
 
-    async fn hyper_execute_bytes(&self, request: Vec<u8>) -> Result<Vec<u8>, RpcError> {
-        self.execute_request(&request).await
+    fn set_endpoint(&self, url: RpcUrl) -> Result<(), RpcError> {
+        self.url.set(url)
     }
 }
\ No newline at end of file
@@ -6,23 +6,26 @@ use hyper_util::rt::TokioExecutor;
 use std::time::Duration;
 use tracing::{debug, info};
 
-use crate::{hyper_rpc::Transport, RpcError};
+use crate::{
+    transport::{url::SharedUrl, RpcUrl, Transport},
+    RpcError,
+};
 
 const CONTENT_TYPE_JSON: &str = "application/json";
 
 /// Direct HTTP transport that creates fresh connections - no pooling or caching
 #[derive(Debug, Clone)]
 pub struct DirectTransport {
-    url: String,
+    url: SharedUrl,
 }
 
 impl DirectTransport {
-    pub fn new(url: &'static str) -> Self {
+    pub fn new(url: RpcUrl) -> Self {
         info!("Creating DirectTransport with no connection pooling for honest benchmarking");
-        Self { url: url.to_string() }
+        Self { url: SharedUrl::new(url) }
     }
 
-    async fn execute_request(&self, request: &[u8]) -> Result<Vec<u8>, RpcError> {
+    async fn execute_request(&self, request: Bytes) -> Result<Bytes, RpcError> {
         let start = std::time::Instant::now();
         
         debug!("Creating fresh HTTP client for direct transport request");
@@ -49,52 +52,59 @@ impl DirectTransport {
             .pool_max_idle_per_host(0) // NO connection reuse at all for honest benchmarking
             .build(https_connector);
         
-        let body = Full::new(Bytes::copy_from_slice(request));
+        let body = Full::new(request);
         
-        let req = hyper::Request::builder()
+        let url = self.url.get();
+        let mut req = hyper::Request::builder()
             .method(hyper::Method::POST)
-            .uri(&self.url)
-            .header("Content-Type", CONTENT_TYPE_JSON)
+            .uri(url.uri().clone())
+            .header("Content-Type", CONTENT_TYPE_JSON);
+        if let Some(authorization) = url.authorization() {
+            req = req.header(hyper::header::AUTHORIZATION, authorization.clone());
+        }
+        let req = req
             .body(body)
             .map_err(|e| RpcError::Transport(format!("Failed to build request: {}", e)))?;
 
-        debug!("Making fresh HTTP request to {} (no connection reuse)", &self.url);
+        debug!("Making fresh HTTP request to {} (no connection reuse)", url);
         let response = client
             .request(req)
             .await
-            .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Err(RpcError::Transport(format!("HTTP error {}", response.status())));
-        }
+            .map_err(|e| RpcError::Connection(format!("Request failed: {}", e)))?;
 
+        let status = response.status();
         let body = response.into_body();
         let body_bytes = body
             .collect()
             .await
-            .map_err(|e| RpcError::Transport(e.to_string()))?
+            .map_err(|e| RpcError::Connection(e.to_string()))?
             .to_bytes();
+
+        if !status.is_success() {
+            return Err(RpcError::Http {
+                status: status.as_u16(),
+                body: String::from_utf8_lossy(&body_bytes).into_owned(),
+            });
+        }
         
         let duration = start.elapsed();
         debug!("Fresh HTTP request completed in {:?} (no cached connections)", duration);
 
-        Ok(body_bytes.into())
+        Ok(body_bytes)
     }
 }
 
 #[async_trait]
 impl Transport for DirectTransport {
-    async fn hyper_execute(&self, request: String) -> Result<String, RpcError> {
-        let response_bytes = self.execute_request(request.as_bytes()).await?;
-        String::from_utf8(response_bytes)
-            .map_err(|e| RpcError::Response(format!("Invalid UTF-8: {}", e)))
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.execute_request(request).await
     }
 
-    async fn hyper_execute_raw(&self, request: &'static [u8]) -> Result<Vec<u8>, RpcError> {
-        self.execute_request(request).await
+    fn endpoint(&self) -> Option<RpcUrl> {
+        Some(RpcUrl::clone(&self.url.get()))
     }
 
-    async fn hyper_execute_bytes(&self, request: Vec<u8>) -> Result<Vec<u8>, RpcError> {
-        self.execute_request(&request).await
+    fn set_endpoint(&self, url: RpcUrl) -> Result<(), RpcError> {
+        self.url.set(url)
     }
 }
\ No newline at end of file
@@ -0,0 +1,136 @@
+//! Engine API client for consensus side tooling. The Engine API is served on
+//! the execution client's authenticated port, build the transport with
+//! `TransportBuilder::auth` and a `JwtAuth` loaded from the node's secret
+//! file
+
+use alloy::primitives::{Bytes, B256};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::{
+    hyper_rpc::{RpcClient, RpcRequest},
+    parser::{
+        lib::top_level_field,
+        types::{
+            BlobAndProof, ExecutionPayload, ExecutionPayloadEnvelope, ForkchoiceState,
+            ForkchoiceUpdated, PayloadAttributes, PayloadId, PayloadStatus,
+        },
+    },
+    RpcError,
+};
+
+/// Methods this client implements, what `exchange_capabilities` announces by
+/// default
+pub const CAPABILITIES: [&str; 7] = [
+    "engine_newPayloadV3",
+    "engine_newPayloadV4",
+    "engine_forkchoiceUpdatedV3",
+    "engine_getPayloadV3",
+    "engine_getPayloadV4",
+    "engine_exchangeCapabilities",
+    "engine_getBlobsV1",
+];
+
+#[derive(Debug, Clone)]
+pub struct EngineClient {
+    client: RpcClient,
+}
+
+impl EngineClient {
+    pub fn new(client: RpcClient) -> Self {
+        Self { client }
+    }
+
+    pub fn client(&self) -> &RpcClient {
+        &self.client
+    }
+
+    /// Hands a Cancun payload to the node for validation
+    pub async fn new_payload_v3(
+        &self,
+        payload: &ExecutionPayload,
+        versioned_hashes: &[B256],
+        parent_beacon_block_root: B256,
+    ) -> Result<PayloadStatus, RpcError> {
+        let params = json!([payload, versioned_hashes, parent_beacon_block_root]);
+        self.call("engine_newPayloadV3", params).await
+    }
+
+    /// Prague `new_payload_v3`, with the EIP-7685 requests of the block
+    pub async fn new_payload_v4(
+        &self,
+        payload: &ExecutionPayload,
+        versioned_hashes: &[B256],
+        parent_beacon_block_root: B256,
+        execution_requests: &[Bytes],
+    ) -> Result<PayloadStatus, RpcError> {
+        let params = json!([
+            payload,
+            versioned_hashes,
+            parent_beacon_block_root,
+            execution_requests
+        ]);
+        self.call("engine_newPayloadV4", params).await
+    }
+
+    /// Moves the head, safe and finalized blocks. With `attributes` the node
+    /// also starts building a payload on the new head
+    pub async fn forkchoice_updated_v3(
+        &self,
+        state: &ForkchoiceState,
+        attributes: Option<&PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated, RpcError> {
+        let params = json!([state, attributes]);
+        self.call("engine_forkchoiceUpdatedV3", params).await
+    }
+
+    pub async fn get_payload_v3(
+        &self,
+        id: PayloadId,
+    ) -> Result<ExecutionPayloadEnvelope, RpcError> {
+        self.call("engine_getPayloadV3", json!([id])).await
+    }
+
+    pub async fn get_payload_v4(
+        &self,
+        id: PayloadId,
+    ) -> Result<ExecutionPayloadEnvelope, RpcError> {
+        self.call("engine_getPayloadV4", json!([id])).await
+    }
+
+    /// Announces `capabilities` and returns the methods the node supports,
+    /// e.g. [`CAPABILITIES`]
+    pub async fn exchange_capabilities(
+        &self,
+        capabilities: &[&str],
+    ) -> Result<Vec<String>, RpcError> {
+        self.call("engine_exchangeCapabilities", json!([capabilities])).await
+    }
+
+    /// Blobs in the node's pool by versioned hash, `None` for the ones it
+    /// does not hold
+    pub async fn get_blobs_v1(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProof>>, RpcError> {
+        self.call("engine_getBlobsV1", json!([versioned_hashes])).await
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &'static str,
+        params: Value,
+    ) -> Result<T, RpcError> {
+        let request = RpcRequest {
+            jsonrpc: "2.0",
+            method,
+            params,
+            id: 1,
+        };
+        let response = self.client.execute_raw(request).await?;
+
+        let (start, end) = top_level_field(&response, b"result")
+            .ok_or_else(|| RpcError::Response(format!("No result in {} response", method)))?;
+        serde_json::from_slice(&response[start..end]).map_err(|e| RpcError::Parse(e.to_string()))
+    }
+}
@@ -0,0 +1,260 @@
+//! Fee suggestions for EIP-1559 and blob transactions built from recent
+//! blocks
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use alloy::primitives::{B256, U256, U64};
+
+use crate::{hyper_rpc::RpcClient, parser::types::BlockHeader, RpcError};
+
+pub const DEFAULT_BLOCK_COUNT: u64 = 20;
+pub const DEFAULT_PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+/// Room for the base fee to double, about six full blocks in a row
+pub const DEFAULT_BASE_FEE_HEADROOM: u64 = 100;
+
+// EIP-1559
+const ELASTICITY_MULTIPLIER: u64 = 2;
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Fees to put in a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSuggestion {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    /// `None` before Cancun
+    pub max_fee_per_blob_gas: Option<U256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// Block the estimate was built on
+    pub block: u64,
+    /// Base fee of the next block
+    pub base_fee_per_gas: U256,
+    /// Blob base fee of the next block, `None` before Cancun
+    pub base_fee_per_blob_gas: Option<U256>,
+    pub slow: FeeSuggestion,
+    pub normal: FeeSuggestion,
+    pub fast: FeeSuggestion,
+}
+
+/// Priority fees paid in a block at each percentile, `None` for blocks
+/// without transactions
+type Sample = Option<[U256; 3]>;
+
+#[derive(Debug, Default)]
+struct Samples {
+    by_block: BTreeMap<u64, Sample>,
+    /// Number and hash of the newest sampled block
+    head: Option<(u64, Option<B256>)>,
+    /// Blob base fee of the block after the newest sampled one, as
+    /// `eth_feeHistory` reports it
+    next_blob_base_fee: Option<(u64, U256)>,
+}
+
+/// Suggests fees from the priority fees paid in the last `block_count` blocks,
+/// `eth_maxPriorityFeePerGas` and the base fee the latest header implies. The
+/// blob base fee is the one `eth_feeHistory` reports for the next block, so it
+/// follows whatever blob parameters the node's fork uses. Per-block samples
+/// are kept between calls, so only blocks not seen yet go through
+/// `eth_feeHistory`.
+#[derive(Debug, Clone)]
+pub struct FeeOracle {
+    client: RpcClient,
+    block_count: u64,
+    percentiles: [f64; 3],
+    base_fee_headroom: u64,
+    samples: Arc<Mutex<Samples>>,
+}
+
+impl FeeOracle {
+    pub fn new(client: RpcClient) -> Self {
+        Self {
+            client,
+            block_count: DEFAULT_BLOCK_COUNT,
+            percentiles: DEFAULT_PERCENTILES,
+            base_fee_headroom: DEFAULT_BASE_FEE_HEADROOM,
+            samples: Arc::default(),
+        }
+    }
+
+    /// Number of recent blocks sampled
+    pub fn block_count(mut self, count: u64) -> Self {
+        self.block_count = count.max(1);
+        self
+    }
+
+    /// Reward percentiles behind the slow, normal and fast tips
+    pub fn percentiles(mut self, slow: f64, normal: f64, fast: f64) -> Self {
+        self.percentiles = [slow, normal, fast];
+        // Samples taken at other percentiles no longer apply
+        self.samples = Arc::default();
+        self
+    }
+
+    /// Percent added on top of the next base fee, and blob base fee, in
+    /// `max_fee_per_gas` and `max_fee_per_blob_gas`
+    pub fn base_fee_headroom(mut self, percent: u64) -> Self {
+        self.base_fee_headroom = percent;
+        self
+    }
+
+    pub async fn estimate(&self) -> Result<FeeEstimate, RpcError> {
+        let (header, node_tip) = futures::try_join!(
+            self.client.get_block_header_with_tag("latest", false),
+            self.client.get_max_priority_fee_per_gas(),
+        )?;
+        let header = header.ok_or_else(|| RpcError::Response("No latest block".into()))?;
+        let base_fee = next_base_fee(&header)
+            .ok_or_else(|| RpcError::Response("Latest block has no base fee".into()))?;
+
+        self.sample(&header).await?;
+        let blob_base_fee = self.next_blob_base_fee(&header);
+        let tips = self.tips(header.number, node_tip);
+
+        let headroom = |fee: U256| fee * U256::from(100 + self.base_fee_headroom) / U256::from(100);
+        let suggestion = |tip: U256| FeeSuggestion {
+            max_fee_per_gas: headroom(base_fee) + tip,
+            max_priority_fee_per_gas: tip,
+            max_fee_per_blob_gas: blob_base_fee.map(headroom),
+        };
+
+        Ok(FeeEstimate {
+            block: header.number,
+            base_fee_per_gas: base_fee,
+            base_fee_per_blob_gas: blob_base_fee,
+            slow: suggestion(tips[0]),
+            normal: suggestion(tips[1]),
+            fast: suggestion(tips[2]),
+        })
+    }
+
+    /// Fetches the samples missing from the window ending at `header`
+    async fn sample(&self, header: &BlockHeader) -> Result<(), RpcError> {
+        let head = header.number;
+        let oldest = (head + 1).saturating_sub(self.block_count);
+        let missing = {
+            let mut guard = self.samples.lock().unwrap();
+            // A different block at or below the sampled head, or a parent
+            // other than it, means the samples came from a dropped chain
+            let reorged = match guard.head {
+                Some((number, hash)) if number == head => hash != header.hash,
+                Some((number, hash)) if number + 1 == head => hash != Some(header.parent_hash),
+                Some((number, _)) => number > head,
+                None => false,
+            };
+            let samples = &mut guard.by_block;
+            if reorged {
+                samples.clear();
+            }
+            // Drop what fell out of the window
+            *samples = samples.split_off(&oldest);
+            match samples.last_key_value() {
+                Some((&last, _)) => head - last,
+                None => head - oldest + 1,
+            }
+        };
+        if missing == 0 {
+            return Ok(());
+        }
+
+        let history = self
+            .client
+            .fee_history(U64::from(missing), U64::from(head), &self.percentiles)
+            .await?;
+
+        let first = history.oldest_block.to::<u64>();
+        let mut samples = self.samples.lock().unwrap();
+        for (i, ratio) in history.gas_used_ratio.iter().enumerate() {
+            let sample = match history.reward.get(i) {
+                Some(row) if *ratio > 0.0 && row.len() == 3 => Some([row[0], row[1], row[2]]),
+                _ => None,
+            };
+            samples.by_block.insert(first + i as u64, sample);
+        }
+        // One entry per block plus the next one
+        samples.next_blob_base_fee = history.base_fee_per_blob_gas.last().map(|&fee| (head, fee));
+        samples.head = Some((head, header.hash));
+        Ok(())
+    }
+
+    /// Blob base fee of the block after `header`, `None` before Cancun or when
+    /// the node left it out of `eth_feeHistory`
+    fn next_blob_base_fee(&self, header: &BlockHeader) -> Option<U256> {
+        header.excess_blob_gas?;
+        match self.samples.lock().unwrap().next_blob_base_fee {
+            Some((block, fee)) if block == header.number => Some(fee),
+            _ => None,
+        }
+    }
+
+    /// Median tip at each percentile across the sampled blocks.
+    /// `eth_maxPriorityFeePerGas` stands in when they were all empty and
+    /// floors the normal and fast tips
+    fn tips(&self, head: u64, node_tip: U256) -> [U256; 3] {
+        let samples = &self.samples.lock().unwrap().by_block;
+        let rows: Vec<&[U256; 3]> =
+            samples.range(..=head).filter_map(|(_, s)| s.as_ref()).collect();
+
+        let mut tips = [node_tip; 3];
+        if !rows.is_empty() {
+            for (i, tip) in tips.iter_mut().enumerate() {
+                let mut column: Vec<U256> = rows.iter().map(|row| row[i]).collect();
+                column.sort_unstable();
+                *tip = column[column.len() / 2];
+            }
+            tips[1] = tips[1].max(node_tip);
+        }
+
+        // Faster never pays less
+        tips[1] = tips[1].max(tips[0]);
+        tips[2] = tips[2].max(tips[1]);
+        tips
+    }
+}
+
+/// Base fee of the block after `header`, `None` before London
+pub fn next_base_fee(header: &BlockHeader) -> Option<U256> {
+    let base_fee = header.base_fee_per_gas?;
+    let target = header.gas_limit / U256::from(ELASTICITY_MULTIPLIER);
+    let used = header.gas_used;
+    if target.is_zero() || used == target {
+        return Some(base_fee);
+    }
+
+    let denominator = target * U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+    Some(if used > target {
+        let delta = base_fee * (used - target) / denominator;
+        base_fee + delta.max(U256::from(1))
+    } else {
+        base_fee - base_fee * (target - used) / denominator
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(base_fee: u64, gas_used: u64) -> BlockHeader {
+        BlockHeader {
+            base_fee_per_gas: Some(U256::from(base_fee)),
+            gas_limit: U256::from(30_000_000),
+            gas_used: U256::from(gas_used),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_next_base_fee() {
+        assert_eq!(next_base_fee(&header(1_000, 15_000_000)), Some(U256::from(1_000)));
+        // Full and empty blocks move it by an eighth
+        assert_eq!(next_base_fee(&header(1_000, 30_000_000)), Some(U256::from(1_125)));
+        assert_eq!(next_base_fee(&header(1_000, 0)), Some(U256::from(875)));
+        // At least one wei up
+        assert_eq!(next_base_fee(&header(1, 15_000_001)), Some(U256::from(2)));
+        assert_eq!(next_base_fee(&BlockHeader::default()), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Canonical headers seen by the client, answering number/hash lookups
+//! without a round trip
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::RwLock,
+};
+
+use alloy::primitives::B256;
+
+use crate::parser::types::BlockHeader;
+
+/// Blocks whose hash changed under the store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorg {
+    /// First height whose stored hash is no longer canonical
+    pub number: u64,
+    /// Hashes dropped from the store, ascending by number
+    pub dropped: Vec<B256>,
+}
+
+#[derive(Debug, Default)]
+struct Canonical {
+    hashes: BTreeMap<u64, B256>,
+    headers: HashMap<B256, BlockHeader>,
+}
+
+/// Thread-safe window of the canonical chain keeping the `depth` highest
+/// headers. A header that disagrees with a stored one, by hash at its height
+/// or by parent link, replaces it and everything stored above it
+#[derive(Debug)]
+pub struct HeaderStore {
+    depth: usize,
+    inner: RwLock<Canonical>,
+}
+
+impl Default for HeaderStore {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DEPTH)
+    }
+}
+
+impl HeaderStore {
+    pub const DEFAULT_DEPTH: usize = 256;
+
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth: depth.max(1),
+            inner: RwLock::new(Canonical::default()),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Records `header` as canonical, returning the reorg it reveals if any.
+    /// Headers without a hash (pending blocks) are ignored
+    pub fn insert(&self, header: BlockHeader) -> Option<Reorg> {
+        let hash = header.hash?;
+        let number = header.number;
+        let mut inner = self.inner.write().unwrap();
+
+        let stale_from = match inner.hashes.get(&number) {
+            Some(&stored) if stored == hash => return None,
+            Some(_) => Some(number),
+            None => {
+                let parent = number.checked_sub(1).and_then(|n| inner.hashes.get(&n));
+                let child =
+                    inner.hashes.get(&(number + 1)).and_then(|child| inner.headers.get(child));
+                if parent.is_some_and(|&parent| parent != header.parent_hash) {
+                    Some(number - 1)
+                } else if child.is_some_and(|child| child.parent_hash != hash) {
+                    Some(number + 1)
+                } else {
+                    None
+                }
+            },
+        };
+
+        let reorg = stale_from.map(|stale_from| {
+            let dropped: Vec<B256> = inner.hashes.split_off(&stale_from).into_values().collect();
+            for hash in &dropped {
+                inner.headers.remove(hash);
+            }
+            Reorg {
+                number: stale_from,
+                dropped,
+            }
+        });
+
+        inner.hashes.insert(number, hash);
+        inner.headers.insert(hash, header);
+        while inner.hashes.len() > self.depth {
+            if let Some((_, oldest)) = inner.hashes.pop_first() {
+                inner.headers.remove(&oldest);
+            }
+        }
+
+        reorg
+    }
+
+    pub fn hash_of(&self, number: u64) -> Option<B256> {
+        self.inner.read().unwrap().hashes.get(&number).copied()
+    }
+
+    pub fn number_of(&self, hash: &B256) -> Option<u64> {
+        self.inner.read().unwrap().headers.get(hash).map(|header| header.number)
+    }
+
+    pub fn header_by_number(&self, number: u64) -> Option<BlockHeader> {
+        let inner = self.inner.read().unwrap();
+        inner.hashes.get(&number).and_then(|hash| inner.headers.get(hash)).cloned()
+    }
+
+    pub fn header_by_hash(&self, hash: &B256) -> Option<BlockHeader> {
+        self.inner.read().unwrap().headers.get(hash).cloned()
+    }
+
+    /// Highest stored header
+    pub fn tip(&self) -> Option<BlockHeader> {
+        let inner = self.inner.read().unwrap();
+        inner
+            .hashes
+            .values()
+            .next_back()
+            .and_then(|hash| inner.headers.get(hash))
+            .cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.hashes.clear();
+        inner.headers.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(number: u64, hash: u8, parent: u8) -> BlockHeader {
+        BlockHeader {
+            number,
+            hash: Some(B256::repeat_byte(hash)),
+            parent_hash: B256::repeat_byte(parent),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_lookups_and_depth() {
+        let store = HeaderStore::new(2);
+        assert_eq!(store.insert(header(1, 1, 0)), None);
+        assert_eq!(store.insert(header(2, 2, 1)), None);
+        assert_eq!(store.insert(header(3, 3, 2)), None);
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.hash_of(1), None);
+        assert_eq!(store.hash_of(3), Some(B256::repeat_byte(3)));
+        assert_eq!(store.number_of(&B256::repeat_byte(2)), Some(2));
+        assert_eq!(store.tip().unwrap().number, 3);
+        // Pending blocks carry no hash
+        assert_eq!(store.insert(BlockHeader::default()), None);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_new_hash_at_known_height_is_a_reorg() {
+        let store = HeaderStore::default();
+        store.insert(header(1, 1, 0));
+        store.insert(header(2, 2, 1));
+        store.insert(header(3, 3, 2));
+        // Seeing the same block again changes nothing
+        assert_eq!(store.insert(header(2, 2, 1)), None);
+
+        assert_eq!(
+            store.insert(header(2, 0x22, 1)),
+            Some(Reorg {
+                number: 2,
+                dropped: vec![B256::repeat_byte(2), B256::repeat_byte(3)],
+            })
+        );
+        assert_eq!(store.hash_of(2), Some(B256::repeat_byte(0x22)));
+        assert_eq!(store.hash_of(3), None);
+        assert!(store.header_by_hash(&B256::repeat_byte(3)).is_none());
+    }
+
+    #[test]
+    fn test_broken_parent_link_is_a_reorg() {
+        let store = HeaderStore::default();
+        store.insert(header(1, 1, 0));
+        store.insert(header(2, 2, 1));
+
+        assert_eq!(
+            store.insert(header(3, 0x33, 0x22)),
+            Some(Reorg {
+                number: 2,
+                dropped: vec![B256::repeat_byte(2)],
+            })
+        );
+        assert_eq!(store.hash_of(1), Some(B256::repeat_byte(1)));
+        assert_eq!(store.tip().unwrap().hash, Some(B256::repeat_byte(0x33)));
+    }
+}
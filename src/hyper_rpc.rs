@@ -1,5 +1,5 @@
 use std::{
-    any::Any,
+    collections::VecDeque,
     str::FromStr,
     sync::Arc,
     time::{Duration, Instant},
@@ -9,27 +9,31 @@ use alloy::{
     hex,
     primitives::{Address, BlockNumber, Bytes, FixedBytes, B256, U256, U64},
 };
-use async_trait::async_trait;
-use parser::types::{FilterParams, TransactionRequest};
+use futures::{future, Stream, StreamExt};
+use parser::types::{CallOverrides, FilterParams, TransactionRequest};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{json, Value};
 
 use super::*;
-use crate::parser::{
-    block_parser::parse_block,
-    lib::{hex_to_b256, hex_to_u256, hex_to_u64},
-    parser_for_small_response::Generic,
-    tx_parser::parse_transaction,
-    types::{Block, BlockHeader, Log, RawJsonResponse, TransactionTx},
+use crate::{
+    header_store::{HeaderStore, Reorg},
+    parser::{
+        block_parser::{parse_block, parse_header, RawBlock},
+        lib::{hex_to_b256, quantity_to_u256, quantity_to_u64, split_array, top_level_field},
+        log_parser::parse_log,
+        parser_for_small_response::{parse_fee_history, Generic},
+        proof_parser::parse_proof,
+        receipt_parser::{parse_block_receipts, parse_receipt},
+        tx_parser::parse_transaction,
+        types::{
+            AccountProof, Block, BlockHeader, FeeHistory, Log, RawJsonResponse, Receipt,
+            TransactionTx,
+        },
+    },
+    transport::{IntoRpcUrl, RpcUrl, Transport},
+    verify::VerificationError,
 };
 
-#[async_trait]
-pub trait Transport: Send + Sync + std::fmt::Debug + Any {
-    async fn hyper_execute_raw(&self, request: &'static [u8]) -> Result<Vec<u8>, RpcError>;
-    async fn hyper_execute(&self, request: String) -> Result<String, RpcError>;
-    async fn hyper_execute_bytes(&self, request: Vec<u8>) -> Result<Vec<u8>, RpcError>;
-}
-
 pub enum BlockIdentifier {
     Hash(B256),
     Number(u64),
@@ -46,8 +50,13 @@ struct BatchingStats {
 pub struct RpcClient {
     pub transport: Arc<dyn Transport>,
     batching_stats: Arc<std::sync::Mutex<BatchingStats>>,
+    headers: Arc<HeaderStore>,
+    reorgs: Arc<std::sync::Mutex<VecDeque<Reorg>>>,
 }
 
+/// Reorgs kept for [`RpcClient::take_reorgs`], older ones are dropped first
+const MAX_PENDING_REORGS: usize = 64;
+
 /// Represents an RPC request to a Ethereum node
 #[derive(Debug, Clone, Serialize)]
 pub struct RpcRequest {
@@ -66,9 +75,52 @@ impl RpcClient {
                 last_batch_time: Duration::from_millis(500),
                 samples: Vec::new(),
             })),
+            headers: Arc::new(HeaderStore::default()),
+            reorgs: Arc::new(std::sync::Mutex::new(VecDeque::new())),
         }
     }
 
+    /// Shares `store` with this client, e.g. to keep a deeper window than
+    /// [`HeaderStore::DEFAULT_DEPTH`]
+    pub fn with_header_store(mut self, store: Arc<HeaderStore>) -> Self {
+        self.headers = store;
+        self
+    }
+
+    /// Headers of every block this client has parsed, lookups here cost no
+    /// round trip. Headers are stored as the node sent them, only
+    /// `HeaderChain::fetch` checks them against their hashes first
+    pub fn header_store(&self) -> &Arc<HeaderStore> {
+        &self.headers
+    }
+
+    /// Reorgs the header store detected since the last call, oldest first.
+    /// At most the last 64 are kept between calls
+    pub fn take_reorgs(&self) -> Vec<Reorg> {
+        self.reorgs.lock().unwrap().drain(..).collect()
+    }
+
+    /// Url requests currently go to, `None` for IPC
+    pub fn endpoint(&self) -> Option<RpcUrl> {
+        self.transport.endpoint()
+    }
+
+    /// Points this client, and every clone of it, at `url`, e.g. to rotate an
+    /// API key. Requests in flight finish against the old url. Only HTTP
+    /// transports support this. The header store is kept, so `url` should
+    /// serve the same chain
+    pub fn set_endpoint(&self, url: impl IntoRpcUrl) -> Result<(), RpcError> {
+        self.transport.set_endpoint(url.into_rpc_url()?)
+    }
+
+    fn record_block(&self, block: &Block) {
+        self.record_header(block.header());
+    }
+
+    pub(crate) fn record_header(&self, header: BlockHeader) {
+        record_header(&self.headers, &self.reorgs, header);
+    }
+
     pub async fn get_chain_id(&self) -> Result<U64, RpcError> {
         let request = RpcRequest {
             jsonrpc: "2.0",
@@ -82,7 +134,7 @@ impl RpcClient {
         match Generic::parse(&response) {
             Some(generic) => {
                 let bytes = &response[generic.result_start.0..generic.result_start.1];
-                Ok(hex_to_u64(&bytes[2..]))
+                Ok(quantity_to_u64(bytes))
             },
             None => Err(RpcError::Response("Failed to parse chain ID".into())),
         }
@@ -101,7 +153,7 @@ impl RpcClient {
         match Generic::parse(&response) {
             Some(generic) => {
                 let bytes = &response[generic.result_start.0..generic.result_start.1];
-                Ok(hex_to_u256(&bytes[2..]))
+                Ok(quantity_to_u256(bytes))
             },
             None => Err(RpcError::Response("Failed to parse gas price".into())),
         }
@@ -120,7 +172,7 @@ impl RpcClient {
         match Generic::parse(&response) {
             Some(generic) => {
                 let bytes = &response[generic.result_start.0..generic.result_start.1];
-                Ok(hex_to_u256(&bytes[2..]))
+                Ok(quantity_to_u256(bytes))
             },
             None => Err(RpcError::Response("Failed to parse max priority fee".into())),
         }
@@ -139,7 +191,7 @@ impl RpcClient {
         match Generic::parse(&response) {
             Some(generic) => {
                 let bytes = &response[generic.result_start.0..generic.result_start.1];
-                Ok(hex_to_u64(&bytes[2..]))
+                Ok(quantity_to_u64(bytes))
             },
             None => Err(RpcError::Response("Failed to parse block number".into())),
         }
@@ -256,7 +308,34 @@ impl RpcClient {
         let response_bytes: Vec<u8> = self.execute_raw(request).await?;
 
         match parse_block(&response_bytes) {
-            Some(block) => Ok(Some(block)),
+            Some(block) => {
+                self.record_block(&block);
+                Ok(Some(block))
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Block with `hash`, parsed like `get_block_by_number`
+    pub(crate) async fn get_block_with_hash(
+        &self,
+        hash: B256,
+        full_tx: bool,
+    ) -> Result<Option<Block>, RpcError> {
+        let request = RpcRequest {
+            jsonrpc: "2.0",
+            method: "eth_getBlockByHash",
+            params: json!([format!("0x{:x}", hash), full_tx]),
+            id: 1,
+        };
+
+        let response_bytes: Vec<u8> = self.execute_raw(request).await?;
+
+        match parse_block(&response_bytes) {
+            Some(block) => {
+                self.record_block(&block);
+                Ok(Some(block))
+            },
             None => Ok(None),
         }
     }
@@ -267,6 +346,19 @@ impl RpcClient {
         &self,
         numbers: Vec<u64>,
         full_tx: bool,
+    ) -> Result<Vec<Option<Block>>, RpcError> {
+        let blocks = self.fetch_blocks_by_numbers(numbers, full_tx).await?;
+        for block in blocks.iter().flatten() {
+            self.record_block(block);
+        }
+        Ok(blocks)
+    }
+
+    /// `get_blocks_by_numbers` without recording the headers
+    pub(crate) async fn fetch_blocks_by_numbers(
+        &self,
+        numbers: Vec<u64>,
+        full_tx: bool,
     ) -> Result<Vec<Option<Block>>, RpcError> {
         if numbers.is_empty() {
             return Ok(Vec::new());
@@ -284,29 +376,30 @@ impl RpcClient {
             .collect();
 
         let response_bytes = self.execute_batch_raw(requests).await?;
+        // Providers answer a whole batch with one error object, e.g. when rate
+        // limiting
+        if response_bytes.iter().find(|b| !b.is_ascii_whitespace()) != Some(&b'[') {
+            return Err(RpcError::from_response(&response_bytes)
+                .unwrap_or_else(|| RpcError::Response("Expected a batch response".into())));
+        }
 
-        // Parse batch response - it should be a JSON array
-        let batch_results: Vec<serde_json::Value> = serde_json::from_slice(&response_bytes)
-            .map_err(|e| RpcError::Parse(format!("Failed to parse batch response: {}", e)))?;
-
-        let mut blocks = Vec::with_capacity(numbers.len());
-
-        for result in batch_results {
-            if let Some(result_data) = result.get("result") {
-                if result_data.is_null() {
-                    blocks.push(None);
-                } else {
-                    // Convert back to bytes for our zero-copy parser
-                    let result_bytes = serde_json::to_vec(result_data)
-                        .map_err(|e| RpcError::Parse(e.to_string()))?;
-
-                    match parse_block(&result_bytes) {
-                        Some(block) => blocks.push(Some(block)),
-                        None => blocks.push(None),
-                    }
+        // Batch responses may come back in any order, ids map them to `numbers`
+        let mut blocks = vec![None; numbers.len()];
+        for (start, end) in split_array(&response_bytes) {
+            let item = &response_bytes[start..end];
+            if let Some(error) = RpcError::from_response(item) {
+                return Err(error);
+            }
+            let id = top_level_field(item, b"id")
+                .and_then(|(s, e)| std::str::from_utf8(&item[s..e]).ok()?.parse::<usize>().ok());
+            let Some(slot) = id.and_then(|id| blocks.get_mut(id.wrapping_sub(1))) else {
+                continue;
+            };
+
+            if let Some((s, e)) = top_level_field(item, b"result") {
+                if item[s] == b'{' {
+                    *slot = RawBlock::parse(&item[s..e]).and_then(|block| block.to_block());
                 }
-            } else {
-                blocks.push(None);
             }
         }
 
@@ -336,6 +429,7 @@ impl RpcClient {
         // FROM BENCHMARK CLONING HERE HAS NO EFFECT ON LATENCY(STUPID RIGHT????????)
         let block: BlockHeader = serde_json::from_value(response["result"].clone())
             .map_err(|e| RpcError::Response(e.to_string()))?;
+        self.record_header(block.clone());
 
         Ok(Some(block))
     }
@@ -364,6 +458,7 @@ impl RpcClient {
 
         let block: BlockHeader = serde_json::from_value(response["result"].clone())
             .map_err(|e| RpcError::Response(e.to_string()))?;
+        self.record_header(block.clone());
 
         Ok(Some(block))
     }
@@ -385,6 +480,7 @@ impl RpcClient {
         // Cloning does not affect latency here from benchmark
         let block: BlockHeader = serde_json::from_value(response["result"].clone())
             .map_err(|e| RpcError::Response(e.to_string()))?;
+        self.record_header(block.clone());
         Ok(Some(block))
     }
 
@@ -401,7 +497,7 @@ impl RpcClient {
         match Generic::parse(&response) {
             Some(generic) => {
                 let bytes = &response[generic.result_start.0..generic.result_start.1];
-                Ok(hex_to_u256(&bytes[2..]))
+                Ok(quantity_to_u256(bytes))
             },
             None => Err(RpcError::Response("Failed to parse balance".into())),
         }
@@ -467,7 +563,7 @@ impl RpcClient {
         match Generic::parse(&response) {
             Some(generic) => {
                 let bytes = &response[generic.result_start.0..generic.result_start.1];
-                Ok(hex_to_u64(&bytes[2..]))
+                Ok(quantity_to_u64(bytes))
             },
             None => Err(RpcError::Response("Failed to parse transaction count".into())),
         }
@@ -483,8 +579,9 @@ impl RpcClient {
     /// * `block` - Optional block number to simulate the transaction against
     ///
     /// # Returns
-    /// * `Result<U256, RpcError>` - The estimated gas as a U256 value,
-    ///   0(default for error)
+    /// * `Result<U256, RpcError>` - The estimated gas as a U256 value. A
+    ///   reverting transaction comes back as `RpcError::JsonRpc`, use
+    ///   `revert_reason` or a `RevertDecoder` on it to see why
     pub async fn estimate_gas(
         &self,
         tx: &TransactionRequest,
@@ -501,9 +598,9 @@ impl RpcClient {
         match Generic::parse(&response) {
             Some(generic) => {
                 let bytes = &response[generic.result_start.0..generic.result_start.1];
-                Ok(hex_to_u256(&bytes[2..]))
+                Ok(quantity_to_u256(bytes))
             },
-            None => Ok(U256::ZERO),
+            None => Err(RpcError::Response("Failed to parse gas estimate".into())),
         }
     }
 
@@ -519,7 +616,7 @@ impl RpcClient {
         match Generic::parse(&response) {
             Some(generic) => {
                 let bytes = &response[generic.result_start.0..generic.result_start.1];
-                Ok(hex_to_u256(&bytes[2..]))
+                Ok(quantity_to_u256(bytes))
             },
             None => Err(RpcError::Response("Failed to create filter".into())),
         }
@@ -537,7 +634,7 @@ impl RpcClient {
         match Generic::parse(&response) {
             Some(generic) => {
                 let bytes = &response[generic.result_start.0..generic.result_start.1];
-                Ok(hex_to_u256(&bytes[2..]))
+                Ok(quantity_to_u256(bytes))
             },
             None => Err(RpcError::Response("Failed to create block filter".into())),
         }
@@ -581,13 +678,12 @@ impl RpcClient {
         }
     }
 
-    /// ISSUE: MAKE THISS USE EXECUTE_RAW
     pub async fn fee_history(
         &self,
         block_count: U64,
         newest_block: U64,
         reward_percentiles: &[f64],
-    ) -> Result<Value, RpcError> {
+    ) -> Result<FeeHistory, RpcError> {
         let request = RpcRequest {
             jsonrpc: "2.0",
             method: "eth_feeHistory",
@@ -599,16 +695,20 @@ impl RpcClient {
             id: 1,
         };
 
-        self.execute(request).await
+        let response = self.execute_raw(request).await?;
+        parse_fee_history(&response)
+            .ok_or_else(|| RpcError::Response("Invalid fee history response".into()))
     }
 
-    /// ISSUE: MAKE THISS USE EXECUTE_RAW
+    /// Account and storage proofs, check them with
+    /// [`AccountProof::verify_against`] before trusting the values. Fails when
+    /// the node proves another account or other slots than the ones asked for
     pub async fn get_proof(
         &self,
         address: Address,
         storage_keys: &[B256],
         block: BlockNumber,
-    ) -> Result<Value, RpcError> {
+    ) -> Result<AccountProof, RpcError> {
         let request = RpcRequest {
             jsonrpc: "2.0",
             method: "eth_getProof",
@@ -620,7 +720,28 @@ impl RpcClient {
             id: 1,
         };
 
-        self.execute(request).await
+        let response = self.execute_raw(request).await?;
+        let proof = parse_proof(&response)
+            .ok_or_else(|| RpcError::Response("Invalid proof response".into()))?;
+
+        let invalid = |slot, reason: String| VerificationError::InvalidProof {
+            address,
+            slot,
+            reason,
+        };
+        if proof.address != address {
+            return Err(invalid(None, format!("node proved {} instead", proof.address)).into());
+        }
+        let proven: Vec<B256> = proof.storage_proof.iter().map(|slot| slot.key).collect();
+        if proven != storage_keys {
+            let slot = storage_keys.iter().zip(&proven).find(|(asked, got)| asked != got);
+            return Err(invalid(
+                slot.map(|(asked, _)| *asked),
+                format!("node proved slots {:?}, asked for {:?}", proven, storage_keys),
+            )
+            .into());
+        }
+        Ok(proof)
     }
 
     pub async fn send_raw_transaction(&self, data: Bytes) -> Result<B256, RpcError> {
@@ -631,10 +752,52 @@ impl RpcClient {
             id: 1,
         };
 
-        self.execute(request).await
+        let response = self.execute_raw(request).await.map_err(RpcError::into_tx_rejection)?;
+        match Generic::parse(&response) {
+            Some(generic) => {
+                let bytes = &response[generic.result_start.0..generic.result_start.1];
+                if bytes.len() != 66 {
+                    return Err(RpcError::Parse("Invalid transaction hash".into()));
+                }
+                Ok(hex_to_b256(bytes))
+            },
+            None => Err(RpcError::Response("Failed to parse transaction hash".into())),
+        }
+    }
+
+    /// Sends a raw transaction and waits until it is included, giving up with
+    /// `RpcError::Timeout` after `timeout`
+    pub async fn send_raw_transaction_sync(
+        &self,
+        data: Bytes,
+        timeout: Duration,
+    ) -> Result<Receipt, RpcError> {
+        let hash = self.send_raw_transaction(data).await?;
+        self.wait_for_receipt(hash, timeout).await
+    }
+
+    /// Polls for the receipt of `hash`, backing off from 100ms up to 2s
+    /// between attempts
+    pub async fn wait_for_receipt(&self, hash: B256, timeout: Duration) -> Result<Receipt, RpcError> {
+        let deadline = Instant::now() + timeout;
+        let mut interval = Duration::from_millis(100);
+
+        loop {
+            if let Some(receipt) = self.get_transaction_receipt(hash).await? {
+                return Ok(receipt);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(RpcError::Timeout(timeout));
+            }
+            tokio::time::sleep(interval.min(remaining)).await;
+            interval = (interval * 2).min(Duration::from_secs(2));
+        }
     }
 
-    pub async fn get_transaction_receipt(&self, hash: B256) -> Result<Value, RpcError> {
+    /// Receipt of a mined transaction, `None` while it is pending or unknown
+    pub async fn get_transaction_receipt(&self, hash: B256) -> Result<Option<Receipt>, RpcError> {
         let request = RpcRequest {
             jsonrpc: "2.0",
             method: "eth_getTransactionReceipt",
@@ -642,19 +805,97 @@ impl RpcClient {
             id: 1,
         };
 
-        self.execute(request).await
+        let response_bytes = self.execute_raw(request).await?;
+        Ok(parse_receipt(&response_bytes))
     }
 
-    // pub async fn get_eth_call()
+    /// `eth_call` at `block` (latest when `None`), optionally on top of a
+    /// state override set and block overrides. Returns the raw return data
+    pub async fn call(
+        &self,
+        tx: &TransactionRequest,
+        block: Option<BlockNumber>,
+        overrides: Option<&CallOverrides>,
+    ) -> Result<Bytes, RpcError> {
+        let block = block.map_or_else(|| "latest".to_string(), |b| format!("0x{:x}", b));
+        let mut params = vec![json!(tx), json!(block)];
+        if let Some(overrides) = overrides {
+            // Block overrides are positional, so an empty state set has to precede them
+            params.push(json!(overrides.state.clone().unwrap_or_default()));
+            if let Some(block_overrides) = &overrides.block {
+                params.push(json!(block_overrides));
+            }
+        }
+
+        let request = RpcRequest {
+            jsonrpc: "2.0",
+            method: "eth_call",
+            params: Value::Array(params),
+            id: 1,
+        };
+
+        let response = self.execute_raw(request).await?;
+        match Generic::parse(&response) {
+            Some(generic) => {
+                let bytes = &response[generic.result_start.0..generic.result_start.1];
+                hex::decode(bytes)
+                    .map(Bytes::from)
+                    .map_err(|e| RpcError::Parse(format!("Invalid call result: {}", e)))
+            },
+            None => Err(RpcError::Response("Failed to parse call result".into())),
+        }
+    }
 
-    pub async fn get_block_receipts(&self, block: BlockNumber) -> Result<Value, RpcError> {
+    /// All receipts of a block in transaction order, empty if the block is unknown
+    pub async fn get_block_receipts(&self, block: BlockNumber) -> Result<Vec<Receipt>, RpcError> {
         let request = RpcRequest {
             jsonrpc: "2.0",
             method: "eth_getBlockReceipts",
             params: json!([format!("0x{:x}", block)]),
             id: 1,
         };
-        self.execute(request).await
+
+        let response_bytes = self.execute_raw(request).await?;
+        Ok(parse_block_receipts(&response_bytes))
+    }
+
+    /// Streams the header of every new block the node imports. Headers are
+    /// parsed with the fast block parser, and the subscription is re-opened
+    /// by the transport after a reconnect. Each header is recorded in the
+    /// header store, reorgs it reveals show up in `take_reorgs`
+    pub async fn subscribe_new_heads(
+        &self,
+    ) -> Result<impl Stream<Item = BlockHeader> + Send + Unpin, RpcError> {
+        let subscription = self.transport.subscribe(json!(["newHeads"])).await?;
+        let (headers, reorgs) = (self.headers.clone(), self.reorgs.clone());
+        Ok(subscription.filter_map(move |payload| {
+            let header = parse_header(&payload);
+            if let Some(header) = &header {
+                record_header(&headers, &reorgs, header.clone());
+            }
+            future::ready(header)
+        }))
+    }
+
+    /// Streams logs matching `filter` as they are mined. Logs of blocks that
+    /// get reorged out are sent again with `removed` set
+    pub async fn subscribe_logs(
+        &self,
+        filter: &FilterParams,
+    ) -> Result<impl Stream<Item = Log> + Send + Unpin, RpcError> {
+        let subscription = self.transport.subscribe(json!(["logs", filter])).await?;
+        Ok(subscription.filter_map(|payload| future::ready(parse_log(&payload))))
+    }
+
+    /// Streams hashes of transactions entering the node's mempool
+    pub async fn subscribe_pending_transactions(
+        &self,
+    ) -> Result<impl Stream<Item = B256> + Send + Unpin, RpcError> {
+        let subscription = self.transport.subscribe(json!(["newPendingTransactions"])).await?;
+        Ok(subscription.filter_map(|payload| {
+            // "0x" + 64 hex digits, quoted
+            future::ready((payload.len() == 68).then(|| hex_to_b256(&payload[1..67])))
+        }))
     }
 
     pub async fn execute_raw(&self, request: RpcRequest) -> Result<Vec<u8>, RpcError> {
@@ -664,9 +905,12 @@ impl RpcClient {
             request.jsonrpc, request.method, request.params, request.id
         );
 
-        let response = self.transport.hyper_execute_bytes(json_request.into_bytes()).await?;
+        let response = self.transport.request(json_request.into()).await?;
 
-        Ok(response)
+        match RpcError::from_response(&response) {
+            Some(error) => Err(error),
+            None => Ok(response.into()),
+        }
     }
 
     /// Execute multiple RPC requests in a single batch call
@@ -676,7 +920,7 @@ impl RpcClient {
         let batch_size = requests.len();
 
         // Determine if we should use concurrent multiplexed requests or single batch
-        let use_multiplexing = {
+        let use_multiplexing = self.transport.supports_batch() && {
             let stats = self.batching_stats.lock().unwrap();
             // Use multiplexing for smaller batches where connection overhead is amortized
             batch_size <= 10 && stats.optimal_batch_size > batch_size * 2
@@ -684,61 +928,52 @@ impl RpcClient {
 
         if use_multiplexing {
             // Use connection multiplexing for concurrent individual requests
-            let individual_requests: Vec<Vec<u8>> = requests
+            let individual_requests: Vec<bytes::Bytes> = requests
                 .iter()
                 .map(|req| {
                     format!(
                         r#"{{"jsonrpc":"{}","method":"{}","params":{},"id":{}}}"#,
                         req.jsonrpc, req.method, req.params, req.id
                     )
-                    .into_bytes()
+                    .into()
                 })
                 .collect();
 
-            // Try to cast transport to HyperTransport to use batch multiplexing
-            if let Some(hyper_transport) = (&*self.transport as &dyn std::any::Any)
-                .downcast_ref::<crate::hyper_transport::HyperTransport>()
-            {
-                let results = hyper_transport
-                    .hyper_execute_bytes_batch(individual_requests)
-                    .await
-                    .map_err(|e| RpcError::Transport(format!("Multiplexed batch failed: {}", e)))?;
-
-                // Combine successful results into a JSON array
-                let mut response_array = Vec::new();
-                response_array.push(b'[');
-
-                let mut first = true;
-                for result in results {
-                    match result {
-                        Ok(response_bytes) => {
-                            if !first {
-                                response_array.push(b',');
-                            }
-                            response_array.extend_from_slice(&response_bytes);
-                            first = false;
-                        },
-                        Err(_) => {
-                            // Skip failed requests in multiplexed mode
-                            continue;
-                        },
-                    }
+            let results = self.transport.request_batch(individual_requests).await;
+
+            // Combine successful results into a JSON array
+            let mut response_array = Vec::new();
+            response_array.push(b'[');
+
+            let mut first = true;
+            for result in results {
+                match result {
+                    Ok(response_bytes) => {
+                        if !first {
+                            response_array.push(b',');
+                        }
+                        response_array.extend_from_slice(&response_bytes);
+                        first = false;
+                    },
+                    // Dropping it would leave the caller with a missing answer
+                    // instead of the reason it is missing
+                    Err(e) => return Err(e),
                 }
+            }
 
-                response_array.push(b']');
+            response_array.push(b']');
 
-                // Update batching stats with multiplexing performance
-                let elapsed = start_time.elapsed();
-                {
-                    let mut stats = self.batching_stats.lock().unwrap();
-                    stats.samples.push((batch_size, elapsed));
-                    if stats.samples.len() > 20 {
-                        stats.samples.remove(0);
-                    }
+            // Update batching stats with multiplexing performance
+            let elapsed = start_time.elapsed();
+            {
+                let mut stats = self.batching_stats.lock().unwrap();
+                stats.samples.push((batch_size, elapsed));
+                if stats.samples.len() > 20 {
+                    stats.samples.remove(0);
                 }
-
-                return Ok(response_array);
             }
+
+            return Ok(response_array);
         }
 
         // Fallback to standard JSON-RPC batch request
@@ -767,7 +1002,12 @@ impl RpcClient {
 
         buffer.push(b']');
 
-        let response = self.transport.hyper_execute_bytes(buffer).await?;
+        let response = self.transport.request(buffer.into()).await?;
+
+        // A node rejecting the whole batch answers with a single error object
+        if let Some(error) = RpcError::from_response(&response) {
+            return Err(error);
+        }
 
         // Update batching performance stats
         let elapsed = start_time.elapsed();
@@ -793,7 +1033,7 @@ impl RpcClient {
             }
         }
 
-        Ok(response)
+        Ok(response.into())
     }
 
     pub async fn execute<T: DeserializeOwned>(&self, request: RpcRequest) -> Result<T, RpcError> {
@@ -801,9 +1041,28 @@ impl RpcClient {
             r#"{{"jsonrpc":"{}","method":"{}","params":{},"id":{}}}"#,
             request.jsonrpc, request.method, request.params, request.id
         );
-        let response = self.transport.hyper_execute(request_str).await?;
+        let response = self.transport.request(request_str.into()).await?;
+
+        if let Some(error) = RpcError::from_response(&response) {
+            return Err(error);
+        }
 
-        serde_json::from_str(&response).map_err(|e| RpcError::Parse(e.to_string()))
+        serde_json::from_slice(&response).map_err(|e| RpcError::Parse(e.to_string()))
+    }
+}
+
+/// Stores `header`, queueing the reorg it reveals for `take_reorgs`
+fn record_header(
+    headers: &HeaderStore,
+    reorgs: &std::sync::Mutex<VecDeque<Reorg>>,
+    header: BlockHeader,
+) {
+    if let Some(reorg) = headers.insert(header) {
+        let mut reorgs = reorgs.lock().unwrap();
+        if reorgs.len() == MAX_PENDING_REORGS {
+            reorgs.pop_front();
+        }
+        reorgs.push_back(reorg);
     }
 }
 
@@ -822,7 +1081,8 @@ mod tests {
 
         let client = RpcClient::new(
             TransportBuilder::new("https://mainnet.infura.io/v3/2DCsBRUv8lDFmznC1BGik1pFKAL")
-                .build_http_hyper(),
+                .build_http_hyper()
+                .unwrap(),
         );
 
         let tx: TransactionRequest = TransactionRequest {
@@ -832,7 +1092,7 @@ mod tests {
             gas_price: Some(U256::from(26112348709_u64)),
             value: None,
             data: Some("0xdd9c5f960000000000000000000000000d500b1d8e8ef31e21c99d1db9a6444d3adf12700000000000000000000000000000000000000000000000056bc75e2d631000000000000000000000000000000b3f868e0be5597d5db7feb59e1cadbb0fdda50a000000000000000000000000000000000000000000000001e1291b1bf0494000000000000000000000000000000000000000000000000001de460b131125fe970000000000000000000000008f54c8c2df62c94772ac14ccfc856037429763120000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000e0020d500B1d8E8eF31E21C99d1Db9A6444d3ADf12700215550133C4F0043E2e988b3c2e9C77e2C670eFe709Bfe30185CD07Ea01423b1E937929B44E4Ad8c40BbB5E7100ffff0186f1d8390222A3691C28938eC7404A1661E618e00185CD07Ea01423b1E937929B44E4Ad8c40BbB5E7100017ceB23fD6bC0adD59E62ac25578270cFf1b9f619026aaa010312692E9cADD3dDaaCE2E112A4e36397bd2f18a0085CD07Ea01423b1E937929B44E4Ad8c40BbB5E7100ffff01Ff5713FdbAD797b81539b5F9766859d4E050a6CC0085CD07Ea01423b1E937929B44E4Ad8c40BbB5E7100".to_string()),
-            nonce: None,
+            ..Default::default()
         };
 
         let x = client.estimate_gas(&tx, None).await.unwrap();
@@ -843,7 +1103,8 @@ mod tests {
     async fn test_get_block() {
         let rpc = RpcClient::new(
             TransportBuilder::new("https://mainnet.infura.io/v3/1f2bd7408b1542e89bd4274b688aa6a4")
-                .build_http_hyper(),
+                .build_http_hyper()
+                .unwrap(),
         );
 
         let x = rpc.get_block_by_number(22349461, true);
@@ -11,9 +11,19 @@ use hyper::header::HeaderValue;
 use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
 use hyper_util::rt::TokioExecutor;
 use tokio::sync::{oneshot, Mutex, Semaphore};
+use tower::ServiceExt;
 use tracing::{debug, info};
 
-use crate::{hyper_rpc::Transport, RpcError};
+use crate::{
+    transport::{
+        auth::{request_headers, AuthProvider},
+        compression::Compression,
+        retry::{HttpResponse, RetryPolicy, DEFAULT_REQUEST_TIMEOUT},
+        url::SharedUrl,
+        RpcUrl, Transport,
+    },
+    RpcError,
+};
 
 const CONTENT_TYPE_JSON: HeaderValue = HeaderValue::from_static("application/json");
 const MAX_CONCURRENT_REQUESTS: usize = 100;
@@ -98,12 +108,15 @@ impl RequestPipeline {
 #[derive(Debug, Clone)]
 pub struct HyperTransport {
     client: Arc<HttpClient>,
-    primary_url: &'static str,
-    pipeline: Arc<RequestPipeline>,
+    url: SharedUrl,
+    auth: Option<Arc<dyn AuthProvider>>,
+    compression: Compression,
+    retry: RetryPolicy,
+    timeout: Duration,
 }
 
 impl HyperTransport {
-    pub fn new_minimal(url: &'static str) -> Self {
+    pub fn new_minimal(url: RpcUrl) -> Self {
         let client = CLIENT_POOL.get_or_init(|| {
             debug!("Creating minimal Alloy-style HTTP client");
 
@@ -137,12 +150,15 @@ impl HyperTransport {
         Self {
             // remove clone
             client: client.clone(),
-            primary_url: url,
-            pipeline: Arc::new(RequestPipeline::new()),
+            url: SharedUrl::new(url),
+            auth: None,
+            compression: Compression::default(),
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
 
-    pub fn new_ultra_fast(url: &'static str) -> Self {
+    pub fn new_ultra_fast(url: RpcUrl) -> Self {
         let client = CLIENT_POOL.get_or_init(|| {
             debug!("Creating ultra-fast HTTP client to beat Alloy");
 
@@ -176,14 +192,17 @@ impl HyperTransport {
         
         Self {
             client: client.clone(),
-            primary_url: url,
-            pipeline: Arc::new(RequestPipeline::new()),
+            url: SharedUrl::new(url),
+            auth: None,
+            compression: Compression::default(),
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
 
     /// Create a performant client for benchmarking - allows some connection reuse
     /// Similar to what Alloy does for realistic comparison
-    pub fn new_benchmark_realistic(url: &'static str) -> Self {
+    pub fn new_benchmark_realistic(url: RpcUrl) -> Self {
         debug!("Creating realistic benchmark HTTP client (minimal connection reuse)");
 
         let http_executor = TokioExecutor::new();
@@ -211,186 +230,162 @@ impl HyperTransport {
 
         Self {
             client: Arc::new(client),
-            primary_url: url,
-            pipeline: Arc::new(RequestPipeline::new()),
+            url: SharedUrl::new(url),
+            auth: None,
+            compression: Compression::default(),
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
 
-    pub fn new(url: &'static str) -> Self {
+    pub fn new(url: RpcUrl) -> Self {
         let client = CLIENT_POOL.get_or_init(|| {
             debug!("Creating shared HTTP client with pipelining support");
-
-            let http_executor = TokioExecutor::new();
-
-            let mut http_connector = hyper_util::client::legacy::connect::HttpConnector::new();
-            http_connector.set_connect_timeout(Some(Duration::from_millis(2000)));
-            http_connector.set_keepalive(Some(Duration::from_secs(90)));
-            http_connector.set_nodelay(true);
-            http_connector.set_happy_eyeballs_timeout(Some(Duration::from_millis(100)));
-            http_connector.set_reuse_address(true);
-            http_connector.enforce_http(false);
-
-            let https_connector = HttpsConnectorBuilder::new()
-                .with_provider_and_webpki_roots(rustls::crypto::aws_lc_rs::default_provider())
-                .expect("Failed to load native root certificates")
-                .https_or_http()
-                .enable_http1()
-                .enable_http2()
-                .wrap_connector(http_connector);
-
-            let client = hyper_util::client::legacy::Client::builder(http_executor)
-                .pool_idle_timeout(Duration::from_secs(90))
-                .pool_max_idle_per_host(100)
-                .retry_canceled_requests(false)
-                .build(https_connector);
-
+            let client = Self::pooled_client(100);
             info!("Shared HTTP client with pipelining created successfully");
-            Arc::new(client)
+            client
         });
 
-        let pipeline = Arc::new(RequestPipeline::new());
-
         Self {
             client: client.clone(),
-            primary_url: url,
-            pipeline: pipeline.clone(),
+            url: SharedUrl::new(url),
+            auth: None,
+            compression: Compression::default(),
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
 
-    pub fn new_with_fallbacks(
-        primary_url: &'static str,
-        _fallback_urls: Vec<&'static str>,
-    ) -> Self {
-        let client = CLIENT_POOL.get_or_init(|| {
-            debug!("Creating shared HTTP client with fallbacks and pipelining");
-
-            let http_executor = TokioExecutor::new();
+    fn pooled_client(max_idle: usize) -> Arc<HttpClient> {
+        let http_executor = TokioExecutor::new();
 
-            let mut http_connector = hyper_util::client::legacy::connect::HttpConnector::new();
-            http_connector.set_connect_timeout(Some(Duration::from_millis(2000)));
-            http_connector.set_keepalive(Some(Duration::from_secs(90)));
-            http_connector.set_nodelay(true);
-            http_connector.set_happy_eyeballs_timeout(Some(Duration::from_millis(100)));
-            http_connector.set_reuse_address(true);
-            http_connector.enforce_http(false);
+        let mut http_connector = hyper_util::client::legacy::connect::HttpConnector::new();
+        http_connector.set_connect_timeout(Some(Duration::from_millis(2000)));
+        http_connector.set_keepalive(Some(Duration::from_secs(90)));
+        http_connector.set_nodelay(true);
+        http_connector.set_happy_eyeballs_timeout(Some(Duration::from_millis(100)));
+        http_connector.set_reuse_address(true);
+        http_connector.enforce_http(false);
 
-            let https_connector = HttpsConnectorBuilder::new()
-                .with_provider_and_webpki_roots(rustls::crypto::aws_lc_rs::default_provider())
-                .expect("Failed to load native root certificates")
-                .https_or_http()
-                .enable_http1()
-                .enable_http2()
-                .wrap_connector(http_connector);
+        let https_connector = HttpsConnectorBuilder::new()
+            .with_provider_and_webpki_roots(rustls::crypto::aws_lc_rs::default_provider())
+            .expect("Failed to load native root certificates")
+            .https_or_http()
+            .enable_http1()
+            .enable_http2()
+            .wrap_connector(http_connector);
 
-            let client = hyper_util::client::legacy::Client::builder(http_executor)
-                .pool_idle_timeout(Duration::from_secs(90))
-                .pool_max_idle_per_host(100)
-                .retry_canceled_requests(false)
-                .build(https_connector);
+        let client = hyper_util::client::legacy::Client::builder(http_executor)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .pool_max_idle_per_host(max_idle)
+            .retry_canceled_requests(false)
+            .build(https_connector);
 
-            info!("Shared HTTP client with fallbacks and pipelining created successfully");
-            Arc::new(client)
-        });
+        Arc::new(client)
+    }
 
-        let pipeline = Arc::new(RequestPipeline::new());
+    /// Moves this transport off the shared client onto its own pool keeping
+    /// at most `max_idle` idle connections per host
+    pub fn with_pool_max_idle(mut self, max_idle: usize) -> Self {
+        self.client = Self::pooled_client(max_idle);
+        self
+    }
 
-        Self {
-            client: client.clone(),
-            primary_url,
-            pipeline: pipeline.clone(),
-        }
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
     }
 
-    pub async fn execute_single_request(&self, request: &[u8]) -> Result<Vec<u8>, RpcError> {
-        let start = std::time::Instant::now();
-        let body = http_body_util::Full::new(Bytes::copy_from_slice(request));
+    /// Upper bound for each attempt, retries get a fresh timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
 
-        let req = hyper::Request::builder()
-            .method(hyper::Method::POST)
-            .uri(self.primary_url)
-            .header(hyper::header::CONTENT_TYPE, CONTENT_TYPE_JSON)
-            .body(body)
-            .map_err(|e| RpcError::Transport(format!("Failed to build request: {}", e)))?;
+    /// Sends the headers `auth` hands out with every request, on top of
+    /// any credentials in the url
+    pub fn with_auth(self, auth: impl AuthProvider + 'static) -> Self {
+        self.with_auth_provider(Some(Arc::new(auth)))
+    }
 
-        debug!("Making HTTP request to {}", self.primary_url);
-        let response = self
-            .client
-            .request(req)
-            .await
-            .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
+    pub(crate) fn with_auth_provider(mut self, auth: Option<Arc<dyn AuthProvider>>) -> Self {
+        self.auth = auth;
+        self
+    }
 
-        if !response.status().is_success() {
-            return Err(RpcError::Transport(format!("HTTP error {}", response.status())));
-        }
+    /// Which encodings responses may use and which requests get compressed,
+    /// by default any of gzip, deflate, br and zstd is accepted
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
 
-        let body = response.into_body();
+    pub async fn execute_single_request(&self, request: &[u8]) -> Result<Vec<u8>, RpcError> {
+        let start = std::time::Instant::now();
+        let request = Bytes::copy_from_slice(request);
         let body_bytes =
-            body.collect().await.map_err(|e| RpcError::Transport(e.to_string()))?.to_bytes();
-        
+            self.retry.execute(self.timeout, || self.post(request.clone())).await?;
+
         let duration = start.elapsed();
         debug!("HTTP request completed in {:?}, response size: {} bytes", duration, body_bytes.len());
 
         Ok(body_bytes.into())
     }
-}
-
-#[async_trait]
-impl Transport for HyperTransport {
-    async fn hyper_execute(&self, request: String) -> Result<String, RpcError> {
-        let url = self.primary_url;
-
-        let reqs = request.as_bytes().to_owned().into();
 
-        let req = hyper::Request::builder()
+    /// Sends the request once and hands back the raw response, only failing
+    /// when the endpoint could not be reached or the body could not be read
+    pub(crate) async fn post(&self, mut request: Bytes) -> Result<HttpResponse, RpcError> {
+        let url = self.url.get();
+        let mut req = hyper::Request::builder()
             .method(hyper::Method::POST)
-            .uri(url)
-            .header("Content-Type", CONTENT_TYPE_JSON)
-            .body(http_body_util::Full::new(reqs))
-            .expect("Failed to build request");
+            .uri(url.uri().clone())
+            .header(hyper::header::CONTENT_TYPE, CONTENT_TYPE_JSON);
+        if let Some(headers) = req.headers_mut() {
+            headers.extend(request_headers(&url, self.auth.as_ref())?);
+            request = self.compression.encode_request(request, headers)?;
+        }
+        let req = req
+            .body(http_body_util::Full::new(request))
+            .map_err(|e| RpcError::Transport(format!("Failed to build request: {}", e)))?;
 
+        debug!("Making HTTP request to {}", url);
+        // Cloning the client only clones its handle to the shared pool
         let response = self
-            .client
-            .request(req)
+            .compression
+            .decompress(HttpClient::clone(&self.client))
+            .oneshot(req)
             .await
-            .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
+            .map_err(|e| RpcError::Connection(format!("Request failed: {}", e)))?;
 
-        let body = response.into_body();
+        let (parts, body) = response.into_parts();
         let body_bytes =
-            body.collect().await.map_err(|e| RpcError::Transport(e.to_string()))?.to_bytes();
+            body.collect().await.map_err(|e| RpcError::Connection(e.to_string()))?.to_bytes();
 
-        String::from_utf8(body_bytes.to_vec())
-            .map_err(|e| RpcError::Response(format!("Invalid UTF-8 in response: {}", e)))
+        Ok(HttpResponse::new(parts.status, &parts.headers, body_bytes))
     }
 
-    async fn hyper_execute_raw(&self, request: &'static [u8]) -> Result<Vec<u8>, RpcError> {
-        let req = hyper::Request::builder()
-            .method(hyper::Method::POST)
-            .uri(self.primary_url)
-            .header(hyper::header::CONTENT_TYPE, CONTENT_TYPE_JSON)
-            .header(hyper::header::CONNECTION, "keep-alive")
-            .body(http_body_util::Full::new(Bytes::from_static(request)))
-            .map_err(|e| RpcError::Transport(format!("Failed to build request: {}", e)))?;
-
-        let response = self
-            .client
-            .request(req)
-            .await
-            .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
+    pub fn url(&self) -> Arc<RpcUrl> {
+        self.url.get()
+    }
+}
 
-        if !response.status().is_success() {
-            return Err(RpcError::Transport(format!("HTTP error {}", response.status())));
-        }
+#[async_trait]
+impl Transport for HyperTransport {
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.retry.execute(self.timeout, || self.post(request.clone())).await
+    }
 
-        // Direct body collection without intermediate conversion
-        let body = response.into_body();
-        let body_bytes =
-            body.collect().await.map_err(|e| RpcError::Transport(e.to_string()))?.to_bytes();
+    /// Batches go out concurrently over the pooled connections
+    fn supports_batch(&self) -> bool {
+        true
+    }
 
-        Ok(body_bytes.into())
+    fn endpoint(&self) -> Option<RpcUrl> {
+        Some(RpcUrl::clone(&self.url.get()))
     }
 
-    async fn hyper_execute_bytes(&self, request: Vec<u8>) -> Result<Vec<u8>, RpcError> {
-        self.execute_single_request(&request).await
+    fn set_endpoint(&self, url: RpcUrl) -> Result<(), RpcError> {
+        self.url.set(url)
     }
 }
 
@@ -415,68 +410,10 @@ impl HyperTransport {
         }
 
         // Use HTTP/2 multiplexing for concurrent requests
-        let futures = requests.into_iter().map(|req| {
-            let client = self.client.clone();
-            let url = self.primary_url;
-            
-            async move {
-                let body = http_body_util::Full::new(bytes::Bytes::from(req));
-                let request = hyper::Request::builder()
-                    .method(hyper::Method::POST)
-                    .uri(url)
-                    .header(hyper::header::CONTENT_TYPE, CONTENT_TYPE_JSON)
-                    .body(body)
-                    .map_err(|e| RpcError::Transport(format!("Failed to build request: {}", e)))?;
-
-                let response = client
-                    .request(request)
-                    .await
-                    .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
-
-                if !response.status().is_success() {
-                    return Err(RpcError::Transport(format!("HTTP error {}", response.status())));
-                }
-
-                let body = response.into_body();
-                let body_bytes = body
-                    .collect()
-                    .await
-                    .map_err(|e| RpcError::Transport(e.to_string()))?
-                    .to_bytes();
-
-                Ok(body_bytes.to_vec())
-            }
-        });
+        let futures = requests.iter().map(|req| self.execute_single_request(req));
 
         // Execute all requests concurrently using HTTP/2 multiplexing
         let results = futures::future::join_all(futures).await;
         Ok(results)
     }
-
-    pub async fn hyper_execute_bytes_batch(
-        &self,
-        requests: Vec<Vec<u8>>,
-    ) -> Result<Vec<Result<Vec<u8>, RpcError>>, RpcError> {
-        let mut handles = Vec::new();
-
-        for request in requests {
-            let pipeline = self.pipeline.clone();
-            let handle = tokio::spawn(async move { pipeline.enqueue_request(request).await });
-            handles.push(handle);
-        }
-
-        let results = futures::future::join_all(handles).await;
-        let mut responses = Vec::new();
-
-        for result in results {
-            match result {
-                Ok(response) => responses.push(response),
-                Err(e) => {
-                    responses.push(Err(RpcError::Transport(format!("Task join error: {}", e))))
-                },
-            }
-        }
-
-        Ok(responses)
-    }
 }
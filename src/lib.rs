@@ -1,21 +1,32 @@
 use std::time::Duration;
 
+use async_trait::async_trait;
+use bytes::Bytes;
+use memchr::memmem;
 use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
 
+pub mod block_stream;
+pub mod engine;
+pub mod fee_oracle;
+pub mod header_store;
 pub mod hyper_rpc;
 pub mod hyper_transport;
 pub mod parser;
 pub mod reqwest_transport;
+pub mod revert;
 pub mod tower_transport;
 pub mod direct_transport;
 pub mod direct_reqwest_transport;
 pub mod transport;
+pub mod verify;
 
 #[derive(Debug)]
 pub struct HttpTransport {
     client: Client,
-    urls: &'static str,
-    current_url: usize,
+    url: transport::url::SharedUrl,
+    retry: transport::retry::RetryPolicy,
     timeout: Duration,
 }
 
@@ -23,40 +34,233 @@ pub struct HttpTransport {
 pub enum RpcError {
     #[error("Transport error: {0}")]
     Transport(String),
+    /// The endpoint could not be reached or the connection dropped mid request
+    #[error("Connection error: {0}")]
+    Connection(String),
+    #[error("Request timed out after {0:?}")]
+    Timeout(Duration),
+    /// Non-success HTTP status, with the body the endpoint sent along
+    #[error("HTTP error {status}: {body}")]
+    Http { status: u16, body: String },
+    /// The `error` object of a JSON-RPC response
+    #[error("JSON-RPC error {code}: {message}")]
+    JsonRpc {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+    /// The node refused a raw transaction, `message` is its original wording
+    #[error("Transaction rejected ({reason:?}): {message}")]
+    TxRejected {
+        reason: TxRejection,
+        message: String,
+    },
     #[error("Invalid response: {0}")]
     Response(String),
+    /// The endpoint url is malformed or of the wrong kind for the transport
+    #[error("Invalid url: {0}")]
+    InvalidUrl(String),
+    /// Credentials could not be loaded or turned into headers
+    #[error("Auth error: {0}")]
+    Auth(String),
+    /// The response failed a consistency check, see `verify`
+    #[error("Verification failed: {0}")]
+    Verification(#[from] verify::VerificationError),
     #[error("Parse error: {0}")]
     Parse(String),
 }
 
+/// Common reasons a node refuses `eth_sendRawTransaction`. Clients word these
+/// differently and only agree on the substrings matched here
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxRejection {
+    NonceTooLow,
+    NonceTooHigh,
+    /// Gas price or fee cap below what the pool accepts
+    Underpriced,
+    /// Same nonce as a pending transaction without a sufficient fee bump
+    ReplacementUnderpriced,
+    InsufficientFunds,
+    IntrinsicGasTooLow,
+    /// The pool already holds this transaction
+    AlreadyKnown,
+}
+
+impl TxRejection {
+    pub fn from_message(message: &str) -> Option<Self> {
+        let message = message.to_ascii_lowercase();
+        // Order matters, "replacement transaction underpriced" contains "underpriced"
+        let reason = if message.contains("nonce too low") {
+            TxRejection::NonceTooLow
+        } else if message.contains("nonce too high") {
+            TxRejection::NonceTooHigh
+        } else if message.contains("replacement") && message.contains("underpriced") {
+            TxRejection::ReplacementUnderpriced
+        } else if message.contains("underpriced") || message.contains("less than block base fee") {
+            TxRejection::Underpriced
+        } else if message.contains("insufficient funds") {
+            TxRejection::InsufficientFunds
+        } else if message.contains("intrinsic gas too low") {
+            TxRejection::IntrinsicGasTooLow
+        } else if message.contains("already known") || message.contains("known transaction") {
+            TxRejection::AlreadyKnown
+        } else {
+            return None;
+        };
+        Some(reason)
+    }
+}
+
+/// JSON-RPC "limit exceeded", used by most providers for rate limiting
+pub const LIMIT_EXCEEDED_CODE: i64 = -32005;
+
+#[derive(Deserialize)]
+struct ErrorObject {
+    code: i64,
+    message: String,
+    #[serde(default)]
+    data: Option<Value>,
+}
+
+impl RpcError {
+    /// Whether sending the same request again may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RpcError::Connection(_) | RpcError::Timeout(_) => true,
+            RpcError::Http { status, .. } => *status == 429 || *status >= 500,
+            RpcError::JsonRpc { code, .. } => *code == LIMIT_EXCEEDED_CODE,
+            RpcError::Transport(_)
+            | RpcError::TxRejected { .. }
+            | RpcError::Response(_)
+            | RpcError::InvalidUrl(_)
+            | RpcError::Auth(_)
+            | RpcError::Verification(_)
+            | RpcError::Parse(_) => false,
+        }
+    }
+
+    /// Extracts the `error` member of a single JSON-RPC response, `None` when
+    /// the response succeeded. Batch responses are left to the caller
+    pub fn from_response(response: &[u8]) -> Option<RpcError> {
+        // Cheap check first, almost every response goes through here
+        memmem::find(response, b"\"error\"")?;
+
+        let (start, end) = parser::lib::top_level_field(response, b"error")?;
+        let raw = &response[start..end];
+        if raw == b"null" {
+            return None;
+        }
+
+        Some(match serde_json::from_slice::<ErrorObject>(raw) {
+            Ok(error) => RpcError::JsonRpc {
+                code: error.code,
+                message: error.message,
+                data: error.data,
+            },
+            Err(_) => RpcError::Response(String::from_utf8_lossy(raw).into_owned()),
+        })
+    }
+
+    /// Turns a JSON-RPC error into `TxRejected` when its message is a known
+    /// transaction pool rejection
+    pub fn into_tx_rejection(self) -> RpcError {
+        let RpcError::JsonRpc { message, .. } = &self else {
+            return self;
+        };
+
+        match TxRejection::from_message(message) {
+            Some(reason) => RpcError::TxRejected {
+                reason,
+                message: message.clone(),
+            },
+            None => self,
+        }
+    }
+}
+
 impl HttpTransport {
-    pub fn new(url: &'static str) -> Self {
-        let client = Client::builder()
+    pub fn new(url: transport::RpcUrl) -> Self {
+        Self {
+            client: Self::client(Client::builder()),
+            url: transport::url::SharedUrl::new(url),
+            retry: transport::retry::RetryPolicy::default(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    fn client(builder: reqwest::ClientBuilder) -> Client {
+        builder
             .timeout(Duration::from_secs(30))
             .pool_idle_timeout(Duration::from_secs(60))
             .tcp_keepalive(Duration::from_secs(60))
             .build()
-            .expect("Failed to create HTTP client");
-
-        Self {
-            client,
-            urls: url,
-            current_url: 0,
-            timeout: Duration::from_secs(30),
-        }
+            .expect("Failed to create HTTP client")
     }
 
     pub fn new_with_config(self) -> Self {
         Self {
             client: self.client,
-            urls: self.urls,
-            current_url: self.current_url,
+            url: self.url,
+            retry: self.retry,
             timeout: self.timeout,
         }
     }
 
+    /// Upper bound for each attempt, retries get a fresh timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
+
+    pub fn with_retry_policy(mut self, retry: transport::retry::RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Replaces the client with one keeping at most `max_idle` idle
+    /// connections per host, otherwise configured like `new`
+    pub fn with_pool_max_idle(mut self, max_idle: usize) -> Self {
+        self.client = Self::client(Client::builder().pool_max_idle_per_host(max_idle));
+        self
+    }
+
+    async fn post(&self, request: Bytes) -> Result<transport::retry::HttpResponse, RpcError> {
+        let url = self.url.get();
+        let mut builder = self.client.post(url.as_str());
+        if let Some(authorization) = url.authorization() {
+            builder = builder.header(reqwest::header::AUTHORIZATION, authorization.clone());
+        }
+        let response = builder
+            .header("Content-Type", "application/json")
+            .body(request)
+            .send()
+            .await
+            .map_err(|e| RpcError::Connection(format!("Request failed: {}", e.without_url())))?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| {
+                RpcError::Connection(format!("Failed to read response: {}", e.without_url()))
+            })?;
+
+        Ok(transport::retry::HttpResponse::new(status, &headers, body))
+    }
+}
+
+#[async_trait]
+impl transport::Transport for HttpTransport {
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.retry.execute(self.timeout, || self.post(request.clone())).await
+    }
+
+    fn endpoint(&self) -> Option<transport::RpcUrl> {
+        Some(transport::RpcUrl::clone(&self.url.get()))
+    }
+
+    fn set_endpoint(&self, url: transport::RpcUrl) -> Result<(), RpcError> {
+        self.url.set(url)
+    }
 }
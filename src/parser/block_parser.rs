@@ -1,12 +1,16 @@
+use alloy::primitives::{Address, B256};
+
 use super::{
     lib::{
-        find_field, hex_to_b256, hex_to_u256, hex_to_u64, unsafe_hex_to_address, unsafe_hex_to_b256,
+        find_field, hex_to_b256, index_members, quantity_to_u256, quantity_to_u64, skip_value,
+        split_array, unsafe_hex_to_address,
     },
-    types::Block,
+    tx_parser::RawTx,
+    types::{Block, BlockHeader, BlockTransactions, Withdrawal},
 };
 
 // Field indices for fast lookup
-const FIELD_COUNT: usize = 17;
+const FIELD_COUNT: usize = 23;
 const NUMBER: usize = 0;
 const HASH: usize = 1;
 const PARENT_HASH: usize = 2;
@@ -24,6 +28,12 @@ const EXTRA_DATA: usize = 13;
 const MIX_HASH: usize = 14;
 const NONCE: usize = 15;
 const BASE_FEE_PER_GAS: usize = 16;
+const PREV_RANDAO: usize = 17;
+const WITHDRAWALS_ROOT: usize = 18;
+const BLOB_GAS_USED: usize = 19;
+const EXCESS_BLOB_GAS: usize = 20;
+const PARENT_BEACON_BLOCK_ROOT: usize = 21;
+const REQUESTS_HASH: usize = 22;
 
 // Field patterns for search
 static FIELD_PATTERNS: [(&[u8], &[u8]); FIELD_COUNT] = [
@@ -44,15 +54,26 @@ static FIELD_PATTERNS: [(&[u8], &[u8]); FIELD_COUNT] = [
     (b"\"mixHash\":\"", b"\""),
     (b"\"nonce\":\"", b"\""),
     (b"\"baseFeePerGas\":\"", b"\""),
+    (b"\"prevRandao\":\"", b"\""),
+    (b"\"withdrawalsRoot\":\"", b"\""),
+    (b"\"blobGasUsed\":\"", b"\""),
+    (b"\"excessBlobGas\":\"", b"\""),
+    (b"\"parentBeaconBlockRoot\":\"", b"\""),
+    (b"\"requestsHash\":\"", b"\""),
 ];
 
+static WITHDRAWAL_FIELDS: [&[u8]; 4] = [b"index", b"validatorIndex", b"address", b"amount"];
+
 #[derive(Debug)]
 pub struct RawBlock<'a> {
     data: &'a [u8],
     fields: [(usize, usize); FIELD_COUNT],
     fields_present: u32,
     transactions: Vec<(usize, usize)>,
+    full_transactions: bool,
     uncles: Vec<(usize, usize)>,
+    // Withdrawals array, post-Shanghai only
+    withdrawals: Option<(usize, usize)>,
 }
 
 #[derive(Debug)]
@@ -65,13 +86,35 @@ pub struct RawJsonResponse<'a> {
 impl<'a> RawBlock<'a> {
     #[inline]
     pub fn parse(input: &'a [u8]) -> Option<Self> {
+        // Estimate transaction count based on response size - newer blocks are much
+        // larger
+        let tx_capacity = if input.len() > 500_000 {
+            1500 // Large modern blocks can have 300+ transactions
+        } else if input.len() > 200_000 {
+            800 // Medium blocks
+        } else {
+            400 // Smaller blocks
+        };
+
+        let mut transactions = Vec::with_capacity(tx_capacity);
+        let mut uncles = Vec::with_capacity(2);
+
+        // Transactions first, header fields are searched around the array
+        let (tx_array, full_transactions) =
+            Self::parse_transactions_array(input, &mut transactions);
+        Self::parse_uncles_array(input, &mut uncles);
+        let withdrawals = memchr::memmem::find(input, b"\"withdrawals\":[").map(|start| {
+            let start = start + b"\"withdrawals\":".len();
+            (start, skip_value(input, start))
+        });
+
         // Use a bitfield to track which fields are present (faster than Option<>)
         let mut fields_present: u32 = 0;
         let mut fields = [(0, 0); FIELD_COUNT];
 
         // Parse all fields in one batch operation
         for (idx, &(prefix, suffix)) in FIELD_PATTERNS.iter().enumerate() {
-            if let Some(range) = find_field(input, prefix, suffix) {
+            if let Some(range) = find_block_field(input, tx_array, prefix, suffix) {
                 fields[idx] = range;
                 fields_present |= 1 << idx;
             }
@@ -83,52 +126,47 @@ impl<'a> RawBlock<'a> {
         //     return None;
         // }
 
-        // Estimate transaction count based on response size - newer blocks are much
-        // larger
-        let tx_capacity = if input.len() > 500_000 {
-            1500 // Large modern blocks can have 300+ transactions
-        } else if input.len() > 200_000 {
-            800 // Medium blocks
-        } else {
-            400 // Smaller blocks
-        };
-
-        let mut transactions = Vec::with_capacity(tx_capacity);
-        let mut uncles = Vec::with_capacity(2);
-
-        // Parse transaction array - only if we need it
-        Self::parse_transactions_array(input, &mut transactions);
-        Self::parse_uncles_array(input, &mut uncles);
-
         Some(Self {
             data: input,
             fields,
             fields_present,
             transactions,
+            full_transactions,
             uncles,
+            withdrawals,
         })
     }
 
+    /// Collects the transaction hashes, or the object ranges when the block
+    /// was requested with full transactions. Returns the range of the whole
+    /// array and whether it held objects
     #[inline]
-    fn parse_transactions_array(data: &[u8], result: &mut Vec<(usize, usize)>) {
-        // Use our existing find_field to locate the transactions array start
-        if let Some(start) = memchr::memmem::find(data, b"\"transactions\":[") {
-            let mut pos = start + b"\"transactions\":[".len();
+    fn parse_transactions_array(
+        data: &[u8],
+        result: &mut Vec<(usize, usize)>,
+    ) -> ((usize, usize), bool) {
+        let len = data.len();
+        let mut full = false;
 
-            // Single-pass extraction of all transaction hashes
-            let len = data.len();
-            while pos < data.len() {
-                // Skip whitespace and commas
-                while pos < len && (data[pos] == b' ' || data[pos] == b',' || data[pos] == b'\n') {
-                    pos += 1;
-                }
+        // Locate the transactions array start
+        let Some(array_start) = memchr::memmem::find(data, b"\"transactions\":[") else {
+            return ((len, len), full);
+        };
+        let mut pos = array_start + b"\"transactions\":[".len();
 
-                if pos >= len || data[pos] == b']' {
-                    break;
-                }
+        // Single-pass extraction of all transaction hashes
+        while pos < len {
+            // Skip whitespace and commas
+            while pos < len && (data[pos] == b' ' || data[pos] == b',' || data[pos] == b'\n') {
+                pos += 1;
+            }
 
-                // Only process string values (transaction hashes)
-                if data[pos] == b'"' {
+            if pos >= len || data[pos] == b']' {
+                break;
+            }
+
+            match data[pos] {
+                b'"' => {
                     pos += 1;
                     let tx_start = pos;
 
@@ -140,15 +178,23 @@ impl<'a> RawBlock<'a> {
                     } else {
                         break;
                     }
-                } else {
-                    // Not a string, might be an object or something else
+                },
+                b'{' => {
+                    let tx_end = skip_value(data, pos);
+                    result.push((pos, tx_end));
+                    full = true;
+                    pos = tx_end;
+                },
+                _ => {
                     // Skip until next comma or closing bracket
                     while pos < len && data[pos] != b',' && data[pos] != b']' {
                         pos += 1;
                     }
-                }
+                },
             }
         }
+
+        ((array_start, (pos + 1).min(len)), full)
     }
 
     #[inline]
@@ -189,46 +235,188 @@ impl<'a> RawBlock<'a> {
         }
     }
 
+    /// Helper to check field presence and get slice
     #[inline]
-    pub fn to_block(&self) -> Block {
-        // Helper to check field presence and get slice
-        let get_field = |idx: usize| -> &[u8] {
-            if (self.fields_present & (1 << idx)) != 0 {
-                let (start, end) = self.fields[idx];
-                &self.data[start..end]
-            } else {
-                b"0x0"
-            }
-        };
+    fn field(&self, idx: usize) -> &'a [u8] {
+        if (self.fields_present & (1 << idx)) != 0 {
+            let (start, end) = self.fields[idx];
+            &self.data[start..end]
+        } else {
+            b"0x0"
+        }
+    }
+
+    /// Slice of a field the block may not have, e.g. one added by a later fork
+    #[inline]
+    fn optional_field(&self, idx: usize) -> Option<&'a [u8]> {
+        ((self.fields_present & (1 << idx)) != 0).then(|| self.field(idx))
+    }
+
+    /// Hash or root field, `None` when missing, null or not 32 bytes
+    #[inline]
+    fn b256_field(&self, idx: usize) -> Option<B256> {
+        self.optional_field(idx).filter(|bytes| bytes.len() == 66).map(hex_to_b256)
+    }
+
+    #[inline]
+    fn address_field(&self, idx: usize) -> Option<Address> {
+        self.optional_field(idx)
+            .filter(|bytes| bytes.len() == 42)
+            .map(unsafe_hex_to_address)
+    }
 
-        Block {
-            number: hex_to_u64(get_field(NUMBER)),
-            hash: Some(hex_to_b256(get_field(HASH))),
-            parent_hash: unsafe_hex_to_b256(get_field(PARENT_HASH)),
-            uncles_hash: unsafe_hex_to_b256(get_field(UNCLES_HASH)),
-            author: unsafe_hex_to_address(get_field(AUTHOR)),
-            state_root: unsafe_hex_to_b256(get_field(STATE_ROOT)),
-            transactions_root: unsafe_hex_to_b256(get_field(TRANSACTIONS_ROOT)),
-            receipts_root: unsafe_hex_to_b256(get_field(RECEIPTS_ROOT)),
+    /// `prevRandao` is only sent by some clients, after the merge the value
+    /// lives in `mixHash`
+    #[inline]
+    fn prev_randao(&self) -> Option<B256> {
+        match self.b256_field(PREV_RANDAO) {
+            Some(prev_randao) => Some(prev_randao),
+            None if quantity_to_u64(self.field(DIFFICULTY)).is_zero() => self.b256_field(MIX_HASH),
+            None => None,
+        }
+    }
+
+    #[inline]
+    pub fn withdrawals(&self) -> Option<Vec<Withdrawal>> {
+        let (start, end) = self.withdrawals?;
+        let array = &self.data[start..end];
+
+        let withdrawals = split_array(array)
+            .into_iter()
+            .filter_map(|(s, e)| {
+                let item = &array[s..e];
+                let (fields, present) = index_members(item, &WITHDRAWAL_FIELDS);
+                if present != 0b1111 {
+                    return None;
+                }
+                let field = |idx: usize| &item[fields[idx].0..fields[idx].1];
+                if field(2).len() != 42 {
+                    return None;
+                }
+
+                Some(Withdrawal {
+                    index: quantity_to_u64(field(0)),
+                    validator_index: quantity_to_u64(field(1)),
+                    address: unsafe_hex_to_address(field(2)),
+                    // Gwei
+                    amount: quantity_to_u256(field(3)),
+                })
+            })
+            .collect();
+        Some(withdrawals)
+    }
+
+    /// `None` when a hash, root or the miner address is missing or malformed,
+    /// or when one of the transactions does not parse
+    #[inline]
+    pub fn to_block(&self) -> Option<Block> {
+        let get_field = |idx: usize| self.field(idx);
+
+        Some(Block {
+            number: quantity_to_u64(get_field(NUMBER)),
+            hash: self.b256_field(HASH),
+            parent_hash: self.b256_field(PARENT_HASH)?,
+            uncles_hash: self.b256_field(UNCLES_HASH)?,
+            author: self.address_field(AUTHOR)?,
+            state_root: self.b256_field(STATE_ROOT)?,
+            transactions_root: self.b256_field(TRANSACTIONS_ROOT)?,
+            receipts_root: self.b256_field(RECEIPTS_ROOT)?,
             logs_bloom: String::from_utf8_lossy(get_field(LOGS_BLOOM)).into_owned(),
-            difficulty: hex_to_u64(get_field(DIFFICULTY)),
-            gas_limit: hex_to_u256(get_field(GAS_LIMIT)),
-            gas_used: hex_to_u256(get_field(GAS_USED)),
-            timestamp: hex_to_u64(get_field(TIMESTAMP)),
+            difficulty: quantity_to_u64(get_field(DIFFICULTY)),
+            gas_limit: quantity_to_u256(get_field(GAS_LIMIT)),
+            gas_used: quantity_to_u256(get_field(GAS_USED)),
+            timestamp: quantity_to_u64(get_field(TIMESTAMP)),
             extra_data: String::from_utf8_lossy(get_field(EXTRA_DATA)).into_owned(),
-            mix_hash: unsafe_hex_to_b256(get_field(MIX_HASH)),
-            nonce: hex_to_u64(get_field(NONCE)),
-            base_fee_per_gas: Some(hex_to_u256(get_field(BASE_FEE_PER_GAS))),
-            prev_randao: None,
+            mix_hash: self.b256_field(MIX_HASH)?,
+            nonce: quantity_to_u64(get_field(NONCE)),
+            base_fee_per_gas: self.optional_field(BASE_FEE_PER_GAS).map(quantity_to_u256),
+            prev_randao: self.prev_randao(),
+            withdrawals_root: self.b256_field(WITHDRAWALS_ROOT),
+            blob_gas_used: self.optional_field(BLOB_GAS_USED).map(quantity_to_u64),
+            excess_blob_gas: self.optional_field(EXCESS_BLOB_GAS).map(quantity_to_u64),
+            parent_beacon_block_root: self.b256_field(PARENT_BEACON_BLOCK_ROOT),
+            requests_hash: self.b256_field(REQUESTS_HASH),
             // Process transactions and uncles as needed
-            transactions: self
+            transactions: self.transactions()?,
+            uncles: self.hashes(&self.uncles)?,
+            withdrawals: self.withdrawals(),
+        })
+    }
+
+    /// Hashes, or fully parsed transactions when the block was requested
+    /// with `full_tx`. `None` if any of them does not parse, rather than
+    /// handing out a block with transactions missing
+    #[inline]
+    pub fn transactions(&self) -> Option<BlockTransactions> {
+        if self.full_transactions {
+            let txs = self
                 .transactions
                 .iter()
-                .map(|&(s, e)| hex_to_b256(&self.data[s..e]))
-                .collect(),
-            uncles: self.uncles.iter().map(|&(s, e)| hex_to_b256(&self.data[s..e])).collect(),
+                .map(|&(s, e)| RawTx::parse(&self.data[s..e])?.to_transaction())
+                .collect::<Option<_>>()?;
+            Some(BlockTransactions::Full(txs))
+        } else {
+            self.hashes(&self.transactions).map(BlockTransactions::Hashes)
         }
     }
+
+    /// Quoted hashes at `ranges`, `None` if any of them is not 32 bytes
+    #[inline]
+    fn hashes(&self, ranges: &[(usize, usize)]) -> Option<Vec<B256>> {
+        ranges
+            .iter()
+            .map(|&(s, e)| &self.data[s..e])
+            .map(|bytes| (bytes.len() == 66).then(|| hex_to_b256(bytes)))
+            .collect()
+    }
+
+    /// Header only view, used for `newHeads` notifications which carry no
+    /// transactions. `None` under the same conditions as `to_block`
+    #[inline]
+    pub fn to_header(&self) -> Option<BlockHeader> {
+        let get_field = |idx: usize| self.field(idx);
+
+        Some(BlockHeader {
+            number: quantity_to_u64(get_field(NUMBER)).to::<u64>(),
+            hash: self.b256_field(HASH),
+            parent_hash: self.b256_field(PARENT_HASH)?,
+            uncles_hash: self.b256_field(UNCLES_HASH)?,
+            author: self.address_field(AUTHOR)?,
+            state_root: self.b256_field(STATE_ROOT)?,
+            transactions_root: self.b256_field(TRANSACTIONS_ROOT)?,
+            receipts_root: self.b256_field(RECEIPTS_ROOT)?,
+            logs_bloom: String::from_utf8_lossy(get_field(LOGS_BLOOM)).into_owned(),
+            difficulty: quantity_to_u64(get_field(DIFFICULTY)).to::<u64>(),
+            gas_limit: quantity_to_u256(get_field(GAS_LIMIT)),
+            gas_used: quantity_to_u256(get_field(GAS_USED)),
+            timestamp: quantity_to_u64(get_field(TIMESTAMP)).to::<u64>(),
+            extra_data: String::from_utf8_lossy(get_field(EXTRA_DATA)).into_owned(),
+            mix_hash: self.b256_field(MIX_HASH)?,
+            nonce: quantity_to_u64(get_field(NONCE)).to::<u64>(),
+            base_fee_per_gas: self.optional_field(BASE_FEE_PER_GAS).map(quantity_to_u256),
+            prev_randao: self.prev_randao(),
+            withdrawls_root: self.b256_field(WITHDRAWALS_ROOT),
+            blob_gas_used: self.optional_field(BLOB_GAS_USED).map(quantity_to_u64),
+            excess_blob_gas: self.optional_field(EXCESS_BLOB_GAS).map(quantity_to_u64),
+            parent_beacon_block_root: self.b256_field(PARENT_BEACON_BLOCK_ROOT),
+            requests_hash: self.b256_field(REQUESTS_HASH),
+            ..Default::default()
+        })
+    }
+}
+
+/// Full transaction objects repeat keys such as `hash` and `nonce`, so header
+/// fields are looked up before and after the transactions array only
+#[inline]
+fn find_block_field(
+    data: &[u8],
+    tx_array: (usize, usize),
+    prefix: &[u8],
+    suffix: &[u8],
+) -> Option<(usize, usize)> {
+    let (start, end) = tx_array;
+    find_field(&data[..start], prefix, suffix)
+        .or_else(|| find_field(&data[end..], prefix, suffix).map(|(s, e)| (s + end, e + end)))
 }
 
 impl<'a> RawJsonResponse<'a> {
@@ -277,5 +465,16 @@ pub fn parse_block(input: &[u8]) -> Option<Block> {
     // Fast path - direct pipeline
     RawJsonResponse::parse_block(input)
         .and_then(|r| r.block())
-        .map(|block| block.to_block())
+        .and_then(|block| block.to_block())
+}
+
+/// Parses a bare header object such as the payload of a `newHeads`
+/// notification
+#[inline]
+pub fn parse_header(input: &[u8]) -> Option<BlockHeader> {
+    let block = RawBlock::parse(input)?;
+    if (block.fields_present & (1 << NUMBER)) == 0 {
+        return None;
+    }
+    block.to_header()
 }
@@ -73,38 +73,11 @@ pub fn hex_to_b256(hex: &[u8]) -> B256 {
     B256::from_slice(&bytes)
 }
 
+/// Same as `quantity_to_u64`. Digits beyond the low 16 are dropped rather
+/// than written past the 8 output bytes
 #[inline]
 pub fn hex_to_u64(hex: &[u8]) -> U64 {
-    let mut bytes = [0u8; 8];
-
-    // Calculate actual number of bytes from hex length
-    let hex_len = hex.len();
-    let byte_len = hex_len / 2;
-
-    let start_idx = 8_usize.saturating_sub(byte_len);
-
-    unsafe {
-        let hex_ptr = hex.as_ptr();
-        let out_ptr = bytes.as_mut_ptr();
-
-        for i in 0..byte_len {
-            let high = match *hex_ptr.add(i * 2) {
-                b @ b'0'..=b'9' => b - b'0',
-                b @ b'a'..=b'f' => b - b'a' + 10,
-                b @ b'A'..=b'F' => b - b'A' + 10,
-                _ => 0,
-            };
-            let low = match *hex_ptr.add(i * 2 + 1) {
-                b @ b'0'..=b'9' => b - b'0',
-                b @ b'a'..=b'f' => b - b'a' + 10,
-                b @ b'A'..=b'F' => b - b'A' + 10,
-                _ => 0,
-            };
-            *out_ptr.add(start_idx + i) = (high << 4) | low;
-        }
-    }
-
-    U64::from_be_bytes(bytes)
+    quantity_to_u64(hex)
 }
 
 pub fn hex_to_bytes(hex: &[u8], out: &mut [u8]) -> Result<(), &'static str> {
@@ -176,6 +149,64 @@ pub fn hex_to_u256(hex: &[u8]) -> U256 {
     U256::from_be_bytes(bytes)
 }
 
+#[inline]
+fn hex_nibble(b: u8) -> u8 {
+    match b {
+        b'0'..=b'9' => b - b'0',
+        b'a'..=b'f' => b - b'a' + 10,
+        b'A'..=b'F' => b - b'A' + 10,
+        _ => 0,
+    }
+}
+
+#[inline]
+fn strip_hex_prefix(hex: &[u8]) -> &[u8] {
+    if hex.len() >= 2 && hex[0] == b'0' && (hex[1] == b'x' || hex[1] == b'X') {
+        &hex[2..]
+    } else {
+        hex
+    }
+}
+
+/// Parses a JSON-RPC quantity, with or without the `0x` prefix. Quantities
+/// carry no leading zeros so the digit count is frequently odd ("0x1"), which
+/// is why this folds nibble by nibble
+#[inline]
+pub fn quantity_to_u64(hex: &[u8]) -> U64 {
+    let hex = strip_hex_prefix(hex);
+
+    let mut value = 0u64;
+    for &b in &hex[hex.len().saturating_sub(16)..] {
+        value = (value << 4) | hex_nibble(b) as u64;
+    }
+
+    U64::from(value)
+}
+
+/// `quantity_to_u64` for values that may not fit 64 bits
+#[inline]
+pub fn quantity_to_u256(hex: &[u8]) -> U256 {
+    let mut bytes = [0u8; 32];
+    let hex = strip_hex_prefix(hex);
+
+    // Fill from the least significant end so odd digit counts line up
+    let mut out = 32;
+    let mut end = hex.len();
+    while end > 0 && out > 0 {
+        let low = hex_nibble(hex[end - 1]);
+        let high = if end >= 2 {
+            hex_nibble(hex[end - 2])
+        } else {
+            0
+        };
+        out -= 1;
+        bytes[out] = (high << 4) | low;
+        end = end.saturating_sub(2);
+    }
+
+    U256::from_be_bytes(bytes)
+}
+
 #[inline]
 pub fn find_field(data: &[u8], prefix: &[u8], suffix: &[u8]) -> Option<(usize, usize)> {
     let start = memchr::memmem::find(data, prefix)?;
@@ -183,3 +214,176 @@ pub fn find_field(data: &[u8], prefix: &[u8], suffix: &[u8]) -> Option<(usize, u
     let end = start + memchr::memmem::find(&data[start..], suffix)?;
     Some((start, end))
 }
+
+#[inline]
+fn skip_whitespace(data: &[u8], mut pos: usize) -> usize {
+    while pos < data.len() && data[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+/// Returns the index just past the JSON value that starts at `pos`
+#[inline]
+pub fn skip_value(data: &[u8], mut pos: usize) -> usize {
+    let len = data.len();
+    match data.get(pos) {
+        Some(b'"') => {
+            pos += 1;
+            while pos < len {
+                match data[pos] {
+                    b'\\' => pos += 2,
+                    b'"' => return pos + 1,
+                    _ => pos += 1,
+                }
+            }
+            len
+        },
+        Some(b'{') | Some(b'[') => {
+            let mut depth = 0usize;
+            while pos < len {
+                match data[pos] {
+                    b'"' => {
+                        pos = skip_value(data, pos);
+                        continue;
+                    },
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return pos + 1;
+                        }
+                    },
+                    _ => {},
+                }
+                pos += 1;
+            }
+            len
+        },
+        // numbers, true, false and null
+        _ => {
+            while pos < len
+                && !matches!(data[pos], b',' | b'}' | b']')
+                && !data[pos].is_ascii_whitespace()
+            {
+                pos += 1;
+            }
+            pos
+        },
+    }
+}
+
+/// Iterator over the top-level members of a JSON object, yielding each key
+/// (without quotes) and the range of its raw value. Nested objects and arrays
+/// are skipped over, never descended into.
+#[derive(Debug)]
+pub struct ObjectMembers<'a> {
+    data: &'a [u8],
+    pos: Option<usize>,
+}
+
+impl<'a> Iterator for ObjectMembers<'a> {
+    type Item = (&'a [u8], (usize, usize));
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self.data;
+        let mut pos = self.pos.take()?;
+
+        loop {
+            pos = skip_whitespace(data, pos);
+            match data.get(pos)? {
+                b'}' => return None,
+                b',' => {
+                    pos += 1;
+                    continue;
+                },
+                b'"' => break,
+                _ => return None,
+            }
+        }
+
+        let key_end = skip_value(data, pos);
+        if key_end < pos + 2 {
+            return None;
+        }
+        let name = &data[pos + 1..key_end - 1];
+
+        pos = skip_whitespace(data, key_end);
+        if data.get(pos) != Some(&b':') {
+            return None;
+        }
+        pos = skip_whitespace(data, pos + 1);
+
+        let value_end = skip_value(data, pos);
+        self.pos = Some(value_end);
+        Some((name, (pos, value_end)))
+    }
+}
+
+pub fn object_members(data: &[u8]) -> ObjectMembers<'_> {
+    let pos = skip_whitespace(data, 0);
+    ObjectMembers {
+        data,
+        pos: (data.get(pos) == Some(&b'{')).then_some(pos + 1),
+    }
+}
+
+/// Records where the wanted top-level members of a JSON object are, in one
+/// pass. Strings lose their quotes, arrays and objects are kept whole and
+/// `null` counts as absent. Bit `i` of the mask is set when `names[i]` was found
+pub fn index_members<const N: usize>(
+    data: &[u8],
+    names: &[&[u8]; N],
+) -> ([(usize, usize); N], u32) {
+    debug_assert!(N <= 32);
+    let mut fields = [(0, 0); N];
+    let mut present = 0u32;
+
+    for (name, (start, end)) in object_members(data) {
+        let Some(idx) = names.iter().position(|field| *field == name) else {
+            continue;
+        };
+
+        fields[idx] = match data[start] {
+            b'"' if end - start >= 2 => (start + 1, end - 1),
+            b'[' | b'{' => (start, end),
+            _ => continue,
+        };
+        present |= 1 << idx;
+    }
+
+    (fields, present)
+}
+
+/// Finds `key` among the top-level members of the JSON object in `data` and
+/// returns the range of its raw value (quotes included for strings).
+/// Unlike `find_field` this never matches keys of nested objects.
+pub fn top_level_field(data: &[u8], key: &[u8]) -> Option<(usize, usize)> {
+    object_members(data).find(|(name, _)| *name == key).map(|(_, range)| range)
+}
+
+/// Splits a top-level JSON array into the ranges of its elements
+pub fn split_array(data: &[u8]) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    let mut pos = skip_whitespace(data, 0);
+    if data.get(pos) != Some(&b'[') {
+        return result;
+    }
+    pos += 1;
+
+    loop {
+        pos = skip_whitespace(data, pos);
+        match data.get(pos) {
+            None | Some(b']') => return result,
+            Some(b',') => pos += 1,
+            Some(_) => {
+                let end = skip_value(data, pos);
+                if end == pos {
+                    return result;
+                }
+                result.push((pos, end));
+                pos = end;
+            },
+        }
+    }
+}
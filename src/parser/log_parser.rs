@@ -1,10 +1,7 @@
-use alloy::{
-    hex,
-    primitives::{Address, B256, U64},
-};
+use alloy::primitives::{Address, B256, U64};
 
 use super::{
-    lib::{find_field, hex_to_b256, hex_to_u64, unsafe_hex_to_address},
+    lib::{find_field, hex_to_b256, quantity_to_u64, unsafe_hex_to_address},
     types::{Log, RawJsonResponse},
 };
 
@@ -14,12 +11,14 @@ pub struct RawLog<'a> {
     // Field positions
     address: (usize, usize),
     topics: [(usize, usize); 4],
+    topic_count: usize,
     data_field: (usize, usize),
     block_number: (usize, usize),
     block_hash: (usize, usize),
     tx_hash: (usize, usize),
     tx_index: (usize, usize),
     log_index: (usize, usize),
+    removed: bool,
 }
 
 #[derive(Debug)]
@@ -118,46 +117,55 @@ impl<'a> Iterator for LogIterator<'a> {
 
 impl<'a> RawLog<'a> {
     #[inline]
-    fn parse(input: &'a [u8]) -> Option<Self> {
+    pub(crate) fn parse(input: &'a [u8]) -> Option<Self> {
         let address = find_field(input, b"\"address\":\"", b"\"")?;
-        let topics = parse_topics_array(input)?;
+        let (topics, topic_count) = parse_topics_array(input)?;
         let data = find_field(input, b"\"data\":\"", b"\"")?;
         let block_number = find_field(input, b"\"blockNumber\":\"", b"\"")?;
         let block_hash = find_field(input, b"\"blockHash\":\"", b"\"")?;
         let tx_hash = find_field(input, b"\"transactionHash\":\"", b"\"")?;
         let tx_index = find_field(input, b"\"transactionIndex\":\"", b"\"")?;
         let log_index = find_field(input, b"\"logIndex\":\"", b"\"")?;
+        let removed = memchr::memmem::find(input, b"\"removed\":true").is_some();
 
         Some(Self {
             data: input,
             address,
             topics,
+            topic_count,
             data_field: data,
             block_number,
             block_hash,
             tx_hash,
             tx_index,
             log_index,
+            removed,
         })
     }
 
     // Accessors that convert to final types
     #[inline]
     pub fn address(&self) -> Address {
-        let bytes = &self.data[self.address.0..self.address.1];
-        unsafe_hex_to_address(&bytes[2..])
+        unsafe_hex_to_address(&self.data[self.address.0..self.address.1])
     }
 
+    /// Topics in order, unused trailing slots are zero
     #[inline]
     pub fn topics(&self) -> [B256; 4] {
         let mut result = [B256::default(); 4];
-        for (i, &(start, end)) in self.topics.iter().enumerate() {
+        for (i, &(start, end)) in self.topics[..self.topic_count].iter().enumerate() {
             let bytes = &self.data[start..end];
             result[i] = hex_to_b256(&bytes[2..]);
         }
         result
     }
 
+    #[inline]
+    pub fn topic_count(&self) -> usize {
+        self.topic_count
+    }
+
+    /// Data as it appears in the response, `0x` prefixed hex
     #[inline]
     pub fn data(&self) -> &'a [u8] {
         &self.data[self.data_field.0..self.data_field.1]
@@ -166,7 +174,7 @@ impl<'a> RawLog<'a> {
     #[inline]
     pub fn block_number(&self) -> U64 {
         let bytes = &self.data[self.block_number.0..self.block_number.1];
-        hex_to_u64(&bytes[2..])
+        quantity_to_u64(bytes)
     }
 
     #[inline]
@@ -184,54 +192,65 @@ impl<'a> RawLog<'a> {
     #[inline]
     pub fn transaction_index(&self) -> U64 {
         let bytes = &self.data[self.tx_index.0..self.tx_index.1];
-        hex_to_u64(&bytes[2..])
+        quantity_to_u64(bytes)
     }
 
     #[inline]
     pub fn log_index(&self) -> U64 {
         let bytes = &self.data[self.log_index.0..self.log_index.1];
-        hex_to_u64(&bytes[2..])
+        quantity_to_u64(bytes)
     }
 
     #[inline]
     pub fn to_log(&self) -> Log {
         Log {
             address: self.address(),
-            topics: self.topics().to_vec(),
-            data: hex::encode_prefixed(self.data()),
+            topics: self.topics()[..self.topic_count].to_vec(),
+            data: String::from_utf8_lossy(self.data()).into_owned(),
             block_number: Some(self.block_number()),
             block_hash: Some(self.block_hash()),
             transaction_hash: Some(self.transaction_hash()),
             transaction_index: Some(self.transaction_index()),
             log_index: Some(self.log_index()),
-            // ISSSUE: This is not correct
-            removed: Some(false),
+            removed: Some(self.removed),
         }
     }
 }
 
+/// Returns the topic ranges and how many of the (at most 4) slots are used
 #[inline]
-fn parse_topics_array(data: &[u8]) -> Option<[(usize, usize); 4]> {
-    let pos = memchr::memmem::find(data, b"\"topics\":[")? + 10; // Hard-coded length
+fn parse_topics_array(data: &[u8]) -> Option<([(usize, usize); 4], usize)> {
+    let mut current = memchr::memmem::find(data, b"\"topics\":[")? + 10; // Hard-coded length
     let mut result = [(0, 0); 4];
+    let mut count = 0;
 
-    unsafe {
-        let mut current = pos;
-        for item in &mut result {
-            while data.get_unchecked(current) != &b'"' {
-                current += 1;
+    while count < 4 {
+        while *data.get(current)? != b'"' {
+            if data[current] == b']' {
+                return Some((result, count));
             }
-            // skip opening quote
             current += 1;
+        }
+        // skip opening quote
+        current += 1;
 
-            // Each topic is exactly 66 bytes (including 0x)
-            *item = (current, current + 66);
-            // skip topic and closing quote
-            current += 67;
+        // Each topic is exactly 66 bytes (including 0x)
+        if current + 66 > data.len() {
+            return None;
         }
+        result[count] = (current, current + 66);
+        // skip topic and closing quote
+        current += 67;
+        count += 1;
     }
 
-    Some(result)
+    Some((result, count))
+}
+
+/// Parses a bare log object such as the payload of a `logs` notification
+#[inline]
+pub fn parse_log(input: &[u8]) -> Option<Log> {
+    RawLog::parse(input).map(|log| log.to_log())
 }
 
 pub fn parse_logs(input: &[u8]) -> Vec<Log> {
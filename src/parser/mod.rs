@@ -2,5 +2,7 @@ pub mod block_parser;
 pub mod lib;
 pub mod log_parser;
 pub mod parser_for_small_response;
+pub mod proof_parser;
+pub mod receipt_parser;
 pub mod tx_parser;
 pub mod types;
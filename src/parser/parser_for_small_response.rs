@@ -1,14 +1,46 @@
-use super::lib::find_field;
+use alloy::primitives::U256;
 
+use super::{
+    lib::{
+        find_field, index_members, quantity_to_u256, quantity_to_u64, split_array, top_level_field,
+    },
+    types::FeeHistory,
+};
+
+/// `eth_feeHistory` result as ranges into the response, strings without
+/// their quotes. Blob fields are empty before Cancun and `reward` when no
+/// percentiles were asked for
 #[derive(Debug)]
 pub struct RawFee {
     pub oldest_block: (usize, usize),
-    pub reward: Vec<(usize, usize)>,
+    /// One row of percentile rewards per block
+    pub reward: Vec<Vec<(usize, usize)>>,
     pub base_fee_per_gas: Vec<(usize, usize)>,
     pub gas_used_ratio: Vec<(usize, usize)>,
     pub base_fee_per_blobs_gas: Vec<(usize, usize)>,
+    pub blob_gas_used_ratio: Vec<(usize, usize)>,
 }
 
+// Field indices for fast lookup
+const FEE_FIELD_COUNT: usize = 6;
+const OLDEST_BLOCK: usize = 0;
+const REWARD: usize = 1;
+const BASE_FEE_PER_GAS: usize = 2;
+const GAS_USED_RATIO: usize = 3;
+const BASE_FEE_PER_BLOB_GAS: usize = 4;
+const BLOB_GAS_USED_RATIO: usize = 5;
+
+static FEE_FIELD_NAMES: [&[u8]; FEE_FIELD_COUNT] = [
+    b"oldestBlock",
+    b"reward",
+    b"baseFeePerGas",
+    b"gasUsedRatio",
+    b"baseFeePerBlobGas",
+    b"blobGasUsedRatio",
+];
+
+const FEE_REQUIRED: u32 = (1 << OLDEST_BLOCK) | (1 << BASE_FEE_PER_GAS) | (1 << GAS_USED_RATIO);
+
 /// single data reply from the rpc call
 #[derive(Debug)]
 pub struct Generic {
@@ -23,43 +55,80 @@ pub struct RawJsonResponse<'a> {
 }
 
 impl RawFee {
+    /// Takes either a full response or the bare result object
     #[inline]
     pub fn parse(input: &[u8]) -> Option<Self> {
+        let offset = top_level_field(input, b"result").map_or(0, |(start, _)| start);
+        let result = &input[offset..];
+        let (fields, present) = index_members(result, &FEE_FIELD_NAMES);
+        if present & FEE_REQUIRED != FEE_REQUIRED {
+            return None;
+        }
+
+        let (start, end) = fields[OLDEST_BLOCK];
+        let array = |idx: usize| {
+            if present & (1 << idx) == 0 {
+                return Vec::new();
+            }
+            let (start, _) = fields[idx];
+            parse_array(input, offset + start)
+        };
+        let reward = array(REWARD)
+            .into_iter()
+            // Rows are arrays themselves, anything else is not a reward
+            .map(|(start, _)| (input[start] == b'[').then(|| parse_array(input, start)))
+            .collect::<Option<Vec<_>>>()?;
+
         Some(Self {
-            oldest_block: find_field(input, b"\"oldestBlock\":\"", b"\"")?,
-            reward: parse_array(input, b"\"reward\":")?,
-            base_fee_per_gas: parse_array(input, b"\"baseFeePerGas\":")?,
-            gas_used_ratio: parse_array(input, b"\"gasUsedRatio\":")?,
-            base_fee_per_blobs_gas: parse_array(input, b"\"baseFeePerBlobGas\":")?,
+            oldest_block: (offset + start, offset + end),
+            reward,
+            base_fee_per_gas: array(BASE_FEE_PER_GAS),
+            gas_used_ratio: array(GAS_USED_RATIO),
+            base_fee_per_blobs_gas: array(BASE_FEE_PER_BLOB_GAS),
+            blob_gas_used_ratio: array(BLOB_GAS_USED_RATIO),
         })
     }
-}
-
-#[inline]
-fn parse_array(data: &[u8], field: &[u8]) -> Option<Vec<(usize, usize)>> {
-    let start = memchr::memmem::find(data, field)? + field.len();
-    let mut pos = start;
-    let mut result = Vec::new();
 
-    while data[pos] != b']' {
-        while data[pos] != b'"' && data[pos] != b']' {
-            pos += 1;
-        }
-        if data[pos] == b']' {
-            break;
-        }
-
-        pos += 1;
-        let value_start = pos;
+    /// Decodes the ranges found by `parse` in the same `input`
+    pub fn to_fee_history(&self, input: &[u8]) -> Option<FeeHistory> {
+        let slice = |(start, end): (usize, usize)| &input[start..end];
+        let fees = |ranges: &[(usize, usize)]| -> Vec<U256> {
+            ranges.iter().map(|&range| quantity_to_u256(slice(range))).collect()
+        };
+        let ratios = |ranges: &[(usize, usize)]| -> Option<Vec<f64>> {
+            ranges
+                .iter()
+                .map(|&range| std::str::from_utf8(slice(range)).ok()?.parse().ok())
+                .collect()
+        };
 
-        while data[pos] != b'"' {
-            pos += 1;
-        }
-        result.push((value_start, pos));
-        pos += 1;
+        Some(FeeHistory {
+            oldest_block: quantity_to_u64(slice(self.oldest_block)),
+            base_fee_per_gas: fees(&self.base_fee_per_gas),
+            base_fee_per_blob_gas: fees(&self.base_fee_per_blobs_gas),
+            gas_used_ratio: ratios(&self.gas_used_ratio)?,
+            blob_gas_used_ratio: ratios(&self.blob_gas_used_ratio)?,
+            reward: self.reward.iter().map(|row| fees(row)).collect(),
+        })
     }
+}
+
+/// Ranges of the elements of the array starting at `start`, absolute in
+/// `data`. Strings lose their quotes, numbers and nested arrays are kept as is
+#[inline]
+fn parse_array(data: &[u8], start: usize) -> Vec<(usize, usize)> {
+    split_array(&data[start..])
+        .into_iter()
+        .map(|(begin, end)| match data[start + begin] {
+            b'"' if end - begin >= 2 => (start + begin + 1, start + end - 1),
+            _ => (start + begin, start + end),
+        })
+        .collect()
+}
 
-    Some(result)
+/// Parses an `eth_feeHistory` response
+pub fn parse_fee_history(input: &[u8]) -> Option<FeeHistory> {
+    RawFee::parse(input)?.to_fee_history(input)
 }
 
 impl Generic {
@@ -0,0 +1,151 @@
+use alloy::{
+    hex,
+    primitives::{Address, Bytes, B256, U256, U64},
+};
+
+use super::{
+    lib::{
+        hex_to_b256, index_members, quantity_to_u256, quantity_to_u64, split_array,
+        unsafe_hex_to_address,
+    },
+    types::{AccountProof, RawJsonResponse, StorageProof},
+};
+
+// Field indices for fast lookup
+const FIELD_COUNT: usize = 7;
+const ADDRESS: usize = 0;
+const BALANCE: usize = 1;
+const NONCE: usize = 2;
+const CODE_HASH: usize = 3;
+const STORAGE_HASH: usize = 4;
+const ACCOUNT_PROOF: usize = 5;
+const STORAGE_PROOF: usize = 6;
+
+static FIELD_NAMES: [&[u8]; FIELD_COUNT] = [
+    b"address",
+    b"balance",
+    b"nonce",
+    b"codeHash",
+    b"storageHash",
+    b"accountProof",
+    b"storageProof",
+];
+
+const REQUIRED: u32 = (1 << FIELD_COUNT) - 1;
+
+static STORAGE_FIELDS: [&[u8]; 3] = [b"key", b"value", b"proof"];
+
+/// `eth_getProof` result located in a response buffer
+#[derive(Debug)]
+pub struct RawAccountProof<'a> {
+    data: &'a [u8],
+    // Strings without their quotes, arrays as is
+    fields: [(usize, usize); FIELD_COUNT],
+}
+
+impl<'a> RawAccountProof<'a> {
+    #[inline]
+    pub fn parse(input: &'a [u8]) -> Option<Self> {
+        let (fields, fields_present) = index_members(input, &FIELD_NAMES);
+
+        if fields_present & REQUIRED != REQUIRED {
+            return None;
+        }
+
+        Some(Self {
+            data: input,
+            fields,
+        })
+    }
+
+    #[inline]
+    fn field(&self, idx: usize) -> &'a [u8] {
+        let (start, end) = self.fields[idx];
+        &self.data[start..end]
+    }
+
+    #[inline]
+    pub fn address(&self) -> Option<Address> {
+        let address = self.field(ADDRESS);
+        (address.len() == 42).then(|| unsafe_hex_to_address(address))
+    }
+
+    #[inline]
+    pub fn balance(&self) -> U256 {
+        quantity_to_u256(self.field(BALANCE))
+    }
+
+    #[inline]
+    pub fn nonce(&self) -> U64 {
+        quantity_to_u64(self.field(NONCE))
+    }
+
+    #[inline]
+    pub fn storage_hash(&self) -> Option<B256> {
+        to_b256(self.field(STORAGE_HASH))
+    }
+
+    pub fn to_account_proof(&self) -> Option<AccountProof> {
+        let storage = self.field(STORAGE_PROOF);
+        let storage_proof = split_array(storage)
+            .into_iter()
+            .map(|(start, end)| parse_storage_proof(&storage[start..end]))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(AccountProof {
+            address: self.address()?,
+            balance: self.balance(),
+            nonce: self.nonce(),
+            code_hash: to_b256(self.field(CODE_HASH))?,
+            storage_hash: self.storage_hash()?,
+            account_proof: parse_nodes(self.field(ACCOUNT_PROOF))?,
+            storage_proof,
+        })
+    }
+}
+
+fn parse_storage_proof(item: &[u8]) -> Option<StorageProof> {
+    let (fields, present) = index_members(item, &STORAGE_FIELDS);
+    if present != 0b111 {
+        return None;
+    }
+    let field = |idx: usize| &item[fields[idx].0..fields[idx].1];
+
+    Some(StorageProof {
+        // Some nodes echo the slot back without padding
+        key: B256::from(quantity_to_u256(field(0))),
+        value: quantity_to_u256(field(1)),
+        proof: parse_nodes(field(2))?,
+    })
+}
+
+/// Array of hex encoded trie nodes
+fn parse_nodes(array: &[u8]) -> Option<Vec<Bytes>> {
+    split_array(array)
+        .into_iter()
+        .map(|(start, end)| {
+            let node = array[start..end].strip_prefix(b"\"")?.strip_suffix(b"\"")?;
+            hex::decode(node).ok().map(Bytes::from)
+        })
+        .collect()
+}
+
+/// `0x` prefixed 32 byte hex
+#[inline]
+fn to_b256(bytes: &[u8]) -> Option<B256> {
+    (bytes.len() == 66).then(|| hex_to_b256(bytes))
+}
+
+impl<'a> RawJsonResponse<'a> {
+    #[inline]
+    pub fn account_proof(&self) -> Option<RawAccountProof<'a>> {
+        RawAccountProof::parse(&self.data[self.result_start..self.result_end])
+    }
+}
+
+/// Parses an `eth_getProof` response
+pub fn parse_proof(input: &[u8]) -> Option<AccountProof> {
+    RawJsonResponse::parse_result(input)
+        .and_then(|r| r.account_proof())
+        .and_then(|proof| proof.to_account_proof())
+}
@@ -0,0 +1,220 @@
+use alloy::primitives::{Address, B256, U256, U64};
+
+use super::{
+    lib::{
+        hex_to_b256, index_members, quantity_to_u256, quantity_to_u64, split_array,
+        top_level_field, unsafe_hex_to_address,
+    },
+    log_parser::RawLog,
+    types::{RawJsonResponse, Receipt},
+};
+
+// Field indices for fast lookup
+const FIELD_COUNT: usize = 17;
+const TRANSACTION_HASH: usize = 0;
+const TRANSACTION_INDEX: usize = 1;
+const BLOCK_HASH: usize = 2;
+const BLOCK_NUMBER: usize = 3;
+const FROM: usize = 4;
+const TO: usize = 5;
+const STATUS: usize = 6;
+const ROOT: usize = 7;
+const CUMULATIVE_GAS_USED: usize = 8;
+const GAS_USED: usize = 9;
+const EFFECTIVE_GAS_PRICE: usize = 10;
+const BLOB_GAS_USED: usize = 11;
+const BLOB_GAS_PRICE: usize = 12;
+const CONTRACT_ADDRESS: usize = 13;
+const LOGS: usize = 14;
+const LOGS_BLOOM: usize = 15;
+const TYPE: usize = 16;
+
+static FIELD_NAMES: [&[u8]; FIELD_COUNT] = [
+    b"transactionHash",
+    b"transactionIndex",
+    b"blockHash",
+    b"blockNumber",
+    b"from",
+    b"to",
+    b"status",
+    b"root",
+    b"cumulativeGasUsed",
+    b"gasUsed",
+    b"effectiveGasPrice",
+    b"blobGasUsed",
+    b"blobGasPrice",
+    b"contractAddress",
+    b"logs",
+    b"logsBloom",
+    b"type",
+];
+
+const REQUIRED: u32 = (1 << TRANSACTION_HASH) | (1 << BLOCK_HASH) | (1 << BLOCK_NUMBER);
+
+/// Receipt located in a response buffer. Unlike the other raw parsers this
+/// walks the top-level members once, since the nested logs repeat keys such
+/// as `blockHash` and `transactionHash`.
+#[derive(Debug)]
+pub struct RawReceipt<'a> {
+    data: &'a [u8],
+    // Strings without their quotes, the logs array as is
+    fields: [(usize, usize); FIELD_COUNT],
+    fields_present: u32,
+}
+
+impl<'a> RawReceipt<'a> {
+    #[inline]
+    pub fn parse(input: &'a [u8]) -> Option<Self> {
+        let (fields, fields_present) = index_members(input, &FIELD_NAMES);
+
+        if fields_present & REQUIRED != REQUIRED {
+            return None;
+        }
+
+        Some(Self {
+            data: input,
+            fields,
+            fields_present,
+        })
+    }
+
+    #[inline]
+    fn field(&self, idx: usize) -> Option<&'a [u8]> {
+        if self.fields_present & (1 << idx) == 0 {
+            return None;
+        }
+        let (start, end) = self.fields[idx];
+        Some(&self.data[start..end])
+    }
+
+    #[inline]
+    fn u64_field(&self, idx: usize) -> Option<U64> {
+        self.field(idx).map(quantity_to_u64)
+    }
+
+    #[inline]
+    fn u256_field(&self, idx: usize) -> Option<U256> {
+        self.field(idx).map(quantity_to_u256)
+    }
+
+    #[inline]
+    fn b256_field(&self, idx: usize) -> Option<B256> {
+        self.field(idx).filter(|bytes| bytes.len() == 66).map(hex_to_b256)
+    }
+
+    #[inline]
+    fn address_field(&self, idx: usize) -> Option<Address> {
+        self.field(idx).filter(|bytes| bytes.len() == 42).map(unsafe_hex_to_address)
+    }
+
+    #[inline]
+    pub fn transaction_hash(&self) -> B256 {
+        self.b256_field(TRANSACTION_HASH).unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn block_hash(&self) -> B256 {
+        self.b256_field(BLOCK_HASH).unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn block_number(&self) -> U64 {
+        self.u64_field(BLOCK_NUMBER).unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn status(&self) -> Option<U64> {
+        self.u64_field(STATUS)
+    }
+
+    #[inline]
+    pub fn gas_used(&self) -> U256 {
+        self.u256_field(GAS_USED).unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn contract_address(&self) -> Option<Address> {
+        self.address_field(CONTRACT_ADDRESS)
+    }
+
+    /// Logs emitted by the transaction, parsed lazily
+    #[inline]
+    pub fn logs(&self) -> impl Iterator<Item = RawLog<'a>> + 'a {
+        let logs = self.field(LOGS).unwrap_or_default();
+        split_array(logs)
+            .into_iter()
+            .filter_map(move |(start, end)| RawLog::parse(&logs[start..end]))
+    }
+
+    #[inline]
+    pub fn to_receipt(&self) -> Receipt {
+        Receipt {
+            transaction_hash: self.transaction_hash(),
+            transaction_index: self.u64_field(TRANSACTION_INDEX).unwrap_or_default(),
+            block_hash: self.block_hash(),
+            block_number: self.block_number(),
+            from: self.address_field(FROM).unwrap_or_default(),
+            to: self.address_field(TO),
+            status: self.status(),
+            root: self.b256_field(ROOT),
+            cumulative_gas_used: self.u256_field(CUMULATIVE_GAS_USED).unwrap_or_default(),
+            gas_used: self.gas_used(),
+            effective_gas_price: self.u256_field(EFFECTIVE_GAS_PRICE).unwrap_or_default(),
+            blob_gas_used: self.u64_field(BLOB_GAS_USED),
+            blob_gas_price: self.u256_field(BLOB_GAS_PRICE),
+            contract_address: self.contract_address(),
+            logs: self.logs().map(|log| log.to_log()).collect(),
+            logs_bloom: self
+                .field(LOGS_BLOOM)
+                .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+                .unwrap_or_default(),
+            transaction_type: self.u64_field(TYPE).unwrap_or_default(),
+        }
+    }
+}
+
+impl<'a> RawJsonResponse<'a> {
+    /// Locates the top-level `result`, which is `null` for unknown receipts
+    #[inline]
+    pub fn parse_result(input: &'a [u8]) -> Option<Self> {
+        let (start, end) = top_level_field(input, b"result")?;
+        if input[start..end] == *b"null" {
+            return None;
+        }
+
+        Some(Self {
+            data: input,
+            result_start: start,
+            result_end: end,
+        })
+    }
+
+    #[inline]
+    pub fn receipt(&self) -> Option<RawReceipt<'a>> {
+        RawReceipt::parse(&self.data[self.result_start..self.result_end])
+    }
+
+    /// Receipts of an `eth_getBlockReceipts` result
+    #[inline]
+    pub fn receipts(&self) -> impl Iterator<Item = RawReceipt<'a>> + 'a {
+        let result = &self.data[self.result_start..self.result_end];
+        split_array(result)
+            .into_iter()
+            .filter_map(move |(start, end)| RawReceipt::parse(&result[start..end]))
+    }
+}
+
+/// Parses an `eth_getTransactionReceipt` response, `None` while pending
+pub fn parse_receipt(input: &[u8]) -> Option<Receipt> {
+    RawJsonResponse::parse_result(input)
+        .and_then(|r| r.receipt())
+        .map(|receipt| receipt.to_receipt())
+}
+
+/// Parses an `eth_getBlockReceipts` response
+pub fn parse_block_receipts(input: &[u8]) -> Vec<Receipt> {
+    match RawJsonResponse::parse_result(input) {
+        Some(response) => response.receipts().map(|receipt| receipt.to_receipt()).collect(),
+        None => Vec::new(),
+    }
+}
@@ -1,152 +1,323 @@
-use alloy::{
-    hex,
-    primitives::{Address, B256, U256, U64},
-};
+use alloy::primitives::{Address, B256, U256, U64};
 
 use super::{
-    lib::{find_field, hex_to_b256, hex_to_u256, hex_to_u64, unsafe_hex_to_address},
-    types::{RawJsonResponse, TransactionTx},
+    lib::{
+        hex_to_b256, index_members, quantity_to_u256, quantity_to_u64, split_array,
+        unsafe_hex_to_address,
+    },
+    types::{
+        AccessListItem, RawJsonResponse, SignedAuthorization, TransactionTx, TypedTransaction,
+    },
 };
 
+// Field indices for fast lookup
+const FIELD_COUNT: usize = 23;
+const BLOCK_HASH: usize = 0;
+const BLOCK_NUMBER: usize = 1;
+const HASH: usize = 2;
+const INPUT: usize = 3;
+const R: usize = 4;
+const S: usize = 5;
+const V: usize = 6;
+const Y_PARITY: usize = 7;
+const GAS: usize = 8;
+const GAS_PRICE: usize = 9;
+const MAX_FEE_PER_GAS: usize = 10;
+const MAX_PRIORITY_FEE_PER_GAS: usize = 11;
+const MAX_FEE_PER_BLOB_GAS: usize = 12;
+const FROM: usize = 13;
+const TX_INDEX: usize = 14;
+const TO: usize = 15;
+const VALUE: usize = 16;
+const NONCE: usize = 17;
+const TYPE: usize = 18;
+const CHAIN_ID: usize = 19;
+const ACCESS_LIST: usize = 20;
+const BLOB_VERSIONED_HASHES: usize = 21;
+const AUTHORIZATION_LIST: usize = 22;
+
+static FIELD_NAMES: [&[u8]; FIELD_COUNT] = [
+    b"blockHash",
+    b"blockNumber",
+    b"hash",
+    b"input",
+    b"r",
+    b"s",
+    b"v",
+    b"yParity",
+    b"gas",
+    b"gasPrice",
+    b"maxFeePerGas",
+    b"maxPriorityFeePerGas",
+    b"maxFeePerBlobGas",
+    b"from",
+    b"transactionIndex",
+    b"to",
+    b"value",
+    b"nonce",
+    b"type",
+    b"chainId",
+    b"accessList",
+    b"blobVersionedHashes",
+    b"authorizationList",
+];
+
+const REQUIRED: u32 =
+    (1 << HASH) | (1 << FROM) | (1 << NONCE) | (1 << GAS) | (1 << VALUE) | (1 << INPUT);
+
+static AUTHORIZATION_FIELDS: [&[u8]; 6] =
+    [b"chainId", b"address", b"nonce", b"yParity", b"r", b"s"];
+static ACCESS_LIST_FIELDS: [&[u8]; 2] = [b"address", b"storageKeys"];
+
+/// Transaction located in a response buffer. Members are indexed at the top
+/// level only, 7702 authorization tuples repeat `nonce`, `r` and `s`.
+/// Block fields are absent for pending transactions, `to` for creations and
+/// `gasPrice`/`v` for some typed transactions.
 #[derive(Debug)]
 pub struct RawTx<'a> {
     data: &'a [u8],
-    // Field positions (start, end)
-    block_hash: (usize, usize),
-    block_number: (usize, usize),
-    hash: (usize, usize),
-    input: (usize, usize),
-    r: (usize, usize),
-    s: (usize, usize),
-    v: (usize, usize),
-    gas: (usize, usize),
-    gas_price: (usize, usize),
-    from: (usize, usize),
-    tx_index: (usize, usize),
-    to: (usize, usize),
-    value: (usize, usize),
-    nonce: (usize, usize),
+    // Strings without their quotes, arrays as is
+    fields: [(usize, usize); FIELD_COUNT],
+    fields_present: u32,
 }
 
 impl<'a> RawTx<'a> {
     #[inline]
-    fn parse(input: &'a [u8]) -> Option<Self> {
-        // Find positions of all fields
+    pub(crate) fn parse(input: &'a [u8]) -> Option<Self> {
+        let (fields, fields_present) = index_members(input, &FIELD_NAMES);
+
+        if fields_present & REQUIRED != REQUIRED {
+            return None;
+        }
+
         Some(Self {
             data: input,
-            block_hash: find_field(input, b"\"blockHash\":\"", b"\"")?,
-            block_number: find_field(input, b"\"blockNumber\":\"", b"\"")?,
-            hash: find_field(input, b"\"hash\":\"", b"\"")?,
-            input: find_field(input, b"\"input\":\"", b"\"")?,
-            r: find_field(input, b"\"r\":\"", b"\"")?,
-            s: find_field(input, b"\"s\":\"", b"\"")?,
-            v: find_field(input, b"\"v\":\"", b"\"")?,
-            gas: find_field(input, b"\"gas\":\"", b"\"")?,
-            gas_price: find_field(input, b"\"gasPrice\":\"", b"\"")?,
-            from: find_field(input, b"\"from\":\"", b"\"")?,
-            tx_index: find_field(input, b"\"transactionIndex\":\"", b"\"")?,
-            to: find_field(input, b"\"to\":\"", b"\"")?,
-            value: find_field(input, b"\"value\":\"", b"\"")?,
-            nonce: find_field(input, b"\"nonce\":\"", b"\"")?,
+            fields,
+            fields_present,
         })
     }
 
     #[inline]
-    pub fn block_hash(&self) -> B256 {
-        let bytes = &self.data[self.block_hash.0..self.block_hash.1];
-        hex_to_b256(&bytes[2..])
+    fn field(&self, idx: usize) -> Option<&'a [u8]> {
+        if self.fields_present & (1 << idx) == 0 {
+            return None;
+        }
+        let (start, end) = self.fields[idx];
+        Some(&self.data[start..end])
     }
 
     #[inline]
-    pub fn from(&self) -> Address {
-        let bytes = &self.data[self.from.0..self.from.1];
-        unsafe_hex_to_address(&bytes[2..])
+    fn u64_field(&self, idx: usize) -> Option<U64> {
+        self.field(idx).map(quantity_to_u64)
     }
 
     #[inline]
-    pub fn to_transaction(&self) -> TransactionTx {
-        TransactionTx {
-            block_hash: Some(self.block_hash()),
-            block_number: Some(self.block_number()),
-            hash: self.hash(),
-            input: hex::encode_prefixed(&self.data[self.input.0..self.input.1]),
-            r: self.r(),
-            s: self.s(),
-            v: self.v(),
-            gas: self.gas(),
-            from: self.from(),
-            transaction_index: Some(self.transaction_index()),
-            to: Some(self.to()),
-            value: self.value(),
-            nonce: self.nonce(),
-            gas_price: self.gas_price(),
-        }
+    fn u256_field(&self, idx: usize) -> Option<U256> {
+        self.field(idx).map(quantity_to_u256)
     }
 
     #[inline]
-    fn block_number(&self) -> U64 {
-        let bytes = &self.data[self.block_number.0..self.block_number.1];
-        hex_to_u64(&bytes[2..])
+    fn b256_field(&self, idx: usize) -> Option<B256> {
+        self.field(idx).and_then(to_b256)
     }
 
     #[inline]
-    fn hash(&self) -> B256 {
-        let bytes = &self.data[self.hash.0..self.hash.1];
-        hex_to_b256(&bytes[2..])
+    fn address_field(&self, idx: usize) -> Option<Address> {
+        self.field(idx).and_then(to_address)
     }
 
     #[inline]
-    fn r(&self) -> B256 {
-        let bytes = &self.data[self.r.0..self.r.1];
-        hex_to_b256(&bytes[2..])
+    pub fn hash(&self) -> B256 {
+        self.b256_field(HASH).unwrap_or_default()
     }
 
     #[inline]
-    fn s(&self) -> B256 {
-        let bytes = &self.data[self.s.0..self.s.1];
-        hex_to_b256(&bytes[2..])
+    pub fn block_hash(&self) -> Option<B256> {
+        self.b256_field(BLOCK_HASH)
     }
 
     #[inline]
-    fn v(&self) -> U64 {
-        let bytes = &self.data[self.v.0..self.v.1];
-        hex_to_u64(&bytes[2..])
+    pub fn from(&self) -> Address {
+        self.address_field(FROM).unwrap_or_default()
     }
 
     #[inline]
-    fn gas(&self) -> U256 {
-        let bytes = &self.data[self.gas.0..self.gas.1];
-        hex_to_u256(&bytes[2..])
+    pub fn to(&self) -> Option<Address> {
+        self.address_field(TO)
     }
 
+    /// EIP-2718 type, transactions from before Berlin carry none. `None` when
+    /// the type does not fit in a byte
     #[inline]
-    fn to(&self) -> Address {
-        let bytes = &self.data[self.to.0..self.to.1];
-        unsafe_hex_to_address(&bytes[2..])
+    pub fn tx_type(&self) -> Option<u8> {
+        self.u64_field(TYPE).map_or(Some(0), |ty| ty.try_into().ok())
     }
 
+    /// `None` for transaction types this parser does not know
     #[inline]
-    fn transaction_index(&self) -> U64 {
-        let bytes = &self.data[self.tx_index.0..self.tx_index.1];
-        hex_to_u64(&bytes[2..])
+    pub fn to_transaction(&self) -> Option<TransactionTx> {
+        Some(TransactionTx {
+            hash: self.hash(),
+            nonce: self.u64_field(NONCE).unwrap_or_default(),
+            block_hash: self.block_hash(),
+            block_number: self.u64_field(BLOCK_NUMBER),
+            transaction_index: self.u64_field(TX_INDEX),
+            from: self.from(),
+            to: self.to(),
+            value: self.u256_field(VALUE).unwrap_or_default(),
+            gas_price: self.u256_field(GAS_PRICE),
+            gas: self.u256_field(GAS).unwrap_or_default(),
+            input: String::from_utf8_lossy(self.field(INPUT).unwrap_or(b"0x")).into_owned(),
+            v: self.u64_field(V),
+            r: self.u256_field(R).unwrap_or_default(),
+            s: self.u256_field(S).unwrap_or_default(),
+            transaction: self.typed()?,
+        })
     }
 
+    /// Per type fields, `None` for types this parser does not know rather than
+    /// reading them as something they are not
     #[inline]
-    fn value(&self) -> U256 {
-        let bytes = &self.data[self.value.0..self.value.1];
-        hex_to_u256(&bytes[2..])
+    pub fn typed(&self) -> Option<TypedTransaction> {
+        let ty = self.tx_type()?;
+        if ty == 0 {
+            return Some(TypedTransaction::Legacy {
+                chain_id: self.u64_field(CHAIN_ID),
+            });
+        }
+
+        let chain_id = self.u64_field(CHAIN_ID).unwrap_or_default();
+        // Older nodes only send `v`, which equals the parity for typed transactions
+        let y_parity = self.u64_field(Y_PARITY).or_else(|| self.u64_field(V)).unwrap_or_default();
+        let max_fee_per_gas = self.u256_field(MAX_FEE_PER_GAS).unwrap_or_default();
+        let max_priority_fee_per_gas =
+            self.u256_field(MAX_PRIORITY_FEE_PER_GAS).unwrap_or_default();
+
+        let typed = match ty {
+            1 => TypedTransaction::Eip2930 {
+                chain_id,
+                access_list: self.access_list(),
+                y_parity,
+            },
+            2 => TypedTransaction::Eip1559 {
+                chain_id,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                access_list: self.access_list(),
+                y_parity,
+            },
+            3 => TypedTransaction::Eip4844 {
+                chain_id,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                max_fee_per_blob_gas: self.u256_field(MAX_FEE_PER_BLOB_GAS).unwrap_or_default(),
+                blob_versioned_hashes: self.blob_versioned_hashes(),
+                access_list: self.access_list(),
+                y_parity,
+            },
+            4 => TypedTransaction::Eip7702 {
+                chain_id,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                access_list: self.access_list(),
+                authorization_list: self.authorization_list(),
+                y_parity,
+            },
+            _ => return None,
+        };
+        Some(typed)
     }
 
-    #[inline]
-    fn nonce(&self) -> U64 {
-        let bytes = &self.data[self.nonce.0..self.nonce.1];
-        hex_to_u64(&bytes[2..])
+    pub fn access_list(&self) -> Vec<AccessListItem> {
+        let Some(list) = self.field(ACCESS_LIST) else {
+            return Vec::new();
+        };
+
+        split_array(list)
+            .into_iter()
+            .filter_map(|(start, end)| {
+                let item = &list[start..end];
+                let (fields, present) = index_members(item, &ACCESS_LIST_FIELDS);
+                if present & 1 == 0 {
+                    return None;
+                }
+
+                let (s, e) = fields[0];
+                let storage_keys = if present & 2 != 0 {
+                    let (ks, ke) = fields[1];
+                    let keys = &item[ks..ke];
+                    split_array(keys)
+                        .into_iter()
+                        .filter_map(|(s, e)| to_b256(unquote(&keys[s..e])))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+                Some(AccessListItem {
+                    address: to_address(&item[s..e])?,
+                    storage_keys,
+                })
+            })
+            .collect()
     }
 
-    #[inline]
-    fn gas_price(&self) -> U64 {
-        let bytes = &self.data[self.gas_price.0..self.gas_price.1];
-        hex_to_u64(&bytes[2..])
+    pub fn blob_versioned_hashes(&self) -> Vec<B256> {
+        let Some(hashes) = self.field(BLOB_VERSIONED_HASHES) else {
+            return Vec::new();
+        };
+
+        split_array(hashes)
+            .into_iter()
+            .filter_map(|(start, end)| to_b256(unquote(&hashes[start..end])))
+            .collect()
+    }
+
+    pub fn authorization_list(&self) -> Vec<SignedAuthorization> {
+        let Some(list) = self.field(AUTHORIZATION_LIST) else {
+            return Vec::new();
+        };
+
+        split_array(list)
+            .into_iter()
+            .filter_map(|(start, end)| {
+                let item = &list[start..end];
+                let (fields, present) = index_members(item, &AUTHORIZATION_FIELDS);
+                if present != 0b11_1111 {
+                    return None;
+                }
+                let field = |idx: usize| &item[fields[idx].0..fields[idx].1];
+
+                Some(SignedAuthorization {
+                    chain_id: quantity_to_u256(field(0)),
+                    address: to_address(field(1))?,
+                    nonce: quantity_to_u64(field(2)),
+                    y_parity: quantity_to_u64(field(3)),
+                    r: quantity_to_u256(field(4)),
+                    s: quantity_to_u256(field(5)),
+                })
+            })
+            .collect()
+    }
+}
+
+/// `0x` prefixed 32 byte hex
+#[inline]
+fn to_b256(bytes: &[u8]) -> Option<B256> {
+    (bytes.len() == 66).then(|| hex_to_b256(bytes))
+}
+
+/// `0x` prefixed 20 byte hex
+#[inline]
+fn to_address(bytes: &[u8]) -> Option<Address> {
+    (bytes.len() == 42).then(|| unsafe_hex_to_address(bytes))
+}
+
+#[inline]
+fn unquote(bytes: &[u8]) -> &[u8] {
+    match bytes {
+        [b'"', inner @ .., b'"'] => inner,
+        _ => bytes,
     }
 }
 
@@ -188,5 +359,5 @@ impl<'a> RawJsonResponse<'a> {
 pub fn parse_transaction(input: &[u8]) -> Option<TransactionTx> {
     RawJsonResponse::parse_tx(input)
         .and_then(|r| r.transaction())
-        .map(|tx| tx.to_transaction())
+        .and_then(|tx| tx.to_transaction())
 }
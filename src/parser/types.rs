@@ -1,17 +1,10 @@
 // source: from the yellow paper
 
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
-use alloy::primitives::{Address, B256, U256, U64};
+use alloy::primitives::{Address, Bytes, B256, B64, U256, U64};
 use serde::{Deserialize, Serialize};
 
-// THIS IS A SCOPE TO TRACK THE HASH OF A BLOCK USING THE BLOCK NUMBER
-// SINCE THE BLOCK HEADER DOES NOT CONTAIN THE HASH OF THE BLOCK, AND MAJORLY WE
-// ARE USING THE BLOCK HEADER
-/// THIS IS A FORM OF BUFFER THAT STORES THE HASH  USING THE THE LAST DIGIT OF
-/// THE BLOCK NUMBER AS INDEX SO POSSIBLY IT CAN ONLY STORE 1-9 BLOCKS
-pub static mut NUM_HASH_DATA: [B256; 10] = [B256::ZERO; 10];
-
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct BlockHeader {
     #[serde(rename = "parentHash")]
@@ -74,6 +67,11 @@ pub struct BlockHeader {
     #[serde(rename = "blobsHash")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub blobs_hash: Option<B256>,
+
+    // Prague additions
+    #[serde(rename = "requestsHash")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_hash: Option<B256>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -114,8 +112,68 @@ pub struct Block {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "baseFeePerGas")]
     pub base_fee_per_gas: Option<U256>,
-    pub transactions: Vec<B256>,
+    #[serde(rename = "withdrawalsRoot")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<B256>,
+    #[serde(rename = "blobGasUsed")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U64>,
+    #[serde(rename = "excessBlobGas")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<U64>,
+    #[serde(rename = "parentBeaconBlockRoot")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<B256>,
+    #[serde(rename = "requestsHash")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_hash: Option<B256>,
+    pub transactions: BlockTransactions,
     pub uncles: Vec<B256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+/// Transactions of a block, hashes unless the block was requested with full
+/// transaction objects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BlockTransactions {
+    Hashes(Vec<B256>),
+    Full(Vec<TransactionTx>),
+}
+
+impl Default for BlockTransactions {
+    fn default() -> Self {
+        BlockTransactions::Hashes(Vec::new())
+    }
+}
+
+impl BlockTransactions {
+    pub fn len(&self) -> usize {
+        match self {
+            BlockTransactions::Hashes(hashes) => hashes.len(),
+            BlockTransactions::Full(txs) => txs.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Transaction hashes in block order, whichever form the block holds
+    pub fn hashes(&self) -> Vec<B256> {
+        match self {
+            BlockTransactions::Hashes(hashes) => hashes.clone(),
+            BlockTransactions::Full(txs) => txs.iter().map(|tx| tx.hash).collect(),
+        }
+    }
+
+    pub fn as_full(&self) -> Option<&[TransactionTx]> {
+        match self {
+            BlockTransactions::Full(txs) => Some(txs),
+            BlockTransactions::Hashes(_) => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -125,56 +183,185 @@ pub struct RawJsonResponse<'a> {
     pub result_end: usize,
 }
 
+/// Earlier name of `TransactionTx`
+pub type Transaction = TransactionTx;
+
+/// A transaction as returned by `eth_getTransactionByHash` and in full blocks.
+/// Fields every type shares live here, the rest in `transaction`
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Transaction {
+pub struct TransactionTx {
     pub hash: B256,
     pub nonce: U64,
-    #[serde(rename = "type")]
-    pub type_tx: Option<U64>,
     #[serde(rename = "blockHash")]
     pub block_hash: Option<B256>,
-    #[serde(rename = "number")]
+    #[serde(rename = "blockNumber")]
     pub block_number: Option<U64>,
     #[serde(rename = "transactionIndex")]
     pub transaction_index: Option<U64>,
     pub from: Address,
+    /// `None` for contract creations
     pub to: Option<Address>,
     pub value: U256,
+    /// For dynamic fee transactions nodes report the effective price once mined
     #[serde(rename = "gasPrice")]
-    pub gas_price: Option<U64>,
-    #[serde(rename = "maxFeePerGas")]
-    pub max_fee_per_gas: Option<U64>,
-    #[serde(rename = "maxPriorityFeePerGas")]
-    pub max_priority_fee_per_gas: Option<U64>,
+    pub gas_price: Option<U256>,
     pub gas: U256,
     pub input: String,
-    pub v: U64,
-    pub r: B256,
-    pub s: B256,
-    pub access_list: Option<Vec<(Address, Vec<B256>)>>,
-    pub init: Option<String>,
+    /// Typed transactions may omit `v` and only carry `yParity`
+    pub v: Option<U64>,
+    pub r: U256,
+    pub s: U256,
+    #[serde(flatten)]
+    pub transaction: TypedTransaction,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TransactionTx {
-    pub hash: B256,
+impl TransactionTx {
+    /// EIP-2718 type byte
+    pub fn tx_type(&self) -> u8 {
+        self.transaction.tx_type()
+    }
+}
+
+/// Fields specific to each EIP-2718 transaction type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(remote = "Self", tag = "type", rename_all_fields = "camelCase")]
+pub enum TypedTransaction {
+    #[serde(rename = "0x0")]
+    Legacy {
+        /// Set for EIP-155 replay protected transactions
+        #[serde(skip_serializing_if = "Option::is_none")]
+        chain_id: Option<U64>,
+    },
+    #[serde(rename = "0x1")]
+    Eip2930 {
+        chain_id: U64,
+        access_list: Vec<AccessListItem>,
+        y_parity: U64,
+    },
+    #[serde(rename = "0x2")]
+    Eip1559 {
+        chain_id: U64,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        access_list: Vec<AccessListItem>,
+        y_parity: U64,
+    },
+    #[serde(rename = "0x3")]
+    Eip4844 {
+        chain_id: U64,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        max_fee_per_blob_gas: U256,
+        blob_versioned_hashes: Vec<B256>,
+        access_list: Vec<AccessListItem>,
+        y_parity: U64,
+    },
+    #[serde(rename = "0x4")]
+    Eip7702 {
+        chain_id: U64,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        access_list: Vec<AccessListItem>,
+        authorization_list: Vec<SignedAuthorization>,
+        y_parity: U64,
+    },
+}
+
+impl Serialize for TypedTransaction {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TypedTransaction::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TypedTransaction {
+    /// Pre-Berlin nodes leave out `type`, those transactions are legacy
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields = serde_json::Map::deserialize(deserializer)?;
+        fields
+            .entry("type")
+            .or_insert_with(|| serde_json::Value::String("0x0".to_string()));
+        TypedTransaction::deserialize(serde_json::Value::Object(fields))
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl TypedTransaction {
+    pub fn tx_type(&self) -> u8 {
+        match self {
+            TypedTransaction::Legacy { .. } => 0,
+            TypedTransaction::Eip2930 { .. } => 1,
+            TypedTransaction::Eip1559 { .. } => 2,
+            TypedTransaction::Eip4844 { .. } => 3,
+            TypedTransaction::Eip7702 { .. } => 4,
+        }
+    }
+
+    pub fn chain_id(&self) -> Option<U64> {
+        match self {
+            TypedTransaction::Legacy { chain_id } => *chain_id,
+            TypedTransaction::Eip2930 { chain_id, .. }
+            | TypedTransaction::Eip1559 { chain_id, .. }
+            | TypedTransaction::Eip4844 { chain_id, .. }
+            | TypedTransaction::Eip7702 { chain_id, .. } => Some(*chain_id),
+        }
+    }
+
+    /// Empty for legacy transactions
+    pub fn access_list(&self) -> &[AccessListItem] {
+        match self {
+            TypedTransaction::Legacy { .. } => &[],
+            TypedTransaction::Eip2930 { access_list, .. }
+            | TypedTransaction::Eip1559 { access_list, .. }
+            | TypedTransaction::Eip4844 { access_list, .. }
+            | TypedTransaction::Eip7702 { access_list, .. } => access_list,
+        }
+    }
+
+    /// Fee cap of dynamic fee transactions
+    pub fn max_fee_per_gas(&self) -> Option<U256> {
+        match self {
+            TypedTransaction::Eip1559 {
+                max_fee_per_gas, ..
+            }
+            | TypedTransaction::Eip4844 {
+                max_fee_per_gas, ..
+            }
+            | TypedTransaction::Eip7702 {
+                max_fee_per_gas, ..
+            } => Some(*max_fee_per_gas),
+            _ => None,
+        }
+    }
+
+    pub fn max_priority_fee_per_gas(&self) -> Option<U256> {
+        match self {
+            TypedTransaction::Eip1559 {
+                max_priority_fee_per_gas,
+                ..
+            }
+            | TypedTransaction::Eip4844 {
+                max_priority_fee_per_gas,
+                ..
+            }
+            | TypedTransaction::Eip7702 {
+                max_priority_fee_per_gas,
+                ..
+            } => Some(*max_priority_fee_per_gas),
+            _ => None,
+        }
+    }
+}
+
+/// EIP-7702 authorization tuple
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedAuthorization {
+    pub chain_id: U256,
+    pub address: Address,
     pub nonce: U64,
-    #[serde(rename = "blockHash")]
-    pub block_hash: Option<B256>,
-    #[serde(rename = "number")]
-    pub block_number: Option<U64>,
-    #[serde(rename = "transactionIndex")]
-    pub transaction_index: Option<U64>,
-    pub from: Address,
-    pub to: Option<Address>,
-    pub value: U256,
-    #[serde(rename = "gasPrice")]
-    pub gas_price: U64,
-    pub gas: U256,
-    pub input: String,
-    pub v: U64,
-    pub r: B256,
-    pub s: B256,
+    pub y_parity: U64,
+    pub r: U256,
+    pub s: U256,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -195,26 +382,185 @@ pub struct Log {
     pub removed: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    pub transaction_hash: B256,
+    pub transaction_index: U64,
+    pub block_hash: B256,
+    pub block_number: U64,
+    pub from: Address,
+    /// `None` for contract creations
+    pub to: Option<Address>,
+    /// 1 for success, 0 for failure. Absent before Byzantium, where `root` is set
+    pub status: Option<U64>,
+    /// Post transaction state root, pre-Byzantium only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<B256>,
+    pub cumulative_gas_used: U256,
+    pub gas_used: U256,
+    pub effective_gas_price: U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_gas_price: Option<U256>,
+    pub contract_address: Option<Address>,
+    pub logs: Vec<Log>,
+    pub logs_bloom: String,
+    #[serde(rename = "type")]
+    pub transaction_type: U64,
+}
+
+impl Receipt {
+    /// `true` when the transaction succeeded. Pre-Byzantium receipts carry no
+    /// status and count as successful
+    pub fn is_success(&self) -> bool {
+        self.status.is_none_or(|status| status == U64::from(1))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TransactionRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub from: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub to: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_price: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<U256>,
+    /// Hex encoded calldata, kept for nodes that only read `data`
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<String>,
+    /// Calldata; takes precedence over `data` on current nodes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub nonce: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<Vec<AccessListItem>>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<U64>,
+}
+
+/// `eth_feeHistory` result. Blob fields stay empty before Cancun and
+/// `reward` when no percentiles were asked for
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    pub oldest_block: U64,
+    /// One entry per block plus the next one
+    pub base_fee_per_gas: Vec<U256>,
+    pub base_fee_per_blob_gas: Vec<U256>,
+    pub gas_used_ratio: Vec<f64>,
+    pub blob_gas_used_ratio: Vec<f64>,
+    /// Reward at each requested percentile, one row per block
+    pub reward: Vec<Vec<U256>>,
+}
+
+/// `eth_getProof` result (EIP-1186): the account fields and Merkle proofs of
+/// the account and the requested storage slots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+    pub address: Address,
+    pub balance: U256,
+    pub nonce: U64,
+    pub code_hash: B256,
+    pub storage_hash: B256,
+    /// Trie nodes from the state root down to the account
+    pub account_proof: Vec<Bytes>,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageProof {
+    pub key: B256,
+    pub value: U256,
+    /// Trie nodes from the storage root down to the slot
+    pub proof: Vec<Bytes>,
+}
+
+/// EIP-2930 access list entry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<B256>,
+}
+
+/// Per account overrides applied before an `eth_call`, geth's state override set.
+/// `state` replaces the whole storage of the account, `state_diff` only the
+/// given slots, so at most one of the two should be set
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<HashMap<B256, B256>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<HashMap<B256, B256>>,
+}
+
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Overrides of the block context an `eth_call` executes in
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_recipient: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_randao: Option<B256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_base_fee: Option<U256>,
+}
+
+/// State and block overrides for `RpcClient::call`
+#[derive(Debug, Clone, Default)]
+pub struct CallOverrides {
+    pub state: Option<StateOverride>,
+    pub block: Option<BlockOverrides>,
 }
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FilterParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub from_block: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub to_block: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub topics: Option<Vec<Option<B256>>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Withdrawal {
     pub address: Address,
     pub amount: U256,
@@ -259,7 +605,11 @@ pub struct Beacon {
     pub body_root: B256,
 }
 
-#[derive(Debug, Default, Clone)]
+/// Execution payload header. The Engine API sends payloads with their
+/// transactions and withdrawals instead of the two roots, see
+/// [`ExecutionPayload`]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Execution {
     pub parent_hash: B256,
     pub fee_recipient: Address,
@@ -273,12 +623,111 @@ pub struct Execution {
     pub timestamp: U64,
     pub extra_data: String,
     pub base_fee_per_gas: U256,
+    pub blob_gas_used: U64,
     pub excess_blob_gas: U64,
     pub block_hash: B256,
+    #[serde(skip)]
     pub transactions_root: B256,
+    #[serde(skip)]
     pub withdrawals_root: B256,
 }
 
+// ********** ENGINE API ********** //
+
+/// `ExecutionPayloadV3`, the header fields plus the block body.
+/// `ExecutionPayload::header` fills in the roots
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPayload {
+    #[serde(flatten)]
+    pub execution: Execution,
+    /// EIP-2718 encoded
+    pub transactions: Vec<Bytes>,
+    pub withdrawals: Vec<Withdrawal>,
+}
+
+/// Identifies a payload being built, from `engine_forkchoiceUpdated`
+pub type PayloadId = B64;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceState {
+    pub head_block_hash: B256,
+    pub safe_block_hash: B256,
+    pub finalized_block_hash: B256,
+}
+
+/// `PayloadAttributesV3`, asks the node to start building on the new head
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadAttributes {
+    pub timestamp: U64,
+    pub prev_randao: B256,
+    pub suggested_fee_recipient: Address,
+    pub withdrawals: Vec<Withdrawal>,
+    pub parent_beacon_block_root: B256,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayloadValidity {
+    Valid,
+    Invalid,
+    /// The node is still syncing and could not validate the payload
+    Syncing,
+    /// Valid so far, but not on the canonical chain
+    Accepted,
+    InvalidBlockHash,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadStatus {
+    pub status: PayloadValidity,
+    /// Most recent valid block in the branch, `None` when unknown
+    pub latest_valid_hash: Option<B256>,
+    pub validation_error: Option<String>,
+}
+
+/// `engine_forkchoiceUpdated` result, `payload_id` is set when building
+/// started
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceUpdated {
+    pub payload_status: PayloadStatus,
+    pub payload_id: Option<PayloadId>,
+}
+
+/// KZG commitments, proofs and blobs of the blob transactions in a payload,
+/// in the same order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobsBundle {
+    pub commitments: Vec<Bytes>,
+    pub proofs: Vec<Bytes>,
+    pub blobs: Vec<Bytes>,
+}
+
+/// `engine_getPayloadV3` and `V4` result. `execution_requests` only comes
+/// with V4
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPayloadEnvelope {
+    pub execution_payload: ExecutionPayload,
+    /// Fees the payload pays to the fee recipient, in wei
+    pub block_value: U256,
+    pub blobs_bundle: BlobsBundle,
+    pub should_override_builder: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_requests: Option<Vec<Bytes>>,
+}
+
+/// A blob from the node's pool, from `engine_getBlobsV1`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobAndProof {
+    pub blob: Bytes,
+    pub proof: Bytes,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct SyncCommittee {
     pub pub_keys: Vec<B256>,
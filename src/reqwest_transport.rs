@@ -1,39 +1,55 @@
+use std::{sync::Arc, time::Duration};
+
 use async_trait::async_trait;
 use bytes::Bytes;
 use reqwest::{Client, ClientBuilder};
 
-use crate::{hyper_rpc::Transport, RpcError};
+use crate::{
+    transport::{
+        auth::{request_headers, AuthProvider},
+        retry::{HttpResponse, RetryPolicy, DEFAULT_REQUEST_TIMEOUT},
+        url::SharedUrl,
+        RpcUrl, Transport,
+    },
+    RpcError,
+};
 
 #[derive(Debug, Clone)]
 pub struct ReqwestTransport {
     client: Client,
-    url: String,
+    url: SharedUrl,
+    auth: Option<Arc<dyn AuthProvider>>,
+    retry: RetryPolicy,
+    timeout: Duration,
 }
 
 impl ReqwestTransport {
-    pub fn new(url: &str) -> Self {
-        let client = ClientBuilder::new()
-            .tcp_nodelay(true)
-            .build()
-            .expect("Failed to create reqwest client");
+    pub fn new(url: RpcUrl) -> Self {
+        let client = Self::client(ClientBuilder::new());
 
         Self {
             client,
-            url: url.to_string(),
+            url: SharedUrl::new(url),
+            auth: None,
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
 
-    pub fn new_minimal(url: &str) -> Self {
+    pub fn new_minimal(url: RpcUrl) -> Self {
         let client = Client::new(); 
 
         Self {
             client,
-            url: url.to_string(),
+            url: SharedUrl::new(url),
+            auth: None,
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
 
     /// Create transport optimized 
-    pub fn new_optimized(url: &str) -> Self {
+    pub fn new_optimized(url: RpcUrl) -> Self {
         let client = ClientBuilder::new()
             .tcp_nodelay(true) 
             .pool_idle_timeout(std::time::Duration::from_secs(90)) 
@@ -45,12 +61,15 @@ impl ReqwestTransport {
 
         Self {
             client,
-            url: url.to_string(),
+            url: SharedUrl::new(url),
+            auth: None,
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
 
     /// Create ultra-fast transport
-    pub fn new_ultra_fast(url: &str) -> Self {
+    pub fn new_ultra_fast(url: RpcUrl) -> Self {
         let client = ClientBuilder::new()
             .tcp_nodelay(true) 
             .pool_idle_timeout(std::time::Duration::from_secs(300))
@@ -63,76 +82,88 @@ impl ReqwestTransport {
 
         Self {
             client,
-            url: url.to_string(),
+            url: SharedUrl::new(url),
+            auth: None,
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
-}
 
-#[async_trait]
-impl Transport for ReqwestTransport {
-    async fn hyper_execute(&self, request: String) -> Result<String, RpcError> {
-        let response = self
-            .client
-            .post(&self.url)
-            .header("Content-Type", "application/json")
-            .body(request)
-            .send()
-            .await
-            .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
+    fn client(builder: ClientBuilder) -> Client {
+        builder.tcp_nodelay(true).build().expect("Failed to create reqwest client")
+    }
 
-        if !response.status().is_success() {
-            return Err(RpcError::Transport(format!("HTTP error {}", response.status())));
-        }
+    /// Replaces the client with one keeping at most `max_idle` idle
+    /// connections per host, otherwise configured like `new`
+    pub fn with_pool_max_idle(mut self, max_idle: usize) -> Self {
+        self.client = Self::client(ClientBuilder::new().pool_max_idle_per_host(max_idle));
+        self
+    }
 
-        let text = response
-            .text()
-            .await
-            .map_err(|e| RpcError::Transport(format!("Failed to read response: {}", e)))?;
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Upper bound for each attempt, retries get a fresh timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends the headers `auth` hands out with every request, on top of
+    /// any credentials in the url
+    pub fn with_auth(self, auth: impl AuthProvider + 'static) -> Self {
+        self.with_auth_provider(Some(Arc::new(auth)))
+    }
 
-        Ok(text)
+    pub(crate) fn with_auth_provider(mut self, auth: Option<Arc<dyn AuthProvider>>) -> Self {
+        self.auth = auth;
+        self
     }
 
-    async fn hyper_execute_raw(&self, request: &'static [u8]) -> Result<Vec<u8>, RpcError> {
+    async fn post(&self, request: Bytes) -> Result<HttpResponse, RpcError> {
+        let url = self.url.get();
         let response = self
             .client
-            .post(&self.url)
+            .post(url.as_str())
             .header("Content-Type", "application/json")
-            .body(Bytes::from_static(request))
+            .headers(request_headers(&url, self.auth.as_ref())?)
+            .body(request)
             .send()
             .await
-            .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Err(RpcError::Transport(format!("HTTP error {}", response.status())));
-        }
+            .map_err(|e| RpcError::Connection(format!("Request failed: {}", e.without_url())))?;
 
+        let status = response.status();
+        let headers = response.headers().clone();
         let bytes = response
             .bytes()
             .await
-            .map_err(|e| RpcError::Transport(format!("Failed to read response: {}", e)))?;
+            .map_err(|e| {
+                RpcError::Connection(format!("Failed to read response: {}", e.without_url()))
+            })?;
 
-        Ok(bytes.to_vec())
+        Ok(HttpResponse::new(status, &headers, bytes))
     }
 
-    async fn hyper_execute_bytes(&self, request: Vec<u8>) -> Result<Vec<u8>, RpcError> {
-        let response = self
-            .client
-            .post(&self.url)
-            .header("Content-Type", "application/json")
-            .body(request)
-            .send()
-            .await
-            .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
+}
 
-        if !response.status().is_success() {
-            return Err(RpcError::Transport(format!("HTTP error {}", response.status())));
-        }
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.retry.execute(self.timeout, || self.post(request.clone())).await
+    }
 
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| RpcError::Transport(format!("Failed to read response: {}", e)))?;
+    /// Batches go out concurrently over the pooled connections
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    fn endpoint(&self) -> Option<RpcUrl> {
+        Some(RpcUrl::clone(&self.url.get()))
+    }
 
-        Ok(bytes.to_vec())
+    fn set_endpoint(&self, url: RpcUrl) -> Result<(), RpcError> {
+        self.url.set(url)
     }
 }
@@ -0,0 +1,256 @@
+use std::{collections::HashMap, fmt};
+
+use alloy::{
+    dyn_abi::{DynSolValue, ErrorExt},
+    hex,
+    json_abi::{Error as AbiError, JsonAbi},
+    primitives::{Bytes, Selector, U256},
+    sol_types::{Panic, PanicKind, Revert, SolError, SolInterface},
+};
+use serde_json::Value;
+
+use crate::RpcError;
+
+/// Why a call reverted, decoded from the revert payload
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevertReason {
+    /// `revert()` or a `require` without a message
+    Empty,
+    /// `Error(string)`, emitted by `require(cond, "reason")` and `revert("reason")`
+    Error(String),
+    /// `Panic(uint256)`; `kind` names the compiler inserted codes
+    Panic { code: U256, kind: Option<PanicKind> },
+    /// Custom error decoded through a registered ABI
+    Custom {
+        name: String,
+        args: Vec<DynSolValue>,
+    },
+    /// Custom error whose selector is not registered, or that failed to decode
+    Unknown { selector: Selector, data: Bytes },
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevertReason::Empty => f.write_str("execution reverted"),
+            RevertReason::Error(reason) => write!(f, "execution reverted: {}", reason),
+            RevertReason::Panic { code, kind } => match kind {
+                Some(kind) => write!(f, "panic: {} (0x{:02x})", kind.as_str(), *kind as u32),
+                None => write!(f, "panic: unknown code (0x{:x})", code),
+            },
+            RevertReason::Custom { name, args } => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{:?}", arg)?;
+                }
+                f.write_str(")")
+            },
+            RevertReason::Unknown { selector, .. } => write!(f, "custom error {}", selector),
+        }
+    }
+}
+
+/// Decodes revert payloads. `Error(string)` and `Panic(uint256)` are always
+/// understood; custom errors need their ABI registered first.
+#[derive(Debug, Clone, Default)]
+pub struct RevertDecoder {
+    errors: HashMap<Selector, AbiError>,
+}
+
+impl RevertDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers every error of a contract ABI
+    pub fn with_abi(mut self, abi: &JsonAbi) -> Self {
+        for error in abi.errors() {
+            self.errors.insert(error.selector(), error.clone());
+        }
+        self
+    }
+
+    pub fn with_error(mut self, error: AbiError) -> Self {
+        self.errors.insert(error.selector(), error);
+        self
+    }
+
+    /// Registers an error type generated by `sol!`. The signature carries no
+    /// parameter names, so arguments decode positionally
+    pub fn with_sol_error<E: SolError>(self) -> Self {
+        match AbiError::parse(E::SIGNATURE) {
+            Ok(error) => self.with_error(error),
+            Err(_) => self,
+        }
+    }
+
+    pub fn decode(&self, data: &[u8]) -> RevertReason {
+        if data.is_empty() {
+            return RevertReason::Empty;
+        }
+
+        if let Ok(revert) = Revert::abi_decode(data, true) {
+            return RevertReason::Error(revert.reason);
+        }
+        if let Ok(panic) = Panic::abi_decode(data, true) {
+            let kind = panic.kind();
+            return RevertReason::Panic {
+                code: panic.code,
+                kind,
+            };
+        }
+
+        if data.len() < 4 {
+            return RevertReason::Unknown {
+                selector: Selector::ZERO,
+                data: Bytes::copy_from_slice(data),
+            };
+        }
+
+        let selector = Selector::from_slice(&data[..4]);
+        match self.errors.get(&selector).map(|error| (error, error.decode_error(data))) {
+            Some((error, Ok(decoded))) => RevertReason::Custom {
+                name: error.name.clone(),
+                args: decoded.body,
+            },
+            _ => RevertReason::Unknown {
+                selector,
+                data: Bytes::copy_from_slice(data),
+            },
+        }
+    }
+
+    /// Decodes the revert carried by a JSON-RPC error, if there is one
+    pub fn decode_error(&self, error: &RpcError) -> Option<RevertReason> {
+        error.revert_data().map(|data| self.decode(&data))
+    }
+}
+
+impl RpcError {
+    /// Raw revert payload from the `data` of a JSON-RPC error. Nodes send it
+    /// either as a hex string or nested as `{"data": "0x.."}`
+    pub fn revert_data(&self) -> Option<Bytes> {
+        let RpcError::JsonRpc {
+            data: Some(data), ..
+        } = self
+        else {
+            return None;
+        };
+
+        let hex_data = match data {
+            Value::String(s) => s.as_str(),
+            Value::Object(obj) => obj.get("data")?.as_str()?,
+            _ => return None,
+        };
+        hex::decode(hex_data).ok().map(Bytes::from)
+    }
+
+    /// Revert reason using only the built-in `Error(string)` and `Panic`
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        RevertDecoder::default().decode_error(self)
+    }
+
+    /// Decodes the revert into an error enum generated by `sol!`
+    pub fn decode_revert<E: SolInterface>(&self) -> Option<E> {
+        E::abi_decode(&self.revert_data()?, true).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::Address, sol};
+    use serde_json::json;
+
+    use super::*;
+
+    sol! {
+        #[derive(Debug, PartialEq)]
+        interface Vault {
+            error InsufficientBalance(address account, uint256 needed);
+            error Paused();
+        }
+    }
+
+    fn reverted(data: Value) -> RpcError {
+        RpcError::JsonRpc {
+            code: 3,
+            message: "execution reverted".into(),
+            data: Some(data),
+        }
+    }
+
+    #[test]
+    fn test_decodes_error_string() {
+        let data = Revert::from("not owner").abi_encode();
+        let error = reverted(json!(hex::encode_prefixed(&data)));
+
+        assert_eq!(error.revert_reason(), Some(RevertReason::Error("not owner".into())));
+    }
+
+    #[test]
+    fn test_decodes_named_panic() {
+        let data = Panic::from(PanicKind::UnderOverflow).abi_encode();
+        let error = reverted(json!({ "data": hex::encode_prefixed(&data) }));
+
+        let reason = error.revert_reason().unwrap();
+        assert_eq!(
+            reason,
+            RevertReason::Panic {
+                code: U256::from(0x11),
+                kind: Some(PanicKind::UnderOverflow)
+            }
+        );
+        assert_eq!(reason.to_string(), "panic: arithmetic underflow or overflow (0x11)");
+    }
+
+    #[test]
+    fn test_decodes_registered_custom_error() {
+        let account = Address::repeat_byte(0xaa);
+        let data = Vault::InsufficientBalance {
+            account,
+            needed: U256::from(5),
+        }
+        .abi_encode();
+        let error = reverted(json!(hex::encode_prefixed(&data)));
+
+        // Unregistered selectors are kept raw
+        let Some(RevertReason::Unknown { selector, .. }) = error.revert_reason() else {
+            panic!("expected an unknown custom error");
+        };
+        assert_eq!(selector, Vault::InsufficientBalance::SELECTOR);
+
+        let abi =
+            JsonAbi::parse(["error InsufficientBalance(address account, uint256 needed)"]).unwrap();
+        let decoder = RevertDecoder::new().with_abi(&abi);
+        assert_eq!(
+            decoder.decode_error(&error),
+            Some(RevertReason::Custom {
+                name: "InsufficientBalance".into(),
+                args: vec![
+                    DynSolValue::Address(account),
+                    DynSolValue::Uint(U256::from(5), 256)
+                ],
+            })
+        );
+
+        let decoder = RevertDecoder::new().with_sol_error::<Vault::InsufficientBalance>();
+        assert!(matches!(decoder.decode(&data), RevertReason::Custom { .. }));
+
+        assert_eq!(
+            error.decode_revert::<Vault::VaultErrors>(),
+            Some(Vault::VaultErrors::InsufficientBalance(Vault::InsufficientBalance {
+                account,
+                needed: U256::from(5)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_empty_and_missing_revert_data() {
+        assert_eq!(reverted(json!("0x")).revert_reason(), Some(RevertReason::Empty));
+        assert_eq!(RpcError::Parse("x".into()).revert_reason(), None);
+    }
+}
@@ -19,7 +19,16 @@ use tower::{
 };
 use tracing::{debug, info, instrument};
 
-use crate::{hyper_rpc::Transport, RpcError};
+use crate::{
+    transport::{
+        auth::{request_headers, AuthProvider},
+        compression::Compression,
+        retry::{HttpResponse, RetryPolicy, DEFAULT_REQUEST_TIMEOUT},
+        url::SharedUrl,
+        IntoRpcUrl, RpcUrl, Transport,
+    },
+    RpcError,
+};
 
 type HttpClient = hyper_util::client::legacy::Client<
     HttpsConnector<hyper_util::client::legacy::connect::HttpConnector>,
@@ -53,7 +62,7 @@ where
     fn call(&mut self, req: Request<B>) -> Self::Future {
         let start = Instant::now();
         let method = req.method().clone();
-        let uri = req.uri().clone();
+        let uri = loggable(req.uri());
         
         let future = self.inner.call(req);
         
@@ -75,6 +84,14 @@ where
     }
 }
 
+/// Url for log lines, with credentials and key-like path segments masked
+fn loggable(uri: &http::Uri) -> String {
+    match uri.clone().into_rpc_url() {
+        Ok(url) => url.redacted(),
+        Err(_) => uri.host().unwrap_or_default().to_string(),
+    }
+}
+
 pub struct MetricsMiddlewareLayer;
 
 impl Default for MetricsMiddlewareLayer {
@@ -201,20 +218,37 @@ impl<S> Layer<S> for CircuitBreakerLayer {
 #[derive(Debug, Clone)]
 pub struct TowerTransport {
     client: Arc<ConcurrencyLimit<Timeout<HttpClient>>>,
-    url: &'static str,
+    url: SharedUrl,
+    auth: Option<Arc<dyn AuthProvider>>,
+    compression: Compression,
+    retry: RetryPolicy,
+    timeout: Duration,
 }
 
 impl TowerTransport {
-    pub fn new(url: &'static str) -> Self {
+    pub fn new(url: RpcUrl) -> Self {
         info!("Creating Tower-based HTTP client");
+        let client = Self::client(16);
+        info!("Tower HTTP client created");
 
+        Self {
+            client,
+            url: SharedUrl::new(url),
+            auth: None,
+            compression: Compression::default(),
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+
+    fn client(max_idle: usize) -> Arc<ConcurrencyLimit<Timeout<HttpClient>>> {
         let http_executor = TokioExecutor::new();
-        
+
         let mut http_connector = hyper_util::client::legacy::connect::HttpConnector::new();
         http_connector.set_nodelay(true);
         http_connector.set_keepalive(Some(Duration::from_secs(30)));
         http_connector.set_connect_timeout(Some(Duration::from_secs(10)));
-        
+
         let https_connector = HttpsConnectorBuilder::new()
             .with_provider_and_webpki_roots(rustls::crypto::aws_lc_rs::default_provider())
             .expect("Failed to load root certificates")
@@ -225,7 +259,7 @@ impl TowerTransport {
 
         let base_client = hyper_util::client::legacy::Client::builder(http_executor)
             .pool_idle_timeout(Duration::from_secs(30))
-            .pool_max_idle_per_host(16)
+            .pool_max_idle_per_host(max_idle)
             .build(https_connector);
 
         let tower_client = ServiceBuilder::new()
@@ -233,15 +267,17 @@ impl TowerTransport {
             .timeout(Duration::from_secs(30))
             .service(base_client);
 
-        info!("Tower HTTP client created");
+        Arc::new(tower_client)
+    }
 
-        Self {
-            client: Arc::new(tower_client),
-            url,
-        }
+    /// Replaces the client with one keeping at most `max_idle` idle
+    /// connections per host, otherwise configured like `new`
+    pub fn with_pool_max_idle(mut self, max_idle: usize) -> Self {
+        self.client = Self::client(max_idle);
+        self
     }
 
-    pub fn new_optimized(url: &'static str) -> Self {
+    pub fn new_optimized(url: RpcUrl) -> Self {
         debug!("Creating optimized Tower-based HTTP client");
 
         let http_executor = TokioExecutor::new();
@@ -274,46 +310,83 @@ impl TowerTransport {
 
         Self {
             client: Arc::new(tower_client),
-            url,
+            url: SharedUrl::new(url),
+            auth: None,
+            compression: Compression::default(),
+            retry: RetryPolicy::default(),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
 
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Upper bound for each attempt, retries get a fresh timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends the headers `auth` hands out with every request, on top of
+    /// any credentials in the url
+    pub fn with_auth(self, auth: impl AuthProvider + 'static) -> Self {
+        self.with_auth_provider(Some(Arc::new(auth)))
+    }
+
+    pub(crate) fn with_auth_provider(mut self, auth: Option<Arc<dyn AuthProvider>>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Which encodings responses may use and which requests get compressed,
+    /// by default any of gzip, deflate, br and zstd is accepted
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
     #[instrument(skip(self, request))]
     pub async fn execute_request(&self, request: &[u8]) -> Result<Vec<u8>, RpcError> {
-        let body = Full::new(Bytes::copy_from_slice(request));
-        
-        let http_request = Request::builder()
+        let request = Bytes::copy_from_slice(request);
+        let body_bytes = self.retry.execute(self.timeout, || self.post(request.clone())).await?;
+
+        Ok(body_bytes.to_vec())
+    }
+
+    async fn post(&self, mut request: Bytes) -> Result<HttpResponse, RpcError> {
+        let url = self.url.get();
+        let mut http_request = Request::builder()
             .method("POST")
-            .uri(self.url)
+            .uri(url.uri().clone())
             .header("Content-Type", "application/json")
-            .header("User-Agent", "palantiri-tower/1.0")
-            .body(body)
+            .header("User-Agent", "palantiri-tower/1.0");
+        if let Some(headers) = http_request.headers_mut() {
+            headers.extend(request_headers(&url, self.auth.as_ref())?);
+            request = self.compression.encode_request(request, headers)?;
+        }
+        let http_request = http_request
+            .body(Full::new(request))
             .map_err(|e| RpcError::Transport(format!("Failed to build request: {}", e)))?;
 
-        let mut client = self.client.as_ref().clone();
+        let mut client = self.compression.decompress(self.client.as_ref().clone());
         let response = client
             .ready()
             .await
             .map_err(|e| RpcError::Transport(format!("Service not ready: {}", e)))?
             .call(http_request)
             .await
-            .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Err(RpcError::Transport(format!(
-                "HTTP error: {}",
-                response.status()
-            )));
-        }
+            .map_err(|e| RpcError::Connection(format!("Request failed: {}", e)))?;
 
-        let body = response.into_body();
+        let (parts, body) = response.into_parts();
         let body_bytes = body
             .collect()
             .await
-            .map_err(|e| RpcError::Transport(format!("Failed to read response: {}", e)))?
+            .map_err(|e| RpcError::Connection(format!("Failed to read response: {}", e)))?
             .to_bytes();
 
-        Ok(body_bytes.to_vec())
+        Ok(HttpResponse::new(parts.status, &parts.headers, body_bytes))
     }
 
     pub async fn execute_batch(&self, requests: Vec<Vec<u8>>) -> Result<Vec<Result<Vec<u8>, RpcError>>, RpcError> {
@@ -321,52 +394,7 @@ impl TowerTransport {
             return Ok(Vec::new());
         }
 
-        if requests.len() == 1 {
-            let result = self.execute_request(&requests[0]).await;
-            return Ok(vec![result]);
-        }
-
-        let futures = requests.into_iter().map(|req| {
-            let client = self.client.clone();
-            let url = self.url;
-            
-            async move {
-                let body = Full::new(Bytes::from(req));
-                
-                let http_request = Request::builder()
-                    .method("POST")
-                    .uri(url)
-                    .header("Content-Type", "application/json")
-                    .header("User-Agent", "palantiri-tower/1.0")
-                    .body(body)
-                    .map_err(|e| RpcError::Transport(format!("Failed to build request: {}", e)))?;
-
-                let mut client_clone = client.as_ref().clone();
-                let response = client_clone
-                    .ready()
-                    .await
-                    .map_err(|e| RpcError::Transport(format!("Service not ready: {}", e)))?
-                    .call(http_request)
-                    .await
-                    .map_err(|e| RpcError::Transport(format!("Request failed: {}", e)))?;
-
-                if !response.status().is_success() {
-                    return Err(RpcError::Transport(format!(
-                        "HTTP error: {}",
-                        response.status()
-                    )));
-                }
-
-                let body = response.into_body();
-                let body_bytes = body
-                    .collect()
-                    .await
-                    .map_err(|e| RpcError::Transport(format!("Failed to read response: {}", e)))?
-                    .to_bytes();
-
-                Ok(body_bytes.to_vec())
-            }
-        });
+        let futures = requests.iter().map(|req| self.execute_request(req));
 
         let results = futures::future::join_all(futures).await;
         Ok(results)
@@ -375,17 +403,20 @@ impl TowerTransport {
 
 #[async_trait]
 impl Transport for TowerTransport {
-    async fn hyper_execute(&self, request: String) -> Result<String, RpcError> {
-        let response_bytes = self.execute_request(request.as_bytes()).await?;
-        String::from_utf8(response_bytes)
-            .map_err(|e| RpcError::Response(format!("Invalid UTF-8: {}", e)))
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.retry.execute(self.timeout, || self.post(request.clone())).await
     }
 
-    async fn hyper_execute_raw(&self, request: &'static [u8]) -> Result<Vec<u8>, RpcError> {
-        self.execute_request(request).await
+    /// Batches go out concurrently over the pooled connections
+    fn supports_batch(&self) -> bool {
+        true
     }
 
-    async fn hyper_execute_bytes(&self, request: Vec<u8>) -> Result<Vec<u8>, RpcError> {
-        self.execute_request(&request).await
+    fn endpoint(&self) -> Option<RpcUrl> {
+        Some(RpcUrl::clone(&self.url.get()))
     }
-}
\ No newline at end of file
+
+    fn set_endpoint(&self, url: RpcUrl) -> Result<(), RpcError> {
+        self.url.set(url)
+    }
+}
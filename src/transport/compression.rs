@@ -0,0 +1,125 @@
+//! Compressed response and request bodies for the HTTP transports
+
+use std::io::Write;
+
+use bytes::Bytes;
+use flate2::{write::GzEncoder, Compression as GzLevel};
+use http::{
+    header::{CONTENT_ENCODING, CONTENT_LENGTH},
+    HeaderMap, HeaderValue,
+};
+use tower_http::decompression::Decompression;
+
+use crate::RpcError;
+
+/// How the HTTP transports compress bodies. By default responses may come
+/// gzip, deflate, br or zstd encoded and are decompressed transparently,
+/// requests go out as is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    responses: bool,
+    /// Request bodies of at least this many bytes are gzipped
+    request_threshold: Option<usize>,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            responses: true,
+            request_threshold: None,
+        }
+    }
+}
+
+impl Compression {
+    /// Plain bodies both ways
+    pub const DISABLED: Self = Self {
+        responses: false,
+        request_threshold: None,
+    };
+
+    /// Whether to send `Accept-Encoding` and decompress what comes back
+    pub fn responses(mut self, enabled: bool) -> Self {
+        self.responses = enabled;
+        self
+    }
+
+    /// Gzip request bodies of at least `bytes`, e.g. big batches. Only for
+    /// endpoints that accept `Content-Encoding: gzip`, which most nodes do
+    /// not without a proxy in front
+    pub fn compress_requests_over(mut self, bytes: usize) -> Self {
+        self.request_threshold = Some(bytes);
+        self
+    }
+
+    /// Wraps `service` to advertise and undo the enabled response encodings
+    pub(crate) fn decompress<S>(&self, service: S) -> Decompression<S> {
+        Decompression::new(service)
+            .gzip(self.responses)
+            .deflate(self.responses)
+            .br(self.responses)
+            .zstd(self.responses)
+    }
+
+    /// Gzips `body` when it is over the request threshold, setting
+    /// `Content-Encoding` to match
+    pub(crate) fn encode_request(
+        &self,
+        body: Bytes,
+        headers: &mut HeaderMap,
+    ) -> Result<Bytes, RpcError> {
+        match self.request_threshold {
+            Some(threshold) if body.len() >= threshold => {
+                let mut encoder =
+                    GzEncoder::new(Vec::with_capacity(body.len() / 4), GzLevel::fast());
+                encoder.write_all(&body).map_err(|e| {
+                    RpcError::Transport(format!("Failed to compress request: {}", e))
+                })?;
+                let compressed = encoder.finish().map_err(|e| {
+                    RpcError::Transport(format!("Failed to compress request: {}", e))
+                })?;
+
+                headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+                headers.remove(CONTENT_LENGTH);
+                Ok(compressed.into())
+            },
+            _ => Ok(body),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    #[test]
+    fn test_only_large_requests_are_gzipped() {
+        let compression = Compression::default().compress_requests_over(64);
+        let mut headers = HeaderMap::new();
+
+        let small = Bytes::from_static(br#"{"jsonrpc":"2.0","method":"eth_blockNumber","id":1}"#);
+        assert_eq!(compression.encode_request(small.clone(), &mut headers).unwrap(), small);
+        assert!(headers.is_empty());
+
+        let batch =
+            Bytes::from(format!("[{}]", vec![&*String::from_utf8_lossy(&small); 50].join(",")));
+        let encoded = compression.encode_request(batch.clone(), &mut headers).unwrap();
+        assert_eq!(headers[CONTENT_ENCODING], "gzip");
+        assert!(encoded.len() < batch.len() / 10);
+
+        let mut decoded = Vec::new();
+        GzDecoder::new(&encoded[..]).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, batch);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            Compression::DISABLED.encode_request(batch.clone(), &mut headers).unwrap(),
+            batch
+        );
+        assert!(headers.is_empty());
+    }
+}
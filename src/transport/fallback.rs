@@ -0,0 +1,303 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use memchr::memmem;
+use tracing::{debug, warn};
+
+use crate::{
+    hyper_transport::HyperTransport,
+    parser::lib::split_array,
+    transport::{
+        auth::AuthProvider,
+        compression::Compression,
+        retry::{ErrorClass, HttpResponse, RetryPolicy, DEFAULT_REQUEST_TIMEOUT},
+        RpcUrl, Transport,
+    },
+    RpcError,
+};
+
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// JSON-RPC error messages that mean this node is behind or pruned rather
+/// than that the request itself is bad, so another node may answer it
+const LAGGING_NODE_ERRORS: [&[u8]; 4] = [
+    b"header not found",
+    b"unknown block",
+    b"block not found",
+    b"missing trie node",
+];
+
+#[derive(Debug)]
+struct Endpoint {
+    transport: HyperTransport,
+    cooldown_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_cooling_down(&self, now: Instant) -> bool {
+        matches!(*self.cooldown_until.lock().unwrap(), Some(until) if until > now)
+    }
+
+    fn fail(&self, cooldown: Duration) {
+        *self.cooldown_until.lock().unwrap() = Some(Instant::now() + cooldown);
+    }
+
+    fn recover(&self) {
+        *self.cooldown_until.lock().unwrap() = None;
+    }
+}
+
+enum Attempt {
+    Served(Bytes),
+    /// Worth trying the next endpoint; the body is kept for node errors so it
+    /// can still be returned when no endpoint does better
+    Failover(RpcError, Option<Bytes>),
+    Fatal(RpcError),
+}
+
+/// HTTP transport over several endpoints. Requests go to the endpoint that
+/// last answered and move down the list on connection errors, 5xx responses
+/// or lagging node errors. A failed endpoint is skipped until its cooldown
+/// expires, unless every endpoint is cooling down. The retry policy applies
+/// to whole rounds, once every endpoint failed.
+#[derive(Debug)]
+pub struct FallbackTransport {
+    endpoints: Vec<Endpoint>,
+    active: AtomicUsize,
+    cooldown: Duration,
+    timeout: Duration,
+    retry: RetryPolicy,
+}
+
+impl FallbackTransport {
+    pub fn new(urls: Vec<RpcUrl>) -> Self {
+        assert!(!urls.is_empty(), "FallbackTransport needs at least one url");
+
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint {
+                transport: HyperTransport::new(url),
+                cooldown_until: Mutex::new(None),
+            })
+            .collect();
+
+        Self {
+            endpoints,
+            active: AtomicUsize::new(0),
+            cooldown: DEFAULT_COOLDOWN,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry: RetryPolicy::none(),
+        }
+    }
+
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Upper bound for each endpoint attempt, a timeout moves on to the next
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retries of a round in which every endpoint failed, none by default.
+    /// Each endpoint attempt is also cut short by the policy's deadline
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Keep at most `max_idle` idle connections per endpoint
+    pub fn with_pool_max_idle(mut self, max_idle: usize) -> Self {
+        for endpoint in &mut self.endpoints {
+            endpoint.transport = endpoint.transport.clone().with_pool_max_idle(max_idle);
+        }
+        self
+    }
+
+    /// Sends the headers `auth` hands out to every endpoint
+    pub fn with_auth(self, auth: impl AuthProvider + 'static) -> Self {
+        self.with_auth_provider(Some(Arc::new(auth)))
+    }
+
+    pub(crate) fn with_auth_provider(mut self, auth: Option<Arc<dyn AuthProvider>>) -> Self {
+        for endpoint in &mut self.endpoints {
+            endpoint.transport = endpoint.transport.clone().with_auth_provider(auth.clone());
+        }
+        self
+    }
+
+    /// Compression settings for every endpoint
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        for endpoint in &mut self.endpoints {
+            endpoint.transport = endpoint.transport.clone().with_compression(compression);
+        }
+        self
+    }
+
+    /// Url of the endpoint requests are currently sent to first
+    pub fn active_url(&self) -> Arc<RpcUrl> {
+        self.endpoints[self.active.load(Ordering::Relaxed)].transport.url()
+    }
+
+    pub fn urls(&self) -> Vec<Arc<RpcUrl>> {
+        self.endpoints.iter().map(|e| e.transport.url()).collect()
+    }
+
+    /// Sends the request with failover and returns the response together with
+    /// the url of the endpoint that served it
+    pub async fn execute_with_endpoint(
+        &self,
+        request: Bytes,
+    ) -> Result<(Bytes, Arc<RpcUrl>), RpcError> {
+        let started = Instant::now();
+        let mut round = 0;
+
+        loop {
+            let error = match self.round(started, &request).await {
+                Ok(served) => {
+                    self.retry.succeeded();
+                    return Ok(served);
+                },
+                Err(error) => error,
+            };
+
+            let delay = ErrorClass::of(&error)
+                .and_then(|class| self.retry.retry_delay(started, round, class, None));
+            let Some(delay) = delay else {
+                return Err(error);
+            };
+            debug!("Every endpoint failed, retrying after {:?}: {}", delay, error);
+            tokio::time::sleep(delay).await;
+            round += 1;
+        }
+    }
+
+    /// Tries each endpoint once, starting with the active one
+    async fn round(
+        &self,
+        started: Instant,
+        request: &Bytes,
+    ) -> Result<(Bytes, Arc<RpcUrl>), RpcError> {
+        let now = Instant::now();
+        let start = self.active.load(Ordering::Relaxed);
+        let len = self.endpoints.len();
+
+        // Healthy endpoints first, then the ones still cooling down
+        let order = (0..len).map(|i| (start + i) % len);
+        let (ready, cooling): (Vec<usize>, Vec<usize>) =
+            order.partition(|&i| !self.endpoints[i].is_cooling_down(now));
+
+        let mut last_error = None;
+        let mut last_node_error = None;
+
+        for idx in ready.into_iter().chain(cooling) {
+            let endpoint = &self.endpoints[idx];
+            let url = endpoint.transport.url();
+            let timeout = self.retry.attempt_timeout(started, self.timeout);
+
+            match self.attempt(&endpoint.transport, request.clone(), timeout).await {
+                Attempt::Served(body) => {
+                    endpoint.recover();
+                    if self.active.swap(idx, Ordering::Relaxed) != idx {
+                        debug!("Failed over to {}", url);
+                    }
+                    return Ok((body, url));
+                },
+                Attempt::Failover(e, body) => {
+                    warn!("Endpoint {} failed, trying next: {}", url, e);
+                    endpoint.fail(self.cooldown);
+                    if let Some(body) = body {
+                        last_node_error = Some((body, url));
+                    }
+                    last_error = Some(e);
+                },
+                Attempt::Fatal(e) => return Err(e),
+            }
+        }
+
+        // Every node lagging is still a valid answer, let the caller parse it
+        if let Some(served) = last_node_error {
+            return Ok(served);
+        }
+
+        Err(last_error.unwrap_or_else(|| RpcError::Transport("No endpoints available".into())))
+    }
+
+    async fn attempt(
+        &self,
+        transport: &HyperTransport,
+        request: Bytes,
+        timeout: Duration,
+    ) -> Attempt {
+        let response = tokio::time::timeout(timeout, transport.post(request)).await;
+        let HttpResponse { status, body, .. } = match response {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => return Attempt::Failover(e, None),
+            Err(_) => {
+                return Attempt::Failover(RpcError::Timeout(timeout), None);
+            },
+        };
+
+        if !status.is_success() {
+            let error = RpcError::Http {
+                status: status.as_u16(),
+                body: String::from_utf8_lossy(&body).into_owned(),
+            };
+            return match status.is_server_error() {
+                true => Attempt::Failover(error, None),
+                false => Attempt::Fatal(error),
+            };
+        }
+
+        if is_lagging_node_error(&body) {
+            let error = RpcError::from_response(&body)
+                .unwrap_or_else(|| RpcError::Response("Lagging node".into()));
+            return Attempt::Failover(error, Some(body));
+        }
+
+        Attempt::Served(body)
+    }
+}
+
+/// Whether the response, or any response of a batch, is an error whose
+/// message says the node is lagging. Results and error data that merely
+/// contain such a phrase do not count
+fn is_lagging_node_error(body: &[u8]) -> bool {
+    if memmem::find(body, b"\"error\"").is_none() {
+        return false;
+    }
+
+    match body.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[') {
+        true => split_array(body).into_iter().any(|(s, e)| is_lagging_response(&body[s..e])),
+        false => is_lagging_response(body),
+    }
+}
+
+fn is_lagging_response(response: &[u8]) -> bool {
+    match RpcError::from_response(response) {
+        Some(RpcError::JsonRpc { message, .. }) => LAGGING_NODE_ERRORS
+            .iter()
+            .any(|needle| memmem::find(message.as_bytes(), needle).is_some()),
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl Transport for FallbackTransport {
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.execute_with_endpoint(request).await.map(|(response, _)| response)
+    }
+
+    fn endpoint(&self) -> Option<RpcUrl> {
+        Some(RpcUrl::clone(&self.active_url()))
+    }
+}
@@ -1,38 +1,46 @@
-use std::time::Duration;
-
-use async_trait::async_trait;
+use std::{path::Path, sync::Arc, time::Duration};
 
 use crate::{
-    hyper_transport::HyperTransport, reqwest_transport::ReqwestTransport, 
-    tower_transport::TowerTransport, direct_transport::DirectTransport, 
-    direct_reqwest_transport::DirectReqwestTransport, HttpTransport, RpcError,
+    direct_reqwest_transport::DirectReqwestTransport,
+    direct_transport::DirectTransport,
+    hyper_transport::HyperTransport,
+    reqwest_transport::ReqwestTransport,
+    tower_transport::TowerTransport,
+    transport::{
+        auth::AuthProvider, compression::Compression, fallback::FallbackTransport,
+        ipc::IpcTransport, retry::RetryPolicy, ws::WsTransport, IntoRpcUrl, RpcUrl,
+    },
+    HttpTransport, RpcError,
 };
 
-#[async_trait]
-pub trait Transport: Send + Sync + std::fmt::Debug {
-    async fn execute(&self, request: String) -> Result<String, RpcError>;
-    async fn connect(&self) -> Result<(), RpcError>;
-}
-
+/// Builds transports to `url`. Urls are checked when the transport is built,
+/// HTTP transports only take http(s) urls and `build_ws` only ws(s) ones
 pub struct TransportBuilder {
-    urls: Vec<&'static str>,
+    urls: Vec<Result<RpcUrl, RpcError>>,
     timeout: Duration,
-    max_retries: u32,
-    pool_max_idle: u32,
+    retry: RetryPolicy,
+    pool_max_idle: Option<usize>,
+    auth: Option<Arc<dyn AuthProvider>>,
+    compression: Compression,
 }
 
 impl TransportBuilder {
-    pub fn new(url: &'static str) -> Self {
+    pub fn new(url: impl IntoRpcUrl) -> Self {
         Self {
-            urls: vec![url],
+            urls: vec![url.into_rpc_url()],
             timeout: Duration::from_secs(10),
-            max_retries: 3,
-            pool_max_idle: 32,
+            retry: RetryPolicy::default(),
+            pool_max_idle: None,
+            auth: None,
+            compression: Compression::default(),
         }
     }
 
-    pub fn with_fallbacks(mut self, additional_urls: Vec<&'static str>) -> Self {
-        self.urls.extend(additional_urls);
+    pub fn with_fallbacks<U: IntoRpcUrl>(
+        mut self,
+        additional_urls: impl IntoIterator<Item = U>,
+    ) -> Self {
+        self.urls.extend(additional_urls.into_iter().map(IntoRpcUrl::into_rpc_url));
         self
     }
 
@@ -42,77 +50,201 @@ impl TransportBuilder {
     }
 
     pub fn max_retries(mut self, retries: u32) -> Self {
-        self.max_retries = retries;
+        self.retry = self.retry.max_retries(retries);
+        self
+    }
+
+    /// Replace the whole retry policy, `max_retries` then adjusts this one
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
         self
     }
 
+    /// Idle connections kept per host by `build_http`, `build_http_hyper`,
+    /// `build_reqwest`, `build_tower` and `build_fallback`, each keeps its own
+    /// default when unset. The tuned `_minimal`, `_optimized`, `_ultra` and
+    /// `_benchmark` builders always use their own pool sizes
     pub fn pool_max_idle(mut self, max_idle: u32) -> Self {
-        self.pool_max_idle = max_idle;
+        self.pool_max_idle = Some(max_idle as usize);
         self
     }
 
-    pub fn build_http(self) -> HttpTransport {
-        HttpTransport::new(self.urls[0])
+    /// Headers to authenticate with, e.g. `StaticHeaders` for an API key,
+    /// `BearerToken` or `JwtAuth` for the Engine API. Taken by the hyper,
+    /// reqwest, tower and fallback transports
+    pub fn auth(mut self, auth: impl AuthProvider + 'static) -> Self {
+        self.auth = Some(Arc::new(auth));
+        self
     }
 
-    pub fn build_http_hyper(self) -> HyperTransport {
-        if self.urls.len() > 1 {
-            HyperTransport::new_with_fallbacks(self.urls[0], self.urls[1..].to_vec())
-        } else {
-            HyperTransport::new(self.urls[0])
-        }
+    /// Response decompression and request compression for the hyper, tower
+    /// and fallback transports, responses are decompressed by default
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
     }
 
-    /// Build minimal Alloy-style Hyper transport for maximum performance 
-    pub fn build_http_hyper_minimal(self) -> HyperTransport {
-        HyperTransport::new_minimal(self.urls[0])
+    /// The only url of a single endpoint transport. Fails when urls were added
+    /// with `with_fallbacks` rather than leaving them unused
+    fn primary_url(&mut self) -> Result<RpcUrl, RpcError> {
+        if self.urls.len() > 1 {
+            return Err(RpcError::Transport(
+                "Fallback urls are only used by build_fallback".into(),
+            ));
+        }
+        self.urls.swap_remove(0)
+    }
+
+    /// The primary url, if it is a http(s) one
+    fn http_url(&mut self) -> Result<RpcUrl, RpcError> {
+        self.primary_url()?.require_http()
+    }
+
+    pub fn build_http(mut self) -> Result<HttpTransport, RpcError> {
+        let transport = HttpTransport::new(self.http_url()?)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout);
+        Ok(match self.pool_max_idle {
+            Some(max_idle) => transport.with_pool_max_idle(max_idle),
+            None => transport,
+        })
+    }
+
+    /// Single endpoint transport on the primary url. Errors when urls were
+    /// added with `with_fallbacks`, `build_fallback` fails over across them
+    pub fn build_http_hyper(mut self) -> Result<HyperTransport, RpcError> {
+        let transport = HyperTransport::new(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_compression(self.compression)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout);
+        Ok(match self.pool_max_idle {
+            Some(max_idle) => transport.with_pool_max_idle(max_idle),
+            None => transport,
+        })
+    }
+
+    /// Hyper transport that fails over across the primary and fallback urls
+    pub fn build_fallback(self) -> Result<FallbackTransport, RpcError> {
+        let urls = self
+            .urls
+            .into_iter()
+            .map(|url| url?.require_http())
+            .collect::<Result<Vec<_>, _>>()?;
+        let transport = FallbackTransport::new(urls)
+            .with_auth_provider(self.auth)
+            .with_compression(self.compression)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout);
+        Ok(match self.pool_max_idle {
+            Some(max_idle) => transport.with_pool_max_idle(max_idle),
+            None => transport,
+        })
+    }
+
+    /// Build minimal Alloy-style Hyper transport for maximum performance
+    pub fn build_http_hyper_minimal(mut self) -> Result<HyperTransport, RpcError> {
+        Ok(HyperTransport::new_minimal(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_compression(self.compression)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout))
     }
 
     /// Build ultra-fast Hyper transport to beat Alloy performance
-    pub fn build_http_hyper_ultra(self) -> HyperTransport {
-        HyperTransport::new_ultra_fast(self.urls[0])
+    pub fn build_http_hyper_ultra(mut self) -> Result<HyperTransport, RpcError> {
+        Ok(HyperTransport::new_ultra_fast(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_compression(self.compression)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout))
     }
 
     /// Build realistic Hyper transport for benchmarking (minimal connection pooling like Alloy)
-    pub fn build_http_hyper_benchmark(self) -> HyperTransport {
-        HyperTransport::new_benchmark_realistic(self.urls[0])
+    pub fn build_http_hyper_benchmark(mut self) -> Result<HyperTransport, RpcError> {
+        Ok(HyperTransport::new_benchmark_realistic(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_compression(self.compression)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout))
     }
 
     pub fn build_http_with_config(self, param: HttpTransport) -> HttpTransport {
         HttpTransport::new_with_config(param)
     }
 
-    pub fn build_reqwest(self) -> ReqwestTransport {
-        ReqwestTransport::new(self.urls[0])
+    pub fn build_reqwest(mut self) -> Result<ReqwestTransport, RpcError> {
+        let transport = ReqwestTransport::new(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout);
+        Ok(match self.pool_max_idle {
+            Some(max_idle) => transport.with_pool_max_idle(max_idle),
+            None => transport,
+        })
     }
 
-    pub fn build_reqwest_minimal(self) -> ReqwestTransport {
-        ReqwestTransport::new_minimal(self.urls[0])
+    pub fn build_reqwest_minimal(mut self) -> Result<ReqwestTransport, RpcError> {
+        Ok(ReqwestTransport::new_minimal(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout))
     }
 
-    pub fn build_reqwest_optimized(self) -> ReqwestTransport {
-        ReqwestTransport::new_optimized(self.urls[0])
+    pub fn build_reqwest_optimized(mut self) -> Result<ReqwestTransport, RpcError> {
+        Ok(ReqwestTransport::new_optimized(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout))
     }
 
-    pub fn build_reqwest_ultra_fast(self) -> ReqwestTransport {
-        ReqwestTransport::new_ultra_fast(self.urls[0])
+    pub fn build_reqwest_ultra_fast(mut self) -> Result<ReqwestTransport, RpcError> {
+        Ok(ReqwestTransport::new_ultra_fast(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout))
     }
 
-    pub fn build_tower(self) -> TowerTransport {
-        TowerTransport::new(self.urls[0])
+    pub fn build_tower(mut self) -> Result<TowerTransport, RpcError> {
+        let transport = TowerTransport::new(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_compression(self.compression)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout);
+        Ok(match self.pool_max_idle {
+            Some(max_idle) => transport.with_pool_max_idle(max_idle),
+            None => transport,
+        })
     }
 
-    pub fn build_tower_optimized(self) -> TowerTransport {
-        TowerTransport::new_optimized(self.urls[0])
+    pub fn build_tower_optimized(mut self) -> Result<TowerTransport, RpcError> {
+        Ok(TowerTransport::new_optimized(self.http_url()?)
+            .with_auth_provider(self.auth)
+            .with_compression(self.compression)
+            .with_retry_policy(self.retry)
+            .with_timeout(self.timeout))
     }
 
     /// Build direct transport for honest benchmarking (fresh connections every time)
-    pub fn build_direct(self) -> DirectTransport {
-        DirectTransport::new(self.urls[0])
+    pub fn build_direct(mut self) -> Result<DirectTransport, RpcError> {
+        Ok(DirectTransport::new(self.http_url()?))
     }
 
     /// Build direct reqwest transport for honest benchmarking (fresh clients every time)  
-    pub fn build_direct_reqwest(self) -> DirectReqwestTransport {
-        DirectReqwestTransport::new(self.urls[0])
+    pub fn build_direct_reqwest(mut self) -> Result<DirectReqwestTransport, RpcError> {
+        Ok(DirectReqwestTransport::new(self.http_url()?))
+    }
+
+    /// Connect a WebSocket transport to the primary url; the builder timeout
+    /// bounds how long each request waits for its response
+    pub async fn build_ws(mut self) -> Result<WsTransport, RpcError> {
+        WsTransport::connect_with_timeout(self.primary_url()?, self.timeout).await
+    }
+
+    /// Connect to a node's IPC socket at `path`, e.g. `~/.ethereum/geth.ipc`,
+    /// with the builder timeout. The builder urls are not used, without a
+    /// timeout to set `IpcTransport::connect` does the same
+    pub async fn build_ipc(self, path: impl AsRef<Path>) -> Result<IpcTransport, RpcError> {
+        IpcTransport::connect_with_timeout(path, self.timeout).await
     }
 }
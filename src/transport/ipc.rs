@@ -0,0 +1,220 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use bytes::{Buf, Bytes, BytesMut};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::UnixStream,
+};
+use tracing::info;
+
+use super::{
+    pubsub::{Connector, PubSubConnection, PubSubFrontend, RawSubscription},
+    Transport,
+};
+use crate::RpcError;
+
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const READ_CHUNK: usize = 16 * 1024;
+
+#[derive(Debug)]
+struct IpcConnector {
+    path: PathBuf,
+}
+
+struct IpcConnection {
+    stream: UnixStream,
+    framer: JsonFramer,
+}
+
+#[async_trait]
+impl Connector for IpcConnector {
+    type Connection = IpcConnection;
+
+    async fn connect(&self) -> Result<IpcConnection, RpcError> {
+        let stream = UnixStream::connect(&self.path).await.map_err(|e| {
+            RpcError::Connection(format!("IPC connect to {} failed: {}", self.path.display(), e))
+        })?;
+
+        Ok(IpcConnection {
+            stream,
+            framer: JsonFramer::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl PubSubConnection for IpcConnection {
+    async fn send(&mut self, payload: Bytes) -> Result<(), RpcError> {
+        let mut message = Vec::with_capacity(payload.len() + 1);
+        message.extend_from_slice(&payload);
+        message.push(b'\n');
+
+        self.stream
+            .write_all(&message)
+            .await
+            .map_err(|e| RpcError::Connection(format!("IPC write failed: {}", e)))
+    }
+
+    async fn recv(&mut self) -> Option<Result<Bytes, RpcError>> {
+        loop {
+            if let Some(message) = self.framer.next_message() {
+                return Some(Ok(message));
+            }
+
+            self.framer.buf.reserve(READ_CHUNK);
+            match self.stream.read_buf(&mut self.framer.buf).await {
+                Ok(0) => return None,
+                Ok(_) => continue,
+                Err(e) => {
+                    return Some(Err(RpcError::Connection(format!("IPC read failed: {}", e))))
+                },
+            }
+        }
+    }
+}
+
+/// Splits a byte stream into top level JSON values. Nodes may or may not
+/// separate messages with newlines, so framing goes by bracket depth and
+/// scanning resumes where the previous read left off.
+#[derive(Debug, Default)]
+struct JsonFramer {
+    buf: BytesMut,
+    scanned: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl JsonFramer {
+    fn next_message(&mut self) -> Option<Bytes> {
+        if self.depth == 0 && self.scanned == 0 {
+            // Drop separators between messages
+            let start = self.buf.iter().position(|b| !b.is_ascii_whitespace());
+            self.buf.advance(start.unwrap_or(self.buf.len()));
+        }
+
+        while self.scanned < self.buf.len() {
+            let byte = self.buf[self.scanned];
+            self.scanned += 1;
+
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {},
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 0 {
+                        let message = self.buf.split_to(self.scanned).freeze();
+                        self.scanned = 0;
+                        return Some(message);
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        None
+    }
+}
+
+/// JSON-RPC over a node's Unix domain socket (e.g. `geth.ipc`). Shares the
+/// request correlation, reconnect and subscription handling of `WsTransport`.
+#[derive(Debug, Clone)]
+pub struct IpcTransport {
+    path: PathBuf,
+    frontend: Arc<PubSubFrontend>,
+}
+
+impl IpcTransport {
+    /// Connects to the socket at `path`, no url involved
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self, RpcError> {
+        Self::connect_with_timeout(path, DEFAULT_REQUEST_TIMEOUT).await
+    }
+
+    /// `connect` with a bound on how long each request waits for its response
+    pub async fn connect_with_timeout(
+        path: impl AsRef<Path>,
+        request_timeout: Duration,
+    ) -> Result<Self, RpcError> {
+        let path = path.as_ref().to_path_buf();
+        let connector = IpcConnector { path: path.clone() };
+        let frontend = PubSubFrontend::connect(connector, request_timeout).await?;
+
+        info!("IPC transport connected to {}", path.display());
+
+        Ok(Self {
+            path,
+            frontend: Arc::new(frontend),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl Transport for IpcTransport {
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.frontend.request(&request).await.map(Bytes::from)
+    }
+
+    /// Responses are matched by id, requests share the connection
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    fn supports_subscriptions(&self) -> bool {
+        true
+    }
+
+    async fn subscribe(&self, params: Value) -> Result<RawSubscription, RpcError> {
+        self.frontend.subscribe(params.to_string().as_bytes()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(framer: &mut JsonFramer, chunk: &[u8]) -> Vec<Bytes> {
+        framer.buf.extend_from_slice(chunk);
+        std::iter::from_fn(|| framer.next_message()).collect()
+    }
+
+    #[test]
+    fn test_frames_split_and_coalesced_messages() {
+        let mut framer = JsonFramer::default();
+
+        assert!(feed(&mut framer, br#"{"id":1,"result":"0x{"#).is_empty());
+        let messages = feed(&mut framer, b"]\"}\n{\"id\":2}[{\"id\":3}]");
+        assert_eq!(messages.len(), 3);
+        assert_eq!(&messages[0][..], br#"{"id":1,"result":"0x{]"}"#);
+        assert_eq!(&messages[1][..], br#"{"id":2}"#);
+        assert_eq!(&messages[2][..], br#"[{"id":3}]"#);
+    }
+
+    #[test]
+    fn test_frames_escaped_quotes() {
+        let mut framer = JsonFramer::default();
+
+        let messages = feed(&mut framer, br#"  {"error":{"message":"bad \"}\" here"}}"#);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(&messages[0][..], br#"{"error":{"message":"bad \"}\" here"}}"#);
+    }
+}
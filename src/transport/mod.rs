@@ -1 +1,58 @@
+pub mod auth;
+pub mod compression;
+pub mod fallback;
 pub mod http;
+pub mod ipc;
+pub mod pubsub;
+pub mod retry;
+pub mod url;
+pub mod ws;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde_json::Value;
+
+pub use self::url::{IntoRpcUrl, RpcUrl};
+use crate::{transport::pubsub::RawSubscription, RpcError};
+
+/// Carries serialized JSON-RPC payloads to a node. `request` is all a
+/// transport needs, batching and subscriptions are opt-in capabilities
+#[async_trait]
+pub trait Transport: Send + Sync + std::fmt::Debug {
+    /// Sends one payload, a single request or a batch array, and returns the
+    /// raw response
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError>;
+
+    /// Whether `request_batch` multiplexes requests, making small batches
+    /// cheaper than a JSON-RPC batch array
+    fn supports_batch(&self) -> bool {
+        false
+    }
+
+    /// Sends the requests concurrently, answering each in request order
+    async fn request_batch(&self, requests: Vec<Bytes>) -> Vec<Result<Bytes, RpcError>> {
+        futures::future::join_all(requests.into_iter().map(|request| self.request(request))).await
+    }
+
+    /// Whether `subscribe` can succeed, only transports holding a persistent
+    /// connection support it
+    fn supports_subscriptions(&self) -> bool {
+        false
+    }
+
+    /// Opens an `eth_subscribe` subscription with the given params array
+    async fn subscribe(&self, _params: Value) -> Result<RawSubscription, RpcError> {
+        Err(RpcError::Transport("Transport does not support subscriptions".into()))
+    }
+
+    /// Url requests currently go to, `None` when there is no single one
+    fn endpoint(&self) -> Option<RpcUrl> {
+        None
+    }
+
+    /// Sends every later request to `url`. Only single endpoint HTTP
+    /// transports support this, socket transports stay on their connection
+    fn set_endpoint(&self, _url: RpcUrl) -> Result<(), RpcError> {
+        Err(RpcError::Transport("Transport does not support changing endpoints".into()))
+    }
+}
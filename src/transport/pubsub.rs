@@ -0,0 +1,620 @@
+use std::{
+    collections::HashMap,
+    ops::ControlFlow,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::Stream;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+
+use crate::{
+    parser::lib::{split_array, top_level_field},
+    RpcError,
+};
+
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Methods with side effects on the node. A call to one of these that was in
+/// flight when the connection dropped may or may not have gone through, so it
+/// fails instead of being sent again
+const NON_IDEMPOTENT: [&[u8]; 5] = [
+    b"eth_sendTransaction",
+    b"eth_sendRawTransaction",
+    b"eth_sendRawTransactionConditional",
+    b"eth_sendRawTransactionSync",
+    b"personal_sendTransaction",
+];
+
+type ResponseSender = oneshot::Sender<Result<Bytes, RpcError>>;
+
+/// A single persistent, message oriented connection to a node
+#[async_trait]
+pub(crate) trait PubSubConnection: Send + 'static {
+    async fn send(&mut self, payload: Bytes) -> Result<(), RpcError>;
+
+    /// Next complete JSON-RPC message, `None` once the connection is closed
+    async fn recv(&mut self) -> Option<Result<Bytes, RpcError>>;
+}
+
+/// Knows how to (re)open a `PubSubConnection`
+#[async_trait]
+pub(crate) trait Connector: Send + Sync + 'static {
+    type Connection: PubSubConnection;
+
+    async fn connect(&self) -> Result<Self::Connection, RpcError>;
+}
+
+#[derive(Debug)]
+enum Instruction {
+    Request {
+        ids: Vec<u64>,
+        payload: Bytes,
+        tx: ResponseSender,
+    },
+    Subscribe {
+        local_id: u64,
+        params: Bytes,
+        tx: oneshot::Sender<Result<(), RpcError>>,
+        sink: mpsc::UnboundedSender<Bytes>,
+    },
+    Unsubscribe {
+        local_id: u64,
+    },
+}
+
+#[derive(Debug)]
+enum Pending {
+    Call(ResponseSender),
+    /// `tx` is `None` when re-subscribing after a reconnect
+    Subscribe {
+        local_id: u64,
+        tx: Option<oneshot::Sender<Result<(), RpcError>>>,
+    },
+}
+
+#[derive(Debug)]
+struct InFlight {
+    payload: Bytes,
+    ids: Vec<u64>,
+    /// Safe to send again on a new connection
+    replayable: bool,
+    pending: Pending,
+}
+
+#[derive(Debug)]
+struct Subscription {
+    params: Bytes,
+    /// Id the node handed out, only valid for the current connection
+    server_id: Option<Vec<u8>>,
+    sink: mpsc::UnboundedSender<Bytes>,
+}
+
+/// Raw `result` payloads of an `eth_subscribe` subscription. The subscription
+/// survives reconnects and is cancelled on the node once this is dropped.
+#[derive(Debug)]
+pub struct RawSubscription {
+    local_id: u64,
+    rx: mpsc::UnboundedReceiver<Bytes>,
+    instructions: mpsc::UnboundedSender<Instruction>,
+}
+
+impl Stream for RawSubscription {
+    type Item = Bytes;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Bytes>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for RawSubscription {
+    fn drop(&mut self) {
+        let _ = self.instructions.send(Instruction::Unsubscribe {
+            local_id: self.local_id,
+        });
+    }
+}
+
+/// Handle held by a socket based transport. Every request gets fresh ids so
+/// callers may reuse ids freely while sharing one connection; the original ids
+/// are put back before the response is handed out.
+#[derive(Debug)]
+pub(crate) struct PubSubFrontend {
+    instructions: mpsc::UnboundedSender<Instruction>,
+    next_id: Arc<AtomicU64>,
+    request_timeout: Duration,
+}
+
+impl PubSubFrontend {
+    /// Opens the first connection eagerly so a bad endpoint fails at build
+    /// time, then hands it to a background task that owns it from then on
+    pub(crate) async fn connect<C: Connector>(
+        connector: C,
+        request_timeout: Duration,
+    ) -> Result<Self, RpcError> {
+        let connection = connector.connect().await?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        let next_id = Arc::new(AtomicU64::new(1));
+
+        let backend = PubSubBackend {
+            connector,
+            instructions: rx,
+            next_id: next_id.clone(),
+            in_flight: HashMap::new(),
+            batch_ids: HashMap::new(),
+            subscriptions: HashMap::new(),
+            server_ids: HashMap::new(),
+        };
+        tokio::spawn(backend.run(connection));
+
+        Ok(Self {
+            instructions: tx,
+            next_id,
+            request_timeout,
+        })
+    }
+
+    pub(crate) async fn request(&self, request: &[u8]) -> Result<Vec<u8>, RpcError> {
+        let mut originals: Vec<(u64, Vec<u8>)> = Vec::with_capacity(1);
+        let payload = map_ids(request, |old| {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            originals.push((id, old.to_vec()));
+            Some(id.to_string().into_bytes())
+        })
+        .filter(|_| !originals.is_empty())
+        .ok_or_else(|| RpcError::Transport("Request has no JSON-RPC id".into()))?;
+
+        let (tx, rx) = oneshot::channel();
+        self.instructions
+            .send(Instruction::Request {
+                ids: originals.iter().map(|(id, _)| *id).collect(),
+                payload: payload.into(),
+                tx,
+            })
+            .map_err(|_| RpcError::Connection("Connection task has shut down".into()))?;
+
+        let response = tokio::time::timeout(self.request_timeout, rx)
+            .await
+            .map_err(|_| RpcError::Timeout(self.request_timeout))?
+            .map_err(|_| RpcError::Connection("Connection task dropped the request".into()))??;
+
+        map_ids(&response, |new| {
+            let new = parse_id(new)?;
+            originals.iter().find(|(id, _)| *id == new).map(|(_, old)| old.clone())
+        })
+        .ok_or_else(|| RpcError::Response("Response id does not match the request".into()))
+    }
+
+    /// Sends `eth_subscribe` with the given JSON params array and waits for
+    /// the node to accept it
+    pub(crate) async fn subscribe(&self, params: &[u8]) -> Result<RawSubscription, RpcError> {
+        let local_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sink, rx) = mpsc::unbounded_channel();
+        let (tx, confirmed) = oneshot::channel();
+
+        self.instructions
+            .send(Instruction::Subscribe {
+                local_id,
+                params: Bytes::copy_from_slice(params),
+                tx,
+                sink,
+            })
+            .map_err(|_| RpcError::Connection("Connection task has shut down".into()))?;
+
+        // Created before waiting so a timeout still unsubscribes on drop
+        let subscription = RawSubscription {
+            local_id,
+            rx,
+            instructions: self.instructions.clone(),
+        };
+
+        tokio::time::timeout(self.request_timeout, confirmed)
+            .await
+            .map_err(|_| RpcError::Timeout(self.request_timeout))?
+            .map_err(|_| RpcError::Connection("Connection task dropped the request".into()))??;
+
+        Ok(subscription)
+    }
+}
+
+struct PubSubBackend<C: Connector> {
+    connector: C,
+    instructions: mpsc::UnboundedReceiver<Instruction>,
+    next_id: Arc<AtomicU64>,
+    /// Requests awaiting a response, keyed by their first id
+    in_flight: HashMap<u64, InFlight>,
+    /// Remaining ids of batch requests mapped to the key of their `InFlight`
+    batch_ids: HashMap<u64, u64>,
+    subscriptions: HashMap<u64, Subscription>,
+    /// Node side subscription ids of the current connection
+    server_ids: HashMap<Vec<u8>, u64>,
+}
+
+impl<C: Connector> PubSubBackend<C> {
+    async fn run(mut self, mut connection: C::Connection) {
+        loop {
+            if self.drive(&mut connection).await.is_break() {
+                debug!("All transport handles dropped, closing connection");
+                return;
+            }
+
+            warn!("Connection lost, reconnecting");
+            connection = match self.reconnect().await {
+                Some(connection) => connection,
+                None => return,
+            };
+        }
+    }
+
+    async fn reconnect(&mut self) -> Option<C::Connection> {
+        let mut backoff = INITIAL_RECONNECT_BACKOFF;
+        loop {
+            if self.instructions.is_closed() {
+                return None;
+            }
+
+            tokio::time::sleep(backoff).await;
+            match self.connector.connect().await {
+                Ok(connection) => {
+                    debug!("Reconnected after connection loss");
+                    return Some(connection);
+                },
+                Err(e) => {
+                    warn!("Reconnect failed: {}", e);
+                    backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+                },
+            }
+        }
+    }
+
+    /// Serves one connection until it drops. Breaks once every frontend is gone
+    async fn drive(&mut self, connection: &mut C::Connection) -> ControlFlow<()> {
+        // Drop requests whose caller already gave up
+        let subscriptions = &self.subscriptions;
+        self.in_flight.retain(|_, request| match &request.pending {
+            Pending::Call(tx) => !tx.is_closed(),
+            Pending::Subscribe { local_id, .. } => subscriptions.contains_key(local_id),
+        });
+
+        // Sending a transaction twice could broadcast it twice, let the caller decide
+        let lost: Vec<u64> = self
+            .in_flight
+            .iter()
+            .filter(|(_, request)| !request.replayable)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in lost {
+            let Some(request) = self.in_flight.remove(&key) else {
+                continue;
+            };
+            for id in &request.ids[1..] {
+                self.batch_ids.remove(id);
+            }
+            if let Pending::Call(tx) = request.pending {
+                let _ = tx.send(Err(RpcError::Connection(
+                    "Connection lost before the node answered a non-idempotent request".into(),
+                )));
+            }
+        }
+
+        // Node side subscriptions died with the old connection, open them again
+        self.server_ids.clear();
+        for (local_id, subscription) in self.subscriptions.iter_mut() {
+            if subscription.server_id.take().is_some() {
+                let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                self.in_flight.insert(
+                    id,
+                    InFlight {
+                        payload: subscribe_payload(id, &subscription.params),
+                        ids: vec![id],
+                        replayable: true,
+                        pending: Pending::Subscribe {
+                            local_id: *local_id,
+                            tx: None,
+                        },
+                    },
+                );
+            }
+        }
+
+        // Replay whatever was in flight when the previous connection died
+        for request in self.in_flight.values() {
+            if connection.send(request.payload.clone()).await.is_err() {
+                return ControlFlow::Continue(());
+            }
+        }
+
+        loop {
+            let result = tokio::select! {
+                instruction = self.instructions.recv() => {
+                    let Some(instruction) = instruction else {
+                        return ControlFlow::Break(());
+                    };
+                    self.handle_instruction(connection, instruction).await
+                },
+                message = connection.recv() => match message {
+                    Some(Ok(message)) => self.dispatch(connection, message).await,
+                    Some(Err(e)) => Err(e),
+                    None => return ControlFlow::Continue(()),
+                },
+            };
+
+            if let Err(e) = result {
+                warn!("Connection failed: {}", e);
+                return ControlFlow::Continue(());
+            }
+        }
+    }
+
+    async fn handle_instruction(
+        &mut self,
+        connection: &mut C::Connection,
+        instruction: Instruction,
+    ) -> Result<(), RpcError> {
+        match instruction {
+            Instruction::Request { ids, payload, tx } => {
+                let key = ids[0];
+                for id in &ids[1..] {
+                    self.batch_ids.insert(*id, key);
+                }
+
+                // Track before sending so a failed write gets replayed
+                let sent = connection.send(payload.clone()).await;
+                self.in_flight.insert(
+                    key,
+                    InFlight {
+                        replayable: is_replayable(&payload),
+                        payload,
+                        ids,
+                        pending: Pending::Call(tx),
+                    },
+                );
+                sent
+            },
+            Instruction::Subscribe {
+                local_id,
+                params,
+                tx,
+                sink,
+            } => {
+                let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                let payload = subscribe_payload(id, &params);
+                self.subscriptions.insert(
+                    local_id,
+                    Subscription {
+                        params,
+                        server_id: None,
+                        sink,
+                    },
+                );
+
+                let sent = connection.send(payload.clone()).await;
+                self.in_flight.insert(
+                    id,
+                    InFlight {
+                        payload,
+                        ids: vec![id],
+                        replayable: true,
+                        pending: Pending::Subscribe {
+                            local_id,
+                            tx: Some(tx),
+                        },
+                    },
+                );
+                sent
+            },
+            Instruction::Unsubscribe { local_id } => {
+                let server_id = self.subscriptions.remove(&local_id).and_then(|sub| sub.server_id);
+                match server_id {
+                    Some(server_id) => {
+                        self.server_ids.remove(&server_id);
+                        self.unsubscribe(connection, &server_id).await
+                    },
+                    // Still in flight, unsubscribed once the node confirms it
+                    None => Ok(()),
+                }
+            },
+        }
+    }
+
+    async fn dispatch(
+        &mut self,
+        connection: &mut C::Connection,
+        message: Bytes,
+    ) -> Result<(), RpcError> {
+        let id = {
+            let first = split_array(&message).first().map(|&(s, e)| message.slice(s..e));
+            let object = first.as_deref().unwrap_or(&message);
+            top_level_field(object, b"id").and_then(|(s, e)| parse_id(&object[s..e]))
+        };
+
+        match id {
+            Some(id) => self.complete(connection, id, message).await,
+            None => self.notify(connection, message).await,
+        }
+    }
+
+    async fn complete(
+        &mut self,
+        connection: &mut C::Connection,
+        id: u64,
+        message: Bytes,
+    ) -> Result<(), RpcError> {
+        let key = self.batch_ids.remove(&id).unwrap_or(id);
+        let Some(request) = self.in_flight.remove(&key) else {
+            debug!("Dropping response for unknown id {}", id);
+            return Ok(());
+        };
+        for id in &request.ids[1..] {
+            self.batch_ids.remove(id);
+        }
+
+        match request.pending {
+            Pending::Call(tx) => {
+                let _ = tx.send(Ok(message));
+                Ok(())
+            },
+            Pending::Subscribe { local_id, tx } => {
+                let server_id =
+                    top_level_field(&message, b"result").map(|(s, e)| unquote(&message[s..e]));
+
+                match (server_id, self.subscriptions.get_mut(&local_id)) {
+                    (Some(server_id), Some(subscription)) => {
+                        subscription.server_id = Some(server_id.to_vec());
+                        self.server_ids.insert(server_id.to_vec(), local_id);
+                        if let Some(tx) = tx {
+                            let _ = tx.send(Ok(()));
+                        }
+                        Ok(())
+                    },
+                    // Dropped by the caller before the node confirmed it
+                    (Some(server_id), None) => self.unsubscribe(connection, server_id).await,
+                    (None, _) => {
+                        let error = RpcError::from_response(&message).unwrap_or_else(|| {
+                            RpcError::Response("Missing subscription id".into())
+                        });
+                        warn!("Subscription rejected: {}", error);
+
+                        // Dropping the sink ends the stream of a re-subscription
+                        self.subscriptions.remove(&local_id);
+                        if let Some(tx) = tx {
+                            let _ = tx.send(Err(error));
+                        }
+                        Ok(())
+                    },
+                }
+            },
+        }
+    }
+
+    async fn notify(
+        &mut self,
+        connection: &mut C::Connection,
+        message: Bytes,
+    ) -> Result<(), RpcError> {
+        let Some((params_start, params_end)) = top_level_field(&message, b"params") else {
+            debug!("Dropping message without id or params");
+            return Ok(());
+        };
+        let params = &message[params_start..params_end];
+
+        let (Some((sub_start, sub_end)), Some((start, end))) =
+            (top_level_field(params, b"subscription"), top_level_field(params, b"result"))
+        else {
+            debug!("Dropping malformed subscription notification");
+            return Ok(());
+        };
+
+        let server_id = unquote(&params[sub_start..sub_end]);
+        let Some(&local_id) = self.server_ids.get(server_id) else {
+            debug!("Dropping notification for unknown subscription");
+            return Ok(());
+        };
+
+        let payload = message.slice(params_start + start..params_start + end);
+        let delivered = self
+            .subscriptions
+            .get(&local_id)
+            .is_some_and(|sub| sub.sink.send(payload).is_ok());
+
+        if !delivered {
+            // The stream went away without unsubscribing
+            let server_id = server_id.to_vec();
+            self.subscriptions.remove(&local_id);
+            self.server_ids.remove(&server_id);
+            return self.unsubscribe(connection, &server_id).await;
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &mut self,
+        connection: &mut C::Connection,
+        server_id: &[u8],
+    ) -> Result<(), RpcError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut payload = Vec::with_capacity(96 + server_id.len());
+        payload.extend_from_slice(br#"{"jsonrpc":"2.0","method":"eth_unsubscribe","params":[""#);
+        payload.extend_from_slice(server_id);
+        payload.extend_from_slice(br#""],"id":"#);
+        payload.extend_from_slice(id.to_string().as_bytes());
+        payload.push(b'}');
+
+        // The response is dropped as an unknown id
+        connection.send(payload.into()).await
+    }
+}
+
+#[inline]
+fn subscribe_payload(id: u64, params: &[u8]) -> Bytes {
+    let mut payload = Vec::with_capacity(80 + params.len());
+    payload.extend_from_slice(br#"{"jsonrpc":"2.0","method":"eth_subscribe","params":"#);
+    payload.extend_from_slice(params);
+    payload.extend_from_slice(br#","id":"#);
+    payload.extend_from_slice(id.to_string().as_bytes());
+    payload.push(b'}');
+    payload.into()
+}
+
+/// Whether none of the requests in `payload`, a single message or a batch,
+/// calls a `NON_IDEMPOTENT` method
+fn is_replayable(payload: &[u8]) -> bool {
+    let calls_non_idempotent = |request: &[u8]| {
+        top_level_field(request, b"method")
+            .is_some_and(|(s, e)| NON_IDEMPOTENT.contains(&unquote(&request[s..e])))
+    };
+
+    match payload.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[') {
+        true => !split_array(payload)
+            .into_iter()
+            .any(|(s, e)| calls_non_idempotent(&payload[s..e])),
+        false => !calls_non_idempotent(payload),
+    }
+}
+
+#[inline]
+fn unquote(raw: &[u8]) -> &[u8] {
+    match raw {
+        [b'"', inner @ .., b'"'] => inner,
+        _ => raw,
+    }
+}
+
+#[inline]
+fn parse_id(raw: &[u8]) -> Option<u64> {
+    std::str::from_utf8(raw).ok()?.parse().ok()
+}
+
+/// Replaces the top-level `id` of a JSON-RPC message, or of every message in a
+/// batch, with whatever `map` returns for it
+fn map_ids(message: &[u8], mut map: impl FnMut(&[u8]) -> Option<Vec<u8>>) -> Option<Vec<u8>> {
+    let mut ranges = Vec::with_capacity(1);
+    if message.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[') {
+        for (start, end) in split_array(message) {
+            let (s, e) = top_level_field(&message[start..end], b"id")?;
+            ranges.push((start + s, start + e));
+        }
+    } else {
+        ranges.push(top_level_field(message, b"id")?);
+    }
+
+    let mut result = Vec::with_capacity(message.len() + 8 * ranges.len());
+    let mut last = 0;
+    for (start, end) in ranges {
+        result.extend_from_slice(&message[last..start]);
+        result.extend_from_slice(&map(&message[start..end])?);
+        last = end;
+    }
+    result.extend_from_slice(&message[last..]);
+
+    Some(result)
+}
@@ -0,0 +1,412 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use http::{header::RETRY_AFTER, HeaderMap, StatusCode};
+use memchr::memmem;
+use tracing::debug;
+
+use crate::RpcError;
+
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// JSON-RPC "limit exceeded", what most providers answer when rate limiting
+const LIMIT_EXCEEDED: &[u8] = b"\"code\":-32005";
+
+/// What went wrong with an attempt, used to decide whether to retry it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The endpoint could not be reached or the connection dropped
+    Connection,
+    /// The attempt did not finish within the request timeout
+    Timeout,
+    /// HTTP 429 or JSON-RPC -32005
+    RateLimited,
+    /// HTTP 5xx
+    Server,
+    /// Any other non-success HTTP status
+    Client,
+}
+
+impl ErrorClass {
+    /// Default decision: everything except client errors is transient
+    pub fn is_transient(self) -> bool {
+        !matches!(self, ErrorClass::Client)
+    }
+
+    /// Class of an error a transport gave up with, `None` for errors that
+    /// are not about reaching the endpoint
+    pub(crate) fn of(error: &RpcError) -> Option<Self> {
+        match error {
+            RpcError::Connection(_) => Some(ErrorClass::Connection),
+            RpcError::Timeout(_) => Some(ErrorClass::Timeout),
+            RpcError::Http { status: 429, .. } => Some(ErrorClass::RateLimited),
+            RpcError::Http { status, .. } if *status >= 500 => Some(ErrorClass::Server),
+            RpcError::Http { .. } => Some(ErrorClass::Client),
+            _ => None,
+        }
+    }
+}
+
+/// Raw outcome of a single HTTP attempt
+#[derive(Debug)]
+pub(crate) struct HttpResponse {
+    pub status: StatusCode,
+    pub retry_after: Option<Duration>,
+    pub body: Bytes,
+}
+
+impl HttpResponse {
+    pub fn new(status: StatusCode, headers: &HeaderMap, body: Bytes) -> Self {
+        Self {
+            status,
+            retry_after: parse_retry_after(headers),
+            body,
+        }
+    }
+}
+
+/// Only the delta-seconds form of `Retry-After` is understood, which is what
+/// RPC providers send
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// Shared allowance of retries so a failing endpoint is not hammered by every
+/// caller at once. Each retry withdraws one token and each successful request
+/// deposits `retry_ratio` of one, up to `max_tokens`.
+#[derive(Debug)]
+pub struct RetryBudget {
+    // Stored in thousandths of a token
+    balance: AtomicU64,
+    max: u64,
+    deposit: u64,
+}
+
+impl RetryBudget {
+    pub fn new(max_tokens: u32, retry_ratio: f32) -> Self {
+        let max = max_tokens as u64 * 1000;
+        Self {
+            balance: AtomicU64::new(max),
+            max,
+            deposit: (retry_ratio.max(0.0) * 1000.0) as u64,
+        }
+    }
+
+    fn deposit(&self) {
+        let _ = self.balance.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| {
+            Some((balance + self.deposit).min(self.max))
+        });
+    }
+
+    fn withdraw(&self) -> bool {
+        self.balance
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| balance.checked_sub(1000))
+            .is_ok()
+    }
+}
+
+/// Retry policy shared by the HTTP transports: exponential backoff with
+/// jitter, an optional retry budget and a per error class decision.
+/// Rate limited responses wait for `Retry-After` when the endpoint sends one,
+/// up to the maximum backoff.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    deadline: Option<Duration>,
+    budget: Option<Arc<RetryBudget>>,
+    retry_if: Arc<dyn Fn(ErrorClass) -> bool + Send + Sync>,
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_retries", &self.max_retries)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("jitter", &self.jitter)
+            .field("deadline", &self.deadline)
+            .field("budget", &self.budget)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            deadline: None,
+            budget: None,
+            retry_if: Arc::new(ErrorClass::is_transient),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        Self::default().max_retries(0)
+    }
+
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Upper bound for a request across all of its attempts and the waits
+    /// between them. No retry is started that would wait past it
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn budget(mut self, budget: RetryBudget) -> Self {
+        self.budget = Some(Arc::new(budget));
+        self
+    }
+
+    /// Override which error classes are retried
+    pub fn retry_if(mut self, f: impl Fn(ErrorClass) -> bool + Send + Sync + 'static) -> Self {
+        self.retry_if = Arc::new(f);
+        self
+    }
+
+    /// Delay before retry number `attempt` (0 based). With jitter the delay is
+    /// drawn from the upper half of the exponential step.
+    pub fn backoff_for(&self, attempt: u32) -> Duration {
+        let step = self
+            .initial_backoff
+            .saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX))
+            .min(self.max_backoff);
+
+        if !self.jitter {
+            return step;
+        }
+
+        let half = step / 2;
+        let random = RandomState::new().build_hasher().finish();
+        half + Duration::from_nanos(random % (half.as_nanos() as u64 + 1))
+    }
+
+    /// Runs `send` until it succeeds, the error is not retryable or retries run
+    /// out. Every attempt is bounded by `timeout` and what is left of the
+    /// deadline.
+    pub(crate) async fn execute<F, Fut>(
+        &self,
+        timeout: Duration,
+        mut send: F,
+    ) -> Result<Bytes, RpcError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<HttpResponse, RpcError>>,
+    {
+        let started = Instant::now();
+        let mut attempt = 0;
+
+        loop {
+            let timeout = self.attempt_timeout(started, timeout);
+            let failure = match tokio::time::timeout(timeout, send()).await {
+                Ok(Ok(response)) => match classify(response) {
+                    Ok(body) => {
+                        self.succeeded();
+                        return Ok(body);
+                    },
+                    Err(failure) => failure,
+                },
+                Ok(Err(e)) => Failure {
+                    class: ErrorClass::Connection,
+                    retry_after: None,
+                    result: Err(e),
+                },
+                Err(_) => Failure {
+                    class: ErrorClass::Timeout,
+                    retry_after: None,
+                    result: Err(RpcError::Timeout(timeout)),
+                },
+            };
+
+            let Some(delay) =
+                self.retry_delay(started, attempt, failure.class, failure.retry_after)
+            else {
+                return failure.result;
+            };
+            debug!("Retrying after {:?} ({:?}, attempt {})", delay, failure.class, attempt + 1);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// `timeout` cut down to what is left of the deadline of a request that
+    /// started at `started`
+    pub(crate) fn attempt_timeout(&self, started: Instant, timeout: Duration) -> Duration {
+        match self.deadline {
+            Some(deadline) => deadline.saturating_sub(started.elapsed()).min(timeout),
+            None => timeout,
+        }
+    }
+
+    /// How long to wait before retry number `attempt` (0 based) after a
+    /// failure of `class`, `None` when it should not be retried
+    pub(crate) fn retry_delay(
+        &self,
+        started: Instant,
+        attempt: u32,
+        class: ErrorClass,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_retries || !(self.retry_if)(class) {
+            return None;
+        }
+
+        let delay = match retry_after {
+            Some(retry_after) => retry_after.min(self.max_backoff),
+            None => self.backoff_for(attempt),
+        };
+        if self.deadline.is_some_and(|deadline| started.elapsed() + delay >= deadline) {
+            return None;
+        }
+
+        // Withdrawn last so a retry that is not made does not cost a token
+        match &self.budget {
+            Some(budget) if !budget.withdraw() => None,
+            _ => Some(delay),
+        }
+    }
+
+    /// Refills the retry budget, if any, after a successful request
+    pub(crate) fn succeeded(&self) {
+        if let Some(budget) = &self.budget {
+            budget.deposit();
+        }
+    }
+}
+
+struct Failure {
+    class: ErrorClass,
+    retry_after: Option<Duration>,
+    /// Returned as is once no retry is left
+    result: Result<Bytes, RpcError>,
+}
+
+fn classify(response: HttpResponse) -> Result<Bytes, Failure> {
+    let HttpResponse {
+        status,
+        retry_after,
+        body,
+    } = response;
+
+    let class = if status == StatusCode::TOO_MANY_REQUESTS {
+        ErrorClass::RateLimited
+    } else if status.is_server_error() {
+        ErrorClass::Server
+    } else if !status.is_success() {
+        ErrorClass::Client
+    } else if memmem::find(&body, LIMIT_EXCEEDED).is_some() {
+        // The node answered, so hand the error body back if retries run out
+        return Err(Failure {
+            class: ErrorClass::RateLimited,
+            retry_after,
+            result: Ok(body),
+        });
+    } else {
+        return Ok(body);
+    };
+
+    Err(Failure {
+        class,
+        retry_after,
+        result: Err(RpcError::Http {
+            status: status.as_u16(),
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let policy = RetryPolicy::default()
+            .backoff(Duration::from_millis(100), Duration::from_secs(1))
+            .jitter(false);
+
+        assert_eq!(policy.backoff_for(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff_for(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff_for(10), Duration::from_secs(1));
+        assert_eq!(policy.backoff_for(64), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_upper_half() {
+        let policy =
+            RetryPolicy::default().backoff(Duration::from_millis(200), Duration::from_secs(1));
+
+        for attempt in 0..4 {
+            let step = Duration::from_millis(200 << attempt).min(Duration::from_secs(1));
+            let delay = policy.backoff_for(attempt);
+            assert!(delay >= step / 2 && delay <= step);
+        }
+    }
+
+    #[test]
+    fn test_retry_after_is_capped_and_deadline_stops_retries() {
+        let policy = RetryPolicy::default()
+            .backoff(Duration::from_millis(100), Duration::from_secs(1))
+            .jitter(false);
+        let now = Instant::now();
+
+        let delay = |policy: &RetryPolicy, retry_after| {
+            policy.retry_delay(now, 0, ErrorClass::RateLimited, retry_after)
+        };
+        assert_eq!(delay(&policy, Some(Duration::from_secs(3600))), Some(Duration::from_secs(1)));
+        assert_eq!(delay(&policy, None), Some(Duration::from_millis(100)));
+
+        let policy = policy.deadline(Duration::from_millis(500));
+        assert_eq!(delay(&policy, None), Some(Duration::from_millis(100)));
+        assert_eq!(delay(&policy, Some(Duration::from_secs(1))), None);
+        assert!(policy.attempt_timeout(now, Duration::from_secs(30)) <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_budget_limits_retries() {
+        let budget = RetryBudget::new(2, 0.5);
+        assert!(budget.withdraw());
+        assert!(budget.withdraw());
+        assert!(!budget.withdraw());
+
+        budget.deposit();
+        budget.deposit();
+        assert!(budget.withdraw());
+        assert!(!budget.withdraw());
+    }
+}
@@ -0,0 +1,142 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, http::header::AUTHORIZATION, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+use tracing::info;
+
+use super::{
+    pubsub::{Connector, PubSubConnection, PubSubFrontend, RawSubscription},
+    IntoRpcUrl, RpcUrl, Transport,
+};
+use crate::RpcError;
+
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct WsConnector {
+    url: RpcUrl,
+}
+
+struct WsConnection {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+#[async_trait]
+impl Connector for WsConnector {
+    type Connection = WsConnection;
+
+    async fn connect(&self) -> Result<WsConnection, RpcError> {
+        let mut request = self
+            .url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| RpcError::InvalidUrl(e.to_string()))?;
+        if let Some(authorization) = self.url.authorization() {
+            request.headers_mut().insert(AUTHORIZATION, authorization.clone());
+        }
+
+        let (stream, _) = connect_async(request)
+            .await
+            .map_err(|e| RpcError::Connection(format!("WebSocket connect failed: {}", e)))?;
+
+        Ok(WsConnection { stream })
+    }
+}
+
+#[async_trait]
+impl PubSubConnection for WsConnection {
+    async fn send(&mut self, payload: Bytes) -> Result<(), RpcError> {
+        let text = String::from_utf8(payload.to_vec())
+            .map_err(|e| RpcError::Transport(format!("Request is not valid UTF-8: {}", e)))?;
+
+        self.stream
+            .send(Message::text(text))
+            .await
+            .map_err(|e| RpcError::Connection(format!("WebSocket send failed: {}", e)))
+    }
+
+    async fn recv(&mut self) -> Option<Result<Bytes, RpcError>> {
+        loop {
+            match self.stream.next().await? {
+                Ok(message @ (Message::Text(_) | Message::Binary(_))) => {
+                    return Some(Ok(message.into_data()))
+                },
+                Ok(Message::Close(_)) => return None,
+                // Pings are answered by tungstenite itself
+                Ok(_) => continue,
+                Err(e) => {
+                    return Some(Err(RpcError::Connection(format!("WebSocket read failed: {}", e))))
+                },
+            }
+        }
+    }
+}
+
+/// JSON-RPC over a single persistent WebSocket. Concurrent requests are
+/// matched to responses by id and the socket is reopened when it drops.
+#[derive(Debug, Clone)]
+pub struct WsTransport {
+    url: RpcUrl,
+    frontend: Arc<PubSubFrontend>,
+}
+
+impl WsTransport {
+    pub async fn connect(url: impl IntoRpcUrl) -> Result<Self, RpcError> {
+        Self::connect_with_timeout(url, DEFAULT_REQUEST_TIMEOUT).await
+    }
+
+    pub async fn connect_with_timeout(
+        url: impl IntoRpcUrl,
+        request_timeout: Duration,
+    ) -> Result<Self, RpcError> {
+        let url = url.into_rpc_url()?;
+        if url.is_http() {
+            return Err(RpcError::InvalidUrl(format!("Expected a ws(s) url, got {}", url)));
+        }
+        let connector = WsConnector { url: url.clone() };
+        let frontend = PubSubFrontend::connect(connector, request_timeout).await?;
+
+        info!("WebSocket transport connected to {}", url);
+
+        Ok(Self {
+            url,
+            frontend: Arc::new(frontend),
+        })
+    }
+
+    pub fn url(&self) -> &RpcUrl {
+        &self.url
+    }
+}
+
+#[async_trait]
+impl Transport for WsTransport {
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.frontend.request(&request).await.map(Bytes::from)
+    }
+
+    /// Responses are matched by id, requests share the connection
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    fn supports_subscriptions(&self) -> bool {
+        true
+    }
+
+    async fn subscribe(&self, params: Value) -> Result<RawSubscription, RpcError> {
+        self.frontend.subscribe(params.to_string().as_bytes()).await
+    }
+
+    fn endpoint(&self) -> Option<RpcUrl> {
+        Some(self.url.clone())
+    }
+}
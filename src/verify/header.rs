@@ -0,0 +1,221 @@
+use std::ops::RangeInclusive;
+
+use alloy::{
+    hex,
+    primitives::{keccak256, Bytes, B256, U256},
+    rlp::Encodable,
+};
+
+use super::{RlpList, VerificationError};
+use crate::{
+    hyper_rpc::RpcClient,
+    parser::types::{Block, BlockHeader},
+    RpcError,
+};
+
+impl BlockHeader {
+    /// RLP encoding of the header, the preimage of the block hash. The field
+    /// set follows the fork: each fork appends fields, so the encoding stops
+    /// at the last one present (London base fee, Shanghai withdrawals root,
+    /// Cancun blob gas and beacon root, Prague requests hash)
+    pub fn rlp_encode(&self) -> Result<Vec<u8>, VerificationError> {
+        let logs_bloom = self.decode_hex("logsBloom", &self.logs_bloom)?;
+        if logs_bloom.len() != 256 {
+            return Err(self.invalid("logsBloom"));
+        }
+        let extra_data = self.decode_hex("extraData", &self.extra_data)?;
+
+        let difficulty = U256::from(self.difficulty);
+        let nonce = self.nonce.to_be_bytes();
+        let mut fields: Vec<&dyn Encodable> = vec![
+            &self.parent_hash,
+            &self.uncles_hash,
+            &self.author,
+            &self.state_root,
+            &self.transactions_root,
+            &self.receipts_root,
+            &logs_bloom,
+            &difficulty,
+            &self.number,
+            &self.gas_limit,
+            &self.gas_used,
+            &self.timestamp,
+            &extra_data,
+            &self.mix_hash,
+            &nonce,
+        ];
+
+        let fork_fields: [(&'static str, Option<&dyn Encodable>); 6] = [
+            ("baseFeePerGas", self.base_fee_per_gas.as_ref().map(|v| v as &dyn Encodable)),
+            ("withdrawalsRoot", self.withdrawls_root.as_ref().map(|v| v as &dyn Encodable)),
+            ("blobGasUsed", self.blob_gas_used.as_ref().map(|v| v as &dyn Encodable)),
+            ("excessBlobGas", self.excess_blob_gas.as_ref().map(|v| v as &dyn Encodable)),
+            (
+                "parentBeaconBlockRoot",
+                self.parent_beacon_block_root.as_ref().map(|v| v as &dyn Encodable),
+            ),
+            ("requestsHash", self.requests_hash.as_ref().map(|v| v as &dyn Encodable)),
+        ];
+        let present =
+            fork_fields.iter().rposition(|(_, value)| value.is_some()).map_or(0, |i| i + 1);
+        for &(field, value) in &fork_fields[..present] {
+            fields.push(value.ok_or(VerificationError::MissingField {
+                number: self.number,
+                field,
+            })?);
+        }
+
+        Ok(alloy::rlp::encode(RlpList(fields)))
+    }
+
+    pub fn compute_hash(&self) -> Result<B256, VerificationError> {
+        self.rlp_encode().map(keccak256)
+    }
+
+    /// Recomputes the hash from the header fields and checks it against the
+    /// one the node sent, returning it on success
+    pub fn verify_hash(&self) -> Result<B256, VerificationError> {
+        let claimed = self.hash.ok_or(VerificationError::MissingHash {
+            number: self.number,
+        })?;
+
+        let computed = self.compute_hash()?;
+        if computed != claimed {
+            return Err(VerificationError::HashMismatch {
+                number: self.number,
+                claimed,
+                computed,
+            });
+        }
+        Ok(computed)
+    }
+
+    fn decode_hex(&self, field: &'static str, value: &str) -> Result<Bytes, VerificationError> {
+        hex::decode(value).map(Bytes::from).map_err(|_| self.invalid(field))
+    }
+
+    fn invalid(&self, field: &'static str) -> VerificationError {
+        VerificationError::InvalidField {
+            number: self.number,
+            field,
+        }
+    }
+}
+
+impl Block {
+    /// The header part of the block
+    pub fn header(&self) -> BlockHeader {
+        BlockHeader {
+            parent_hash: self.parent_hash,
+            hash: self.hash,
+            uncles_hash: self.uncles_hash,
+            author: self.author,
+            state_root: self.state_root,
+            transactions_root: self.transactions_root,
+            receipts_root: self.receipts_root,
+            logs_bloom: self.logs_bloom.clone(),
+            difficulty: self.difficulty.to::<u64>(),
+            prev_randao: self.prev_randao,
+            number: self.number.to::<u64>(),
+            gas_limit: self.gas_limit,
+            gas_used: self.gas_used,
+            timestamp: self.timestamp.to::<u64>(),
+            extra_data: self.extra_data.clone(),
+            mix_hash: self.mix_hash,
+            nonce: self.nonce.to::<u64>(),
+            base_fee_per_gas: self.base_fee_per_gas,
+            withdrawls_root: self.withdrawals_root,
+            blob_gas_used: self.blob_gas_used,
+            excess_blob_gas: self.excess_blob_gas,
+            parent_beacon_block_root: self.parent_beacon_block_root,
+            blobs_hash: None,
+            requests_hash: self.requests_hash,
+        }
+    }
+
+    pub fn verify_hash(&self) -> Result<B256, VerificationError> {
+        self.header().verify_hash()
+    }
+}
+
+/// Contiguous run of headers, each verified against its own hash and linked
+/// to the one before it through `parent_hash`
+#[derive(Debug, Clone, Default)]
+pub struct HeaderChain {
+    headers: Vec<BlockHeader>,
+}
+
+impl HeaderChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verifies `headers`, which must be in ascending block order
+    pub fn from_headers(headers: Vec<BlockHeader>) -> Result<Self, VerificationError> {
+        let mut chain = Self::new();
+        for header in headers {
+            chain.push(header)?;
+        }
+        Ok(chain)
+    }
+
+    /// Fetches `range` in one batch and verifies it. The headers go into the
+    /// client's header store only once the whole range checks out
+    pub async fn fetch(client: &RpcClient, range: RangeInclusive<u64>) -> Result<Self, RpcError> {
+        let numbers: Vec<u64> = range.collect();
+        let blocks = client.fetch_blocks_by_numbers(numbers.clone(), false).await?;
+
+        let mut chain = Self::new();
+        for (number, block) in numbers.into_iter().zip(blocks) {
+            let block =
+                block.ok_or_else(|| RpcError::Response(format!("Block {} not found", number)))?;
+            chain.push(block.header())?;
+        }
+        for header in &chain.headers {
+            client.record_header(header.clone());
+        }
+        Ok(chain)
+    }
+
+    /// Appends `header` after checking its hash and that it extends the tip
+    pub fn push(&mut self, header: BlockHeader) -> Result<(), VerificationError> {
+        header.verify_hash()?;
+
+        if let Some(tip) = self.headers.last() {
+            if header.number != tip.number + 1 {
+                return Err(VerificationError::NotContiguous {
+                    number: header.number,
+                    expected: tip.number + 1,
+                });
+            }
+            // The tip's hash was verified when it was pushed
+            let expected = tip.hash.unwrap_or_default();
+            if header.parent_hash != expected {
+                return Err(VerificationError::BrokenLink {
+                    number: header.number,
+                    parent_hash: header.parent_hash,
+                    expected,
+                });
+            }
+        }
+
+        self.headers.push(header);
+        Ok(())
+    }
+
+    pub fn tip(&self) -> Option<&BlockHeader> {
+        self.headers.last()
+    }
+
+    pub fn headers(&self) -> &[BlockHeader] {
+        &self.headers
+    }
+
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+}
@@ -0,0 +1,88 @@
+//! Checks that let a client trust responses without trusting the provider
+
+use alloy::{
+    primitives::{Address, B256},
+    rlp::{length_of_length, BufMut, Encodable, Header},
+};
+
+pub mod header;
+pub mod proof;
+pub mod trie;
+
+pub use header::HeaderChain;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum VerificationError {
+    #[error("Block {number} has no hash to verify against")]
+    MissingHash { number: u64 },
+    /// A field of a later fork is set while one of an earlier fork is not
+    #[error("Block {number} is missing {field} for its fork")]
+    MissingField { number: u64, field: &'static str },
+    #[error("Block {number} has a malformed {field}")]
+    InvalidField { number: u64, field: &'static str },
+    #[error("Block {number} hashes to {computed}, node claims {claimed}")]
+    HashMismatch {
+        number: u64,
+        claimed: B256,
+        computed: B256,
+    },
+    /// `parent_hash` of block `number` is not the hash of the block before it
+    #[error("Block {number} links to parent {parent_hash}, expected {expected}")]
+    BrokenLink {
+        number: u64,
+        parent_hash: B256,
+        expected: B256,
+    },
+    #[error("Expected block {expected}, got {number}")]
+    NotContiguous { number: u64, expected: u64 },
+    /// The block was fetched with transaction hashes only
+    #[error("Block {number} has no full transactions to verify")]
+    MissingTransactions { number: u64 },
+    #[error("Transaction {hash} has a malformed {field}")]
+    InvalidTransaction { hash: B256, field: &'static str },
+    #[error("Receipt of {hash} has a malformed {field}")]
+    InvalidReceipt { hash: B256, field: &'static str },
+    /// `root` is the header field that did not match, e.g. `transactionsRoot`
+    #[error("Block {number} {root} is {claimed}, computed {computed}")]
+    RootMismatch {
+        number: u64,
+        root: &'static str,
+        claimed: B256,
+        computed: B256,
+    },
+    /// The account proof, or the proof of `slot` when set, does not lead to
+    /// the reported value
+    #[error("Invalid proof for {address} (slot {slot:?}): {reason}")]
+    InvalidProof {
+        address: Address,
+        slot: Option<B256>,
+        reason: String,
+    },
+}
+
+/// RLP list of fields of different types
+pub(crate) struct RlpList<'a>(pub Vec<&'a dyn Encodable>);
+
+impl RlpList<'_> {
+    fn payload_length(&self) -> usize {
+        self.0.iter().map(|field| field.length()).sum()
+    }
+}
+
+impl Encodable for RlpList<'_> {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header {
+            list: true,
+            payload_length: self.payload_length(),
+        }
+        .encode(out);
+        for field in &self.0 {
+            field.encode(out);
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + length_of_length(payload_length)
+    }
+}
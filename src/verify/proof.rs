@@ -0,0 +1,69 @@
+use alloy::{
+    primitives::{keccak256, B256, U256},
+    rlp,
+};
+use alloy_trie::{proof::verify_proof, Nibbles, TrieAccount, EMPTY_ROOT_HASH, KECCAK_EMPTY};
+
+use super::VerificationError;
+use crate::parser::types::{AccountProof, BlockHeader, StorageProof};
+
+impl AccountProof {
+    /// Checks the account against `state_root`, then every storage slot
+    /// against the proven `storage_hash`. An account or slot that does not
+    /// exist must come with a proof of its absence
+    pub fn verify_against(&self, state_root: B256) -> Result<(), VerificationError> {
+        let account = TrieAccount {
+            nonce: self.nonce.to::<u64>(),
+            balance: self.balance,
+            storage_root: self.storage_hash,
+            code_hash: self.code_hash,
+        };
+        // Nodes report absent accounts with zeroed or empty hashes
+        let empty = account.nonce == 0
+            && account.balance.is_zero()
+            && (account.code_hash == KECCAK_EMPTY || account.code_hash.is_zero())
+            && (account.storage_root == EMPTY_ROOT_HASH || account.storage_root.is_zero());
+        let expected = (!empty).then(|| rlp::encode(account));
+
+        verify_proof(
+            state_root,
+            Nibbles::unpack(keccak256(self.address)),
+            expected,
+            &self.account_proof,
+        )
+        .map_err(|e| self.invalid(None, e))?;
+
+        for slot in &self.storage_proof {
+            slot.verify_against(self.storage_hash)
+                .map_err(|e| self.invalid(Some(slot.key), e))?;
+        }
+        Ok(())
+    }
+
+    /// Same as `verify_against` with the state root of a trusted header
+    pub fn verify_against_header(&self, header: &BlockHeader) -> Result<(), VerificationError> {
+        self.verify_against(header.state_root)
+    }
+
+    /// Proven value of `key`, zero when proven absent and `None` when the
+    /// proof does not cover it
+    pub fn storage_value(&self, key: B256) -> Option<U256> {
+        self.storage_proof.iter().find(|slot| slot.key == key).map(|slot| slot.value)
+    }
+
+    fn invalid(&self, slot: Option<B256>, error: impl std::fmt::Display) -> VerificationError {
+        VerificationError::InvalidProof {
+            address: self.address,
+            slot,
+            reason: error.to_string(),
+        }
+    }
+}
+
+impl StorageProof {
+    fn verify_against(&self, storage_root: B256) -> Result<(), String> {
+        let expected = (!self.value.is_zero()).then(|| rlp::encode(self.value));
+        verify_proof(storage_root, Nibbles::unpack(keccak256(self.key)), expected, &self.proof)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,315 @@
+use alloy::{
+    hex,
+    primitives::{Bytes, B256},
+    rlp::{BufMut, Encodable},
+};
+use alloy_trie::root::ordered_trie_root_with_encoder;
+
+use super::{RlpList, VerificationError};
+use crate::parser::types::{
+    AccessListItem, Block, BlockTransactions, Execution, ExecutionPayload, Receipt,
+    SignedAuthorization, TransactionTx, TypedTransaction, Withdrawal,
+};
+
+impl Encodable for AccessListItem {
+    fn encode(&self, out: &mut dyn BufMut) {
+        RlpList(vec![&self.address, &self.storage_keys]).encode(out)
+    }
+
+    fn length(&self) -> usize {
+        RlpList(vec![&self.address, &self.storage_keys]).length()
+    }
+}
+
+impl SignedAuthorization {
+    fn fields(&self) -> RlpList<'_> {
+        RlpList(vec![
+            &self.chain_id,
+            &self.address,
+            &self.nonce,
+            &self.y_parity,
+            &self.r,
+            &self.s,
+        ])
+    }
+}
+
+impl Encodable for SignedAuthorization {
+    fn encode(&self, out: &mut dyn BufMut) {
+        self.fields().encode(out)
+    }
+
+    fn length(&self) -> usize {
+        self.fields().length()
+    }
+}
+
+/// EIP-4895, the amount is in gwei
+impl Encodable for Withdrawal {
+    fn encode(&self, out: &mut dyn BufMut) {
+        RlpList(vec![
+            &self.index,
+            &self.validator_index,
+            &self.address,
+            &self.amount,
+        ])
+        .encode(out)
+    }
+
+    fn length(&self) -> usize {
+        RlpList(vec![
+            &self.index,
+            &self.validator_index,
+            &self.address,
+            &self.amount,
+        ])
+        .length()
+    }
+}
+
+impl TransactionTx {
+    /// EIP-2718 encoding, the type byte followed by the RLP payload for typed
+    /// transactions and the bare RLP list for legacy ones
+    pub fn encoded_2718(&self) -> Result<Vec<u8>, VerificationError> {
+        let invalid = |field| VerificationError::InvalidTransaction {
+            hash: self.hash,
+            field,
+        };
+        let input = Bytes::from(hex::decode(&self.input).map_err(|_| invalid("input"))?);
+        // Creations encode the recipient as the empty string
+        let to: &dyn Encodable = match &self.to {
+            Some(to) => to,
+            None => &[0u8; 0],
+        };
+        let gas_price = self.gas_price.ok_or_else(|| invalid("gasPrice"));
+
+        let mut out = Vec::new();
+        match &self.transaction {
+            TypedTransaction::Legacy { .. } => {
+                let v = self.v.ok_or_else(|| invalid("v"))?;
+                RlpList(vec![
+                    &self.nonce,
+                    &gas_price?,
+                    &self.gas,
+                    to,
+                    &self.value,
+                    &input,
+                    &v,
+                    &self.r,
+                    &self.s,
+                ])
+                .encode(&mut out);
+            },
+            TypedTransaction::Eip2930 {
+                chain_id,
+                access_list,
+                y_parity,
+            } => {
+                out.put_u8(1);
+                RlpList(vec![
+                    chain_id,
+                    &self.nonce,
+                    &gas_price?,
+                    &self.gas,
+                    to,
+                    &self.value,
+                    &input,
+                    access_list,
+                    y_parity,
+                    &self.r,
+                    &self.s,
+                ])
+                .encode(&mut out);
+            },
+            TypedTransaction::Eip1559 {
+                chain_id,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                access_list,
+                y_parity,
+            } => {
+                out.put_u8(2);
+                RlpList(vec![
+                    chain_id,
+                    &self.nonce,
+                    max_priority_fee_per_gas,
+                    max_fee_per_gas,
+                    &self.gas,
+                    to,
+                    &self.value,
+                    &input,
+                    access_list,
+                    y_parity,
+                    &self.r,
+                    &self.s,
+                ])
+                .encode(&mut out);
+            },
+            TypedTransaction::Eip4844 {
+                chain_id,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                max_fee_per_blob_gas,
+                blob_versioned_hashes,
+                access_list,
+                y_parity,
+            } => {
+                out.put_u8(3);
+                RlpList(vec![
+                    chain_id,
+                    &self.nonce,
+                    max_priority_fee_per_gas,
+                    max_fee_per_gas,
+                    &self.gas,
+                    to,
+                    &self.value,
+                    &input,
+                    access_list,
+                    max_fee_per_blob_gas,
+                    blob_versioned_hashes,
+                    y_parity,
+                    &self.r,
+                    &self.s,
+                ])
+                .encode(&mut out);
+            },
+            TypedTransaction::Eip7702 {
+                chain_id,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                access_list,
+                authorization_list,
+                y_parity,
+            } => {
+                out.put_u8(4);
+                RlpList(vec![
+                    chain_id,
+                    &self.nonce,
+                    max_priority_fee_per_gas,
+                    max_fee_per_gas,
+                    &self.gas,
+                    to,
+                    &self.value,
+                    &input,
+                    access_list,
+                    authorization_list,
+                    y_parity,
+                    &self.r,
+                    &self.s,
+                ])
+                .encode(&mut out);
+            },
+        }
+
+        Ok(out)
+    }
+}
+
+impl Receipt {
+    /// EIP-2718 encoding of the consensus part of the receipt: outcome,
+    /// cumulative gas, bloom and logs
+    pub fn encoded_2718(&self) -> Result<Vec<u8>, VerificationError> {
+        let invalid = |field| VerificationError::InvalidReceipt {
+            hash: self.transaction_hash,
+            field,
+        };
+        let decode =
+            |field, value: &str| hex::decode(value).map(Bytes::from).map_err(|_| invalid(field));
+
+        // Byzantium replaced the post state root with the status code
+        let outcome: &dyn Encodable = match (&self.status, &self.root) {
+            (Some(status), _) => status,
+            (None, Some(root)) => root,
+            (None, None) => return Err(invalid("status")),
+        };
+        let logs_bloom = decode("logsBloom", &self.logs_bloom)?;
+        if logs_bloom.len() != 256 {
+            return Err(invalid("logsBloom"));
+        }
+        let data = self
+            .logs
+            .iter()
+            .map(|log| decode("logs", &log.data))
+            .collect::<Result<Vec<_>, _>>()?;
+        let logs: Vec<RlpList<'_>> = self
+            .logs
+            .iter()
+            .zip(&data)
+            .map(|(log, data)| RlpList(vec![&log.address, &log.topics, data]))
+            .collect();
+
+        let mut out = Vec::new();
+        let tx_type = self.transaction_type.to::<u8>();
+        if tx_type != 0 {
+            out.put_u8(tx_type);
+        }
+        RlpList(vec![outcome, &self.cumulative_gas_used, &logs_bloom, &logs]).encode(&mut out);
+        Ok(out)
+    }
+}
+
+/// Root of the ordered trie keyed by the RLP encoded index of each item
+fn ordered_root(encoded: &[Vec<u8>]) -> B256 {
+    ordered_trie_root_with_encoder(encoded, |item, out| out.extend_from_slice(item))
+}
+
+impl ExecutionPayload {
+    /// The payload header, with the transactions and withdrawals roots built
+    /// from the body
+    pub fn header(&self) -> Execution {
+        let transactions: Vec<Vec<u8>> = self.transactions.iter().map(|tx| tx.to_vec()).collect();
+        let withdrawals: Vec<Vec<u8>> = self.withdrawals.iter().map(alloy::rlp::encode).collect();
+        Execution {
+            transactions_root: ordered_root(&transactions),
+            withdrawals_root: ordered_root(&withdrawals),
+            ..self.execution.clone()
+        }
+    }
+}
+
+impl Block {
+    /// Rebuilds the transactions trie and checks it against
+    /// `transactions_root`, returning the root on success. Needs the block to
+    /// be fetched with full transactions
+    pub fn verify_transactions_root(&self) -> Result<B256, VerificationError> {
+        let transactions = match &self.transactions {
+            BlockTransactions::Full(transactions) => transactions.as_slice(),
+            BlockTransactions::Hashes(hashes) if hashes.is_empty() => &[],
+            BlockTransactions::Hashes(_) => {
+                return Err(VerificationError::MissingTransactions {
+                    number: self.number.to::<u64>(),
+                })
+            },
+        };
+
+        let encoded = transactions
+            .iter()
+            .map(TransactionTx::encoded_2718)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.check_root("transactionsRoot", self.transactions_root, ordered_root(&encoded))
+    }
+
+    /// Rebuilds the receipts trie from `receipts`, in transaction order, and
+    /// checks it against `receipts_root`, returning the root on success
+    pub fn verify_receipts_root(&self, receipts: &[Receipt]) -> Result<B256, VerificationError> {
+        let encoded = receipts.iter().map(Receipt::encoded_2718).collect::<Result<Vec<_>, _>>()?;
+        self.check_root("receiptsRoot", self.receipts_root, ordered_root(&encoded))
+    }
+
+    fn check_root(
+        &self,
+        root: &'static str,
+        claimed: B256,
+        computed: B256,
+    ) -> Result<B256, VerificationError> {
+        if claimed != computed {
+            return Err(VerificationError::RootMismatch {
+                number: self.number.to::<u64>(),
+                root,
+                claimed,
+                computed,
+            });
+        }
+        Ok(computed)
+    }
+}
@@ -0,0 +1,154 @@
+//! Mock nodes shared by the integration tests. Each binds a local port, serves
+//! until the test ends and hands back the url to reach it
+
+// Every test binary uses a different subset
+#![allow(dead_code)]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use futures::{SinkExt, StreamExt};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    header::HeaderMap,
+    server::conn::http1,
+    service::service_fn,
+    Request, Response,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+/// A request as the HTTP mock received it
+pub struct Received {
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl Received {
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap()
+    }
+}
+
+/// `200 OK` carrying `body`
+pub fn ok(body: impl Into<Bytes>) -> Response<Full<Bytes>> {
+    Response::new(Full::new(body.into()))
+}
+
+/// JSON-RPC success response to `request`
+pub fn result(request: &Value, result: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": request["id"], "result": result})
+}
+
+/// Serves HTTP, answering every request with whatever `respond` makes of it
+pub async fn serve_http<F>(respond: F) -> String
+where
+    F: Fn(Received) -> Response<Full<Bytes>> + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let respond = Arc::new(respond);
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let respond = respond.clone();
+            let service = service_fn(move |request: Request<Incoming>| {
+                let respond = respond.clone();
+                async move {
+                    let headers = request.headers().clone();
+                    let body = request.into_body().collect().await?.to_bytes();
+                    Ok::<_, hyper::Error>(respond(Received { headers, body }))
+                }
+            });
+            tokio::spawn(http1::Builder::new().serve_connection(TokioIo::new(stream), service));
+        }
+    });
+
+    url
+}
+
+/// Serves JSON-RPC over HTTP, `answer` gives the result of each request.
+/// Batches are answered request by request
+pub async fn serve_rpc<F>(answer: F) -> String
+where
+    F: Fn(&Value) -> Value + Send + Sync + 'static,
+{
+    serve_http(move |received| {
+        let request = received.json();
+        let response = match request.as_array() {
+            Some(batch) => batch.iter().map(|request| result(request, answer(request))).collect(),
+            None => result(&request, answer(&request)),
+        };
+        ok(response.to_string())
+    })
+    .await
+}
+
+/// What the WebSocket mock sends back for one request
+#[derive(Default)]
+pub struct WsReply {
+    pub messages: Vec<Value>,
+    /// Hang up once the messages are sent
+    pub close: bool,
+}
+
+impl WsReply {
+    pub fn send(messages: Vec<Value>) -> Self {
+        Self {
+            messages,
+            close: false,
+        }
+    }
+
+    pub fn close(mut self) -> Self {
+        self.close = true;
+        self
+    }
+}
+
+/// Serves JSON-RPC over WebSocket. `respond` gets the number of the
+/// connection, counting from 0, the number of the request on it and the
+/// request itself
+pub async fn serve_ws<F>(respond: F) -> String
+where
+    F: Fn(usize, usize, Value) -> WsReply + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let respond = Arc::new(respond);
+    let connections = AtomicUsize::new(0);
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let connection = connections.fetch_add(1, Ordering::SeqCst);
+            let respond = respond.clone();
+            tokio::spawn(async move {
+                let mut ws = accept_async(stream).await.unwrap();
+                let mut requests = 0;
+                while let Some(Ok(message)) = ws.next().await {
+                    if !message.is_text() {
+                        continue;
+                    }
+                    let request = serde_json::from_slice(&message.into_data()).unwrap();
+                    let reply = respond(connection, requests, request);
+                    requests += 1;
+
+                    for message in reply.messages {
+                        ws.send(Message::text(message.to_string())).await.unwrap();
+                    }
+                    if reply.close {
+                        let _ = ws.close(None).await;
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    url
+}
@@ -0,0 +1,238 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use alloy::primitives::{address, b256, bytes, B256, B64, U256, U64};
+use alloy_trie::EMPTY_ROOT_HASH;
+use common::{ok, serve_http};
+use hyper::header::AUTHORIZATION;
+use palantiri::{
+    engine::{EngineClient, CAPABILITIES},
+    hyper_rpc::RpcClient,
+    parser::types::{
+        ExecutionPayload, ForkchoiceState, PayloadAttributes, PayloadValidity, Withdrawal,
+    },
+    transport::{auth::JwtAuth, http::TransportBuilder},
+    RpcError,
+};
+use serde_json::{json, Value};
+
+const PAYLOAD_ID: &str = "0x0000000000000001";
+const BLOCK_HASH: B256 = b256!("b084a4e1c5b0c0d4a4d5c7fc9ff4c7fdb1d2a5e8a0e8b1d0c9d6a3b5e6f7a8b9");
+
+fn payload_json() -> Value {
+    json!({
+        "parentHash": format!("0x{}", "11".repeat(32)),
+        "feeRecipient": "0x2222222222222222222222222222222222222222",
+        "stateRoot": format!("0x{}", "33".repeat(32)),
+        "receiptsRoot": format!("0x{}", "44".repeat(32)),
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "prevRandao": format!("0x{}", "55".repeat(32)),
+        "blockNumber": "0x10",
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x5208",
+        "timestamp": "0x6553f100",
+        "extraData": "0x",
+        "baseFeePerGas": "0x7",
+        "blockHash": BLOCK_HASH,
+        "transactions": ["0x02f86b0180843b9aca00"],
+        "withdrawals": [{
+            "index": "0x1",
+            "validatorIndex": "0x2",
+            "address": "0x3333333333333333333333333333333333333333",
+            "amount": "0x4"
+        }],
+        "blobGasUsed": "0x20000",
+        "excessBlobGas": "0x0"
+    })
+}
+
+/// Engine API mock: answers by method and keeps the requests and the
+/// `Authorization` header each came with
+async fn spawn_engine_server() -> (String, Arc<Mutex<Vec<(Value, String)>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+
+    let url = serve_http(move |received| {
+        let authorization = received
+            .headers
+            .get(AUTHORIZATION)
+            .map_or(String::new(), |value| value.to_str().unwrap().to_string());
+        let request = received.json();
+        let response = answer(&request);
+        recorded.lock().unwrap().push((request, authorization));
+        ok(response.to_string())
+    })
+    .await;
+
+    (url, requests)
+}
+
+fn answer(request: &Value) -> Value {
+    let params = &request["params"];
+    let result = match request["method"].as_str().unwrap() {
+        "engine_exchangeCapabilities" => params[0].clone(),
+        "engine_forkchoiceUpdatedV3" => json!({
+            "payloadStatus": {
+                "status": "VALID",
+                "latestValidHash": params[0]["headBlockHash"],
+                "validationError": null
+            },
+            // Building only starts with payload attributes
+            "payloadId": (!params[1].is_null()).then_some(PAYLOAD_ID)
+        }),
+        "engine_newPayloadV3" | "engine_newPayloadV4" => {
+            match params[0]["blockHash"] == json!(BLOCK_HASH) {
+                true => json!({
+                    "status": "VALID",
+                    "latestValidHash": BLOCK_HASH,
+                    "validationError": null
+                }),
+                false => json!({
+                    "status": "INVALID_BLOCK_HASH",
+                    "latestValidHash": null,
+                    "validationError": "blockhash mismatch"
+                }),
+            }
+        },
+        method @ ("engine_getPayloadV3" | "engine_getPayloadV4") => {
+            if params[0] != json!(PAYLOAD_ID) {
+                return json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": {"code": -38001, "message": "Unknown payload"}
+                });
+            }
+            let mut envelope = json!({
+                "executionPayload": payload_json(),
+                "blockValue": "0x1bc16d674ec80000",
+                "blobsBundle": {
+                    "commitments": ["0xc0"],
+                    "proofs": ["0xc1"],
+                    "blobs": ["0xc2"]
+                },
+                "shouldOverrideBuilder": false
+            });
+            if method == "engine_getPayloadV4" {
+                envelope["executionRequests"] = json!(["0x01aa"]);
+            }
+            envelope
+        },
+        "engine_getBlobsV1" => json!([{"blob": "0xb10b", "proof": "0xbeef"}, null]),
+        _ => Value::Null,
+    };
+    common::result(request, result)
+}
+
+fn engine_client(url: &str) -> EngineClient {
+    let jwt = JwtAuth::from_hex(&"5e".repeat(32)).unwrap();
+    EngineClient::new(RpcClient::new(
+        TransportBuilder::new(url).auth(jwt).build_http_hyper().unwrap(),
+    ))
+}
+
+#[tokio::test]
+async fn test_engine_block_production_round() {
+    let (url, requests) = spawn_engine_server().await;
+    let engine = engine_client(&url);
+
+    let capabilities = engine.exchange_capabilities(&CAPABILITIES).await.unwrap();
+    assert_eq!(capabilities, CAPABILITIES);
+
+    let state = ForkchoiceState {
+        head_block_hash: B256::repeat_byte(0xaa),
+        safe_block_hash: B256::repeat_byte(0xbb),
+        finalized_block_hash: B256::repeat_byte(0xcc),
+    };
+    let updated = engine.forkchoice_updated_v3(&state, None).await.unwrap();
+    assert_eq!(updated.payload_status.status, PayloadValidity::Valid);
+    assert_eq!(updated.payload_status.latest_valid_hash, Some(state.head_block_hash));
+    assert_eq!(updated.payload_id, None);
+
+    let attributes = PayloadAttributes {
+        timestamp: U64::from(0x6553f100),
+        suggested_fee_recipient: address!("2222222222222222222222222222222222222222"),
+        ..Default::default()
+    };
+    let updated = engine.forkchoice_updated_v3(&state, Some(&attributes)).await.unwrap();
+    let id = updated.payload_id.unwrap();
+    assert_eq!(id, B64::from(1u64.to_be_bytes()));
+
+    let envelope = engine.get_payload_v3(id).await.unwrap();
+    let payload = envelope.execution_payload;
+    assert_eq!(payload.execution.block_hash, BLOCK_HASH);
+    assert_eq!(payload.execution.block_number, U64::from(0x10));
+    assert_eq!(payload.execution.blob_gas_used, U64::from(0x20000));
+    assert_eq!(payload.transactions, vec![bytes!("02f86b0180843b9aca00")]);
+    assert_eq!(
+        payload.withdrawals,
+        vec![Withdrawal {
+            address: address!("3333333333333333333333333333333333333333"),
+            amount: U256::from(4),
+            index: U64::from(1),
+            validator_index: U64::from(2),
+        }]
+    );
+    assert_eq!(envelope.block_value, U256::from(2_000_000_000_000_000_000u64));
+    assert_eq!(envelope.blobs_bundle.blobs, vec![bytes!("c2")]);
+    assert_eq!(envelope.execution_requests, None);
+
+    let envelope = engine.get_payload_v4(id).await.unwrap();
+    assert_eq!(envelope.execution_requests, Some(vec![bytes!("01aa")]));
+
+    let root = B256::repeat_byte(0xdd);
+    let status = engine.new_payload_v3(&payload, &[], root).await.unwrap();
+    assert_eq!(status.status, PayloadValidity::Valid);
+    let status = engine.new_payload_v4(&payload, &[], root, &[bytes!("01aa")]).await.unwrap();
+    assert_eq!(status.latest_valid_hash, Some(BLOCK_HASH));
+
+    let mut tampered = payload.clone();
+    tampered.execution.block_hash = B256::ZERO;
+    let status = engine.new_payload_v3(&tampered, &[], root).await.unwrap();
+    assert_eq!(status.status, PayloadValidity::InvalidBlockHash);
+    assert_eq!(status.validation_error.as_deref(), Some("blockhash mismatch"));
+
+    let requests = requests.lock().unwrap();
+    // The payload goes back in the shape it came in
+    let (new_payload, _) = &requests[5];
+    assert_eq!(new_payload["method"], "engine_newPayloadV3");
+    assert_eq!(new_payload["params"], json!([payload_json(), [], root]));
+    assert_eq!(requests[6].0["params"][3], json!(["0x01aa"]));
+    assert_eq!(requests[2].0["params"][1]["parentBeaconBlockRoot"], json!(B256::ZERO));
+    assert!(requests.iter().all(|(_, authorization)| authorization.starts_with("Bearer ey")));
+}
+
+#[tokio::test]
+async fn test_engine_blobs_and_errors() {
+    let (url, _) = spawn_engine_server().await;
+    let engine = engine_client(&url);
+
+    let blobs = engine
+        .get_blobs_v1(&[B256::repeat_byte(1), B256::repeat_byte(2)])
+        .await
+        .unwrap();
+    assert_eq!(blobs.len(), 2);
+    assert_eq!(blobs[0].as_ref().unwrap().blob, bytes!("b10b"));
+    assert_eq!(blobs[0].as_ref().unwrap().proof, bytes!("beef"));
+    assert!(blobs[1].is_none());
+
+    let unknown = engine.get_payload_v3(B64::repeat_byte(9)).await;
+    assert!(matches!(unknown, Err(RpcError::JsonRpc { code: -38001, .. })));
+}
+
+#[test]
+fn test_payload_header_roots() {
+    let mut payload: ExecutionPayload = serde_json::from_value(payload_json()).unwrap();
+    payload.transactions.clear();
+    payload.withdrawals.clear();
+
+    let header = payload.header();
+    assert_eq!(header.transactions_root, EMPTY_ROOT_HASH);
+    assert_eq!(header.withdrawals_root, EMPTY_ROOT_HASH);
+    assert_eq!(header.block_hash, BLOCK_HASH);
+
+    // Roots are not part of the Engine API payload
+    let json = serde_json::to_value(&payload).unwrap();
+    assert!(json.get("transactionsRoot").is_none());
+    assert_eq!(json["blobGasUsed"], "0x20000");
+}
@@ -1,9 +1,11 @@
-use alloy::primitives::{B256, U256, U64};
+use alloy::primitives::{Address, B256, U256, U64};
+use palantiri::parser::types::{BlockTransactions, TransactionTx, TypedTransaction};
 use palantiri::parser::{
-    block_parser::parse_block,
-    lib::{hex_to_b256, hex_to_u256, hex_to_u64},
-    log_parser::parse_logs,
-    parser_for_small_response::{Generic, RawFee},
+    block_parser::{parse_block, parse_header},
+    lib::{hex_to_b256, hex_to_u256, hex_to_u64, quantity_to_u256, quantity_to_u64},
+    log_parser::{parse_log, parse_logs},
+    parser_for_small_response::{parse_fee_history, Generic, RawFee},
+    receipt_parser::{parse_block_receipts, parse_receipt},
     tx_parser::parse_transaction,
 };
 
@@ -19,6 +21,8 @@ fn test_hex_helpers() {
     assert_eq!(hex_to_u64(b"0x0"), U64::from(0));
     assert_eq!(hex_to_u64(b"0x1a"), U64::from(0x1a));
     assert_eq!(hex_to_u64(b"1a"), U64::from(0x1a));
+    assert_eq!(hex_to_u64(b"0x1506a1f"), U64::from(0x1506a1f));
+    assert_eq!(hex_to_u64(b"0x10000000000000001"), U64::from(1));
 
     let v = hex_to_u256(b"0x01");
     assert_eq!(v, U256::from(1u64));
@@ -53,7 +57,7 @@ fn test_parse_transaction_basic() {
     let tx = parse_transaction(json.as_bytes()).expect("tx should parse");
     assert_eq!(tx.block_number.unwrap(), U64::from(1));
     assert_eq!(tx.gas, U256::from(0x5208u64));
-    assert_eq!(tx.gas_price, U64::from(1_000_000_000));
+    assert_eq!(tx.gas_price, Some(U256::from(1_000_000_000u64)));
 }
 
 #[test]
@@ -160,3 +164,355 @@ fn test_raw_fee_parsing() {
     assert!(fee.reward.len() >= 2);
     assert_eq!(&json[fee.base_fee_per_gas[0].0..fee.base_fee_per_gas[0].1], b"0x5");
 }
+
+#[test]
+fn test_parse_fee_history() {
+    let json = br#"{"jsonrpc":"2.0","id":1,"result":{"oldestBlock":"0x1506a1c","reward":[["0x3b9aca00","0x77359400"],["0x1","0x2"]],"baseFeePerGas":["0x5","0x6","0x7"],"gasUsedRatio":[0.5,0.25],"baseFeePerBlobGas":["0x1","0x1","0x2"],"blobGasUsedRatio":[1,0]}}"#;
+
+    let fee = parse_fee_history(json).expect("fee history should parse");
+    assert_eq!(fee.oldest_block, U64::from(0x1506a1c));
+    assert_eq!(fee.base_fee_per_gas, vec![U256::from(5), U256::from(6), U256::from(7)]);
+    assert_eq!(fee.base_fee_per_blob_gas[2], U256::from(2));
+    assert_eq!(fee.gas_used_ratio, vec![0.5, 0.25]);
+    assert_eq!(fee.blob_gas_used_ratio, vec![1.0, 0.0]);
+    assert_eq!(fee.reward.len(), 2);
+    assert_eq!(fee.reward[0], vec![U256::from(1_000_000_000u64), U256::from(2_000_000_000u64)]);
+}
+
+#[test]
+fn test_parse_fee_history_before_cancun() {
+    // No blob fields, and no reward without percentiles
+    let json = br#"{"jsonrpc":"2.0","id":1,"result":{"oldestBlock":"0x10","baseFeePerGas":["0x5","0x6"],"gasUsedRatio":[0.9]}}"#;
+
+    let fee = parse_fee_history(json).expect("fee history should parse");
+    assert_eq!(fee.oldest_block, U64::from(0x10));
+    assert!(fee.base_fee_per_blob_gas.is_empty());
+    assert!(fee.blob_gas_used_ratio.is_empty());
+    assert!(fee.reward.is_empty());
+    assert_eq!(fee.gas_used_ratio, vec![0.9]);
+
+    assert!(parse_fee_history(br#"{"jsonrpc":"2.0","id":1,"result":null}"#).is_none());
+}
+
+#[test]
+fn test_hex_quantities_with_odd_digit_count() {
+    assert_eq!(quantity_to_u64(b"0x1"), U64::from(1));
+    assert_eq!(quantity_to_u64(b"0x1506a1f"), U64::from(0x1506a1f));
+    assert_eq!(quantity_to_u256(b"0x3b9aca0"), U256::from(0x3b9aca0u64));
+}
+
+#[test]
+fn test_parse_log_notification_with_two_topics() {
+    let json = br#"{"address":"0x1111111111111111111111111111111111111111","topics":["0x0000000000000000000000000000000000000000000000000000000000000001","0x0000000000000000000000000000000000000000000000000000000000000002"],"data":"0x","blockNumber":"0x1506a1f","transactionHash":"0x00000000000000000000000000000000000000000000000000000000000000bb","transactionIndex":"0x0","blockHash":"0x00000000000000000000000000000000000000000000000000000000000000aa","logIndex":"0x3","removed":true}"#;
+
+    let log = parse_log(json).expect("log should parse");
+    assert_eq!(log.address, Address::repeat_byte(0x11));
+    assert_eq!(log.topics.len(), 2);
+    assert_eq!(log.topics[1], B256::with_last_byte(2));
+    assert_eq!(log.block_number.unwrap(), U64::from(0x1506a1f));
+    assert_eq!(log.removed, Some(true));
+}
+
+#[test]
+fn test_parse_header_notification() {
+    let json = format!(
+        r#"{{"parentHash":"0x{}","sha3Uncles":"0x{}","miner":"0x{}","stateRoot":"0x{}","transactionsRoot":"0x{}","receiptsRoot":"0x{}","logsBloom":"0x{}","difficulty":"0x0","number":"0x1506a1f","gasLimit":"0x1c9c380","gasUsed":"0x5208","timestamp":"0x6553f100","extraData":"0x","mixHash":"0x{}","nonce":"0x0000000000000000","baseFeePerGas":"0x7","hash":"0x{}"}}"#,
+        "ab".repeat(32),
+        "ac".repeat(32),
+        "12".repeat(20),
+        "ad".repeat(32),
+        "ae".repeat(32),
+        "af".repeat(32),
+        "00".repeat(256),
+        "b0".repeat(32),
+        "aa".repeat(32),
+    );
+
+    let header = parse_header(json.as_bytes()).expect("header should parse");
+    assert_eq!(header.number, 0x1506a1f);
+    assert_eq!(header.hash, Some(B256::repeat_byte(0xaa)));
+    assert_eq!(header.parent_hash, B256::repeat_byte(0xab));
+    assert_eq!(header.author, Address::repeat_byte(0x12));
+    assert_eq!(header.base_fee_per_gas, Some(U256::from(7)));
+
+    assert!(parse_header(b"{}").is_none());
+
+    // Missing or truncated hashes reject the header instead of being read past
+    let without_parent = json.replace(&format!(r#""parentHash":"0x{}","#, "ab".repeat(32)), "");
+    assert!(parse_header(without_parent.as_bytes()).is_none());
+    let short_root = json.replace(&"ad".repeat(32), "ad");
+    assert!(parse_header(short_root.as_bytes()).is_none());
+}
+
+fn receipt_json(index: u64, to: &str, contract: &str, logs: &str) -> String {
+    format!(
+        r#"{{"blockHash":"0x{}","blockNumber":"0x1506a1f","contractAddress":{},"cumulativeGasUsed":"0x{:x}","effectiveGasPrice":"0x3b9aca07","from":"0x{}","gasUsed":"0x5208","logs":[{}],"logsBloom":"0x{}","status":"0x1","to":{},"transactionHash":"0x{:064x}","transactionIndex":"0x{:x}","type":"0x2"}}"#,
+        "aa".repeat(32),
+        contract,
+        21000 * (index + 1),
+        "12".repeat(20),
+        logs,
+        "00".repeat(256),
+        to,
+        index + 1,
+        index,
+    )
+}
+
+#[test]
+fn test_parse_transaction_receipt() {
+    let log = r#"{"address":"0x1111111111111111111111111111111111111111","topics":["0x0000000000000000000000000000000000000000000000000000000000000001"],"data":"0x2a","blockNumber":"0x1506a1f","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001","transactionIndex":"0x0","blockHash":"0x00000000000000000000000000000000000000000000000000000000000000aa","logIndex":"0x0","removed":false}"#;
+    let json = format!(
+        r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#,
+        receipt_json(0, r#""0x2222222222222222222222222222222222222222""#, "null", log)
+    );
+
+    let receipt = parse_receipt(json.as_bytes()).expect("receipt should parse");
+    assert_eq!(receipt.transaction_hash, B256::with_last_byte(1));
+    assert_eq!(receipt.block_hash, B256::repeat_byte(0xaa));
+    assert_eq!(receipt.block_number, U64::from(0x1506a1f));
+    assert_eq!(receipt.from, Address::repeat_byte(0x12));
+    assert_eq!(receipt.to, Some(Address::repeat_byte(0x22)));
+    assert_eq!(receipt.contract_address, None);
+    assert_eq!(receipt.gas_used, U256::from(21000));
+    assert_eq!(receipt.effective_gas_price, U256::from(0x3b9aca07u64));
+    assert_eq!(receipt.blob_gas_used, None);
+    assert_eq!(receipt.transaction_type, U64::from(2));
+    assert!(receipt.is_success());
+    assert_eq!(receipt.logs.len(), 1);
+    assert_eq!(receipt.logs[0].address, Address::repeat_byte(0x11));
+    assert_eq!(receipt.logs[0].data, "0x2a");
+
+    assert!(parse_receipt(br#"{"jsonrpc":"2.0","id":1,"result":null}"#).is_none());
+}
+
+#[test]
+fn test_parse_block_receipts() {
+    let receipts = (0..300)
+        .map(|i| {
+            if i == 0 {
+                receipt_json(i, "null", r#""0x3333333333333333333333333333333333333333""#, "")
+            } else {
+                receipt_json(i, r#""0x2222222222222222222222222222222222222222""#, "null", "")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let json = format!(r#"{{"jsonrpc":"2.0","id":1,"result":[{}]}}"#, receipts);
+
+    let receipts = parse_block_receipts(json.as_bytes());
+    assert_eq!(receipts.len(), 300);
+    assert_eq!(receipts[0].to, None);
+    assert_eq!(receipts[0].contract_address, Some(Address::repeat_byte(0x33)));
+    assert_eq!(receipts[299].transaction_index, U64::from(299));
+    assert_eq!(receipts[299].cumulative_gas_used, U256::from(21000 * 300));
+
+    assert!(parse_block_receipts(br#"{"jsonrpc":"2.0","id":1,"result":null}"#).is_empty());
+}
+
+fn full_tx_json(index: u64, to: &str) -> String {
+    format!(
+        r#"{{"blockHash":"0x{}","blockNumber":"0x10","from":"0x1111111111111111111111111111111111111111","gas":"0x5208","gasPrice":"0x7","hash":"0x{:064x}","input":"0x","nonce":"0x{:x}","r":"0x{}","s":"0x{}","to":{},"transactionIndex":"0x{:x}","type":"0x0","v":"0x25","value":"0x1"}}"#,
+        "aa".repeat(32),
+        0xc0 + index,
+        100 + index,
+        "cc".repeat(32),
+        "dd".repeat(32),
+        to,
+        index,
+    )
+}
+
+#[test]
+fn test_parse_block_with_full_transactions() {
+    // Keys in the order geth sends them, block fields on both sides of the array
+    let json = format!(
+        r#"{{"jsonrpc":"2.0","id":1,"result":{{"baseFeePerGas":"0x7","difficulty":"0x0","extraData":"0x","gasLimit":"0x1c9c380","gasUsed":"0xa410","hash":"0x{}","logsBloom":"0x{}","miner":"0x{}","mixHash":"0x{}","nonce":"0x0000000000000000","number":"0x10","parentHash":"0x{}","receiptsRoot":"0x{}","sha3Uncles":"0x{}","size":"0x400","stateRoot":"0x{}","timestamp":"0x5","transactions":[{},{}],"transactionsRoot":"0x{}","uncles":[]}}}}"#,
+        "aa".repeat(32),
+        "00".repeat(256),
+        "12".repeat(20),
+        "b0".repeat(32),
+        "ab".repeat(32),
+        "af".repeat(32),
+        "ac".repeat(32),
+        "ad".repeat(32),
+        full_tx_json(0, "null"),
+        full_tx_json(1, r#""0x2222222222222222222222222222222222222222""#),
+        "ae".repeat(32),
+    );
+
+    let block = parse_block(json.as_bytes()).expect("block should parse");
+    assert_eq!(block.number, U64::from(0x10));
+    assert_eq!(block.hash, Some(B256::repeat_byte(0xaa)));
+    assert_eq!(block.nonce, U64::ZERO);
+    assert_eq!(block.transactions_root, B256::repeat_byte(0xae));
+    assert!(block.uncles.is_empty());
+
+    let BlockTransactions::Full(txs) = &block.transactions else {
+        panic!("expected full transactions");
+    };
+    assert_eq!(txs.len(), 2);
+    assert_eq!(txs[0].hash, B256::with_last_byte(0xc0));
+    assert_eq!(txs[0].nonce, U64::from(100));
+    assert_eq!(txs[0].to, None);
+    assert!(txs[1].to.is_some());
+    assert_eq!(txs[1].transaction_index, Some(U64::from(1)));
+    assert_eq!(block.transactions.hashes()[1], B256::with_last_byte(0xc1));
+
+    // A transaction that does not parse fails the block instead of going missing
+    let broken = json.replace(
+        &full_tx_json(1, r#""0x2222222222222222222222222222222222222222""#),
+        r#"{"hash":"0x01"}"#,
+    );
+    assert!(parse_block(broken.as_bytes()).is_none());
+}
+
+fn typed_tx_response(ty: u8, extra: &str) -> String {
+    // geth field order: the authorization list comes before the signature
+    format!(
+        r#"{{"jsonrpc":"2.0","id":1,"result":{{"blockHash":null,"blockNumber":null,"from":"0x1111111111111111111111111111111111111111","gas":"0x5208","gasPrice":"0x9","maxFeePerGas":"0x9","maxPriorityFeePerGas":"0x1","hash":"0x{}","input":"0x","nonce":"0x7","to":null,"transactionIndex":null,"value":"0x0","type":"0x{:x}","accessList":[{{"address":"0x2222222222222222222222222222222222222222","storageKeys":["0x{}"]}}],"chainId":"0x1",{}"v":"0x1","r":"0x{}","s":"0x{}","yParity":"0x1"}}}}"#,
+        "bb".repeat(32),
+        ty,
+        "01".repeat(32),
+        extra,
+        "cc".repeat(32),
+        "0dd",
+    )
+}
+
+#[test]
+fn test_parse_eip1559_pending_creation() {
+    let json = typed_tx_response(2, "");
+    let tx = parse_transaction(json.as_bytes()).expect("tx should parse");
+
+    assert_eq!(tx.tx_type(), 2);
+    assert_eq!(tx.to, None);
+    assert_eq!(tx.block_hash, None);
+    assert_eq!(tx.from, Address::repeat_byte(0x11));
+    assert_eq!(tx.nonce, U64::from(7));
+    assert_eq!(tx.s, U256::from(0xdd));
+    match &tx.transaction {
+        TypedTransaction::Eip1559 {
+            chain_id,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            access_list,
+            y_parity,
+        } => {
+            assert_eq!(*chain_id, U64::from(1));
+            assert_eq!(*max_fee_per_gas, U256::from(9));
+            assert_eq!(*max_priority_fee_per_gas, U256::from(1));
+            assert_eq!(access_list.len(), 1);
+            assert_eq!(access_list[0].address, Address::repeat_byte(0x22));
+            assert_eq!(access_list[0].storage_keys, vec![B256::repeat_byte(0x01)]);
+            assert_eq!(*y_parity, U64::from(1));
+        },
+        other => panic!("expected an EIP-1559 transaction, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_blob_and_set_code_transactions() {
+    let json = typed_tx_response(
+        3,
+        &format!(r#""maxFeePerBlobGas":"0x3","blobVersionedHashes":["0x{}"],"#, "01".repeat(32)),
+    );
+    let tx = parse_transaction(json.as_bytes()).expect("blob tx should parse");
+    let TypedTransaction::Eip4844 {
+        max_fee_per_blob_gas,
+        blob_versioned_hashes,
+        ..
+    } = &tx.transaction
+    else {
+        panic!("expected a blob transaction");
+    };
+    assert_eq!(*max_fee_per_blob_gas, U256::from(3));
+    assert_eq!(blob_versioned_hashes, &vec![B256::repeat_byte(0x01)]);
+
+    let authorization = r#""authorizationList":[{"chainId":"0x1","address":"0x3333333333333333333333333333333333333333","nonce":"0x2a","yParity":"0x0","r":"0x5","s":"0x6"}],"#;
+    let json = typed_tx_response(4, authorization);
+    let tx = parse_transaction(json.as_bytes()).expect("7702 tx should parse");
+
+    // Tuple fields must not leak into the transaction itself
+    assert_eq!(tx.nonce, U64::from(7));
+    assert_eq!(tx.r, U256::from_be_bytes([0xcc; 32]));
+    let TypedTransaction::Eip7702 {
+        authorization_list, ..
+    } = &tx.transaction
+    else {
+        panic!("expected a set code transaction");
+    };
+    assert_eq!(authorization_list.len(), 1);
+    assert_eq!(authorization_list[0].address, Address::repeat_byte(0x33));
+    assert_eq!(authorization_list[0].nonce, U64::from(0x2a));
+    assert_eq!(authorization_list[0].r, U256::from(5));
+}
+
+#[test]
+fn test_parse_legacy_transaction_without_type() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"result":{"blockHash":"0x00000000000000000000000000000000000000000000000000000000000000aa","blockNumber":"0x1","from":"0x1111111111111111111111111111111111111111","gas":"0x5208","gasPrice":"0x3b9aca00","hash":"0x00000000000000000000000000000000000000000000000000000000000000bb","input":"0x","nonce":"0x0","to":"0x2222222222222222222222222222222222222222","transactionIndex":"0x0","value":"0x0","v":"0x1b","r":"0x1","s":"0x2"}}"#;
+
+    let tx = parse_transaction(json.as_bytes()).expect("tx should parse");
+    assert_eq!(tx.transaction, TypedTransaction::Legacy { chain_id: None });
+    assert_eq!(tx.to, Some(Address::repeat_byte(0x22)));
+    assert_eq!(tx.v, Some(U64::from(27)));
+
+    // serde agrees with the fast parser on transactions without `type`
+    let result = &json[json.find(r#""result":"#).unwrap() + 9..json.len() - 1];
+    let tx: TransactionTx = serde_json::from_str(result).expect("serde should parse");
+    assert_eq!(tx.transaction, TypedTransaction::Legacy { chain_id: None });
+    assert_eq!(tx.gas_price, Some(U256::from(1_000_000_000u64)));
+    assert_eq!(tx.v, Some(U64::from(27)));
+}
+
+#[test]
+fn test_parse_rejects_unknown_transaction_types() {
+    // An OP stack deposit and a type that does not fit in a byte
+    for ty in [0x7e, 0x100] {
+        let json =
+            typed_tx_response(0, "").replace(r#""type":"0x0""#, &format!(r#""type":"0x{:x}""#, ty));
+        assert!(parse_transaction(json.as_bytes()).is_none(), "type {:#x}", ty);
+    }
+}
+
+#[test]
+fn test_parse_prague_block_with_withdrawals() {
+    let json = format!(
+        r#"{{"jsonrpc":"2.0","id":1,"result":{{"baseFeePerGas":"0x7","blobGasUsed":"0x20000","difficulty":"0x0","excessBlobGas":"0x40000","extraData":"0x","gasLimit":"0x1c9c380","gasUsed":"0x0","hash":"0x{}","logsBloom":"0x{}","miner":"0x{}","mixHash":"0x{}","nonce":"0x0000000000000000","number":"0x10","parentBeaconBlockRoot":"0x{}","parentHash":"0x{}","receiptsRoot":"0x{}","requestsHash":"0x{}","sha3Uncles":"0x{}","stateRoot":"0x{}","timestamp":"0x5","transactions":[],"transactionsRoot":"0x{}","uncles":[],"withdrawals":[{{"index":"0x2a","validatorIndex":"0x3e8","address":"0x{}","amount":"0x1bc16d"}}],"withdrawalsRoot":"0x{}"}}}}"#,
+        "aa".repeat(32),
+        "00".repeat(256),
+        "12".repeat(20),
+        "b0".repeat(32),
+        "b1".repeat(32),
+        "ab".repeat(32),
+        "af".repeat(32),
+        "b2".repeat(32),
+        "ac".repeat(32),
+        "ad".repeat(32),
+        "ae".repeat(32),
+        "34".repeat(20),
+        "b3".repeat(32),
+    );
+
+    let block = parse_block(json.as_bytes()).expect("block should parse");
+    assert_eq!(block.blob_gas_used, Some(U64::from(0x20000)));
+    assert_eq!(block.excess_blob_gas, Some(U64::from(0x40000)));
+    assert_eq!(block.parent_beacon_block_root, Some(B256::repeat_byte(0xb1)));
+    assert_eq!(block.requests_hash, Some(B256::repeat_byte(0xb2)));
+    assert_eq!(block.withdrawals_root, Some(B256::repeat_byte(0xb3)));
+    // Post merge the randao value is carried in mixHash
+    assert_eq!(block.prev_randao, Some(B256::repeat_byte(0xb0)));
+
+    let withdrawals = block.withdrawals.expect("withdrawals should parse");
+    assert_eq!(withdrawals.len(), 1);
+    assert_eq!(withdrawals[0].index, U64::from(42));
+    assert_eq!(withdrawals[0].validator_index, U64::from(1000));
+    assert_eq!(withdrawals[0].address, Address::repeat_byte(0x34));
+    assert_eq!(withdrawals[0].amount, U256::from(0x1bc16d));
+
+    let result = &json[json.find(r#""result":"#).unwrap() + 9..json.len() - 1];
+    let header = parse_header(result.as_bytes()).expect("header should parse");
+    assert_eq!(header.requests_hash, Some(B256::repeat_byte(0xb2)));
+    assert_eq!(header.withdrawls_root, Some(B256::repeat_byte(0xb3)));
+    assert_eq!(header.blob_gas_used, Some(U64::from(0x20000)));
+}
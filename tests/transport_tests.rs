@@ -0,0 +1,1490 @@
+mod common;
+
+use std::{
+    io::{Read, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use alloy::primitives::{address, b256, bytes, Bytes as AlloyBytes, B256, U256, U64};
+use async_trait::async_trait;
+use common::{ok, serve_http, serve_rpc, serve_ws, Received, WsReply};
+use flate2::{read::GzDecoder, write::GzEncoder};
+use futures::{Stream, StreamExt};
+use hyper::{
+    body::Bytes,
+    header::{HeaderMap, ACCEPT_ENCODING, AUTHORIZATION, CONTENT_ENCODING},
+    Response, StatusCode,
+};
+use palantiri::{
+    block_stream::{BlockEvent, BlockStream, Confirmation},
+    fee_oracle::FeeOracle,
+    header_store::Reorg,
+    hyper_rpc::{RpcClient, RpcRequest},
+    parser::types::{AccountOverride, BlockOverrides, CallOverrides, TransactionRequest},
+    revert::RevertReason,
+    transport::{
+        auth::{BearerToken, JwtAuth, StaticHeaders},
+        compression::Compression,
+        fallback::FallbackTransport,
+        http::TransportBuilder,
+        ipc::IpcTransport,
+        retry::{ErrorClass, RetryPolicy},
+        RpcUrl, Transport,
+    },
+    RpcError, TxRejection,
+};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, UnixListener},
+};
+
+fn mock_result(method: &str) -> Value {
+    match method {
+        "eth_chainId" => json!("0xaa36a7"),
+        "eth_blockNumber" => json!("0x10"),
+        _ => Value::Null,
+    }
+}
+
+/// Answers every request on the socket, closing it after `per_connection`
+/// responses to force the client through a reconnect
+async fn spawn_ws_server(per_connection: usize) -> String {
+    serve_ws(move |_, served, request| {
+        let result = mock_result(request["method"].as_str().unwrap());
+        let reply = WsReply::send(vec![common::result(&request, result)]);
+        match served + 1 == per_connection {
+            true => reply.close(),
+            false => reply,
+        }
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_ws_correlates_concurrent_requests() {
+    let url = spawn_ws_server(usize::MAX).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_ws().await.unwrap());
+
+    // Both requests go out with id 1 over the same socket
+    let (chain_id, block_number) = tokio::join!(client.get_chain_id(), client.get_block_number());
+    assert_eq!(chain_id.unwrap(), U64::from(11155111));
+    assert_eq!(block_number.unwrap(), U64::from(0x10));
+}
+
+#[tokio::test]
+async fn test_ws_reconnects_after_close() {
+    let url = spawn_ws_server(1).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_ws().await.unwrap());
+
+    for _ in 0..3 {
+        assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+    }
+}
+
+#[tokio::test]
+async fn test_ws_does_not_replay_transactions_after_reconnect() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let recorded = received.clone();
+
+    // The first connection takes both requests and hangs up without answering
+    let url = serve_ws(move |connection, served, request| {
+        let method = request["method"].as_str().unwrap().to_string();
+        recorded.lock().unwrap().push(method.clone());
+        match (connection, served) {
+            (0, 0) => WsReply::default(),
+            (0, _) => WsReply::default().close(),
+            _ => WsReply::send(vec![common::result(&request, mock_result(&method))]),
+        }
+    })
+    .await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_ws().await.unwrap());
+
+    let (sent, block_number) = tokio::join!(
+        client.send_raw_transaction(AlloyBytes::from_static(&[0x02])),
+        client.get_block_number()
+    );
+    assert!(matches!(sent, Err(RpcError::Connection(_))));
+    assert_eq!(block_number.unwrap(), U64::from(0x10));
+
+    let received = received.lock().unwrap();
+    let sends = received.iter().filter(|method| *method == "eth_sendRawTransaction").count();
+    assert_eq!(sends, 1);
+    assert_eq!(received.iter().filter(|method| *method == "eth_blockNumber").count(), 2);
+}
+
+fn header_json(number: u64) -> String {
+    format!(
+        r#"{{"number":"0x{:x}","hash":"0x{}","parentHash":"0x{}","sha3Uncles":"0x{}","miner":"0x{}","stateRoot":"0x{}","transactionsRoot":"0x{}","receiptsRoot":"0x{}","logsBloom":"0x{}","difficulty":"0x0","gasLimit":"0x1c9c380","gasUsed":"0x5208","timestamp":"0x6553f100","extraData":"0x","mixHash":"0x{}","nonce":"0x0000000000000000","baseFeePerGas":"0x7"}}"#,
+        number,
+        "11".repeat(32),
+        "22".repeat(32),
+        "33".repeat(32),
+        "44".repeat(20),
+        "55".repeat(32),
+        "66".repeat(32),
+        "77".repeat(32),
+        "00".repeat(256),
+        "88".repeat(32),
+    )
+}
+
+/// Accepts `eth_subscribe` for new heads, pushes one header per connection and
+/// hangs up so the client has to re-subscribe
+async fn spawn_ws_heads_server() -> String {
+    serve_ws(|connection, _, request| {
+        if request["method"] != "eth_subscribe" {
+            return WsReply::default();
+        }
+        assert_eq!(request["params"], json!(["newHeads"]));
+
+        let number = connection as u64 + 1;
+        let sub_id = format!("0xabc{}", number);
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "eth_subscription",
+            "params": {
+                "subscription": sub_id,
+                "result": serde_json::from_str::<Value>(&header_json(number)).unwrap()
+            }
+        });
+        WsReply::send(vec![common::result(&request, json!(sub_id)), notification]).close()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_ws_new_heads_survive_reconnect() {
+    let url = spawn_ws_heads_server().await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_ws().await.unwrap());
+
+    let mut heads = client.subscribe_new_heads().await.unwrap();
+
+    let first = heads.next().await.unwrap();
+    assert_eq!(first.number, 1);
+    assert_eq!(first.parent_hash, B256::repeat_byte(0x22));
+    assert_eq!(first.gas_limit, U256::from(30_000_000u64));
+
+    // Delivered on a fresh connection after the transport re-subscribed
+    let second = heads.next().await.unwrap();
+    assert_eq!(second.number, 2);
+}
+
+#[tokio::test]
+async fn test_client_reports_reorgs_it_sees() {
+    // Block 5 comes back with a different hash the second time
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let url = serve_rpc(move |_| {
+        let mut header: Value = serde_json::from_str(&header_json(5)).unwrap();
+        if counter.fetch_add(1, Ordering::SeqCst) > 0 {
+            header["hash"] = json!(format!("0x{}", "99".repeat(32)));
+        }
+        header
+    })
+    .await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    client.get_block_header_by_number(5, false).await.unwrap();
+    assert!(client.take_reorgs().is_empty());
+
+    client.get_block_header_by_number(5, false).await.unwrap();
+    assert_eq!(
+        client.take_reorgs(),
+        vec![Reorg {
+            number: 5,
+            dropped: vec![B256::repeat_byte(0x11)],
+        }]
+    );
+    assert!(client.take_reorgs().is_empty());
+    assert_eq!(client.header_store().hash_of(5), Some(B256::repeat_byte(0x99)));
+}
+
+#[tokio::test]
+async fn test_subscribe_unsupported_over_http() {
+    let client =
+        RpcClient::new(TransportBuilder::new("http://127.0.0.1:1").build_reqwest().unwrap());
+    assert!(client.subscribe_new_heads().await.is_err());
+}
+
+#[tokio::test]
+async fn test_ws_connect_error() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    assert!(TransportBuilder::new(url).build_ws().await.is_err());
+}
+
+/// Serves one IPC connection, answering requests in a single write without
+/// newline separators the way some nodes stream them
+async fn spawn_ipc_server() -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("palantiri-{}.ipc", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            if n == 0 {
+                return;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+
+            let mut requests = Vec::new();
+            while let Some(end) = buf.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buf.drain(..=end).collect();
+                requests.push(serde_json::from_slice::<Value>(&line).unwrap());
+            }
+            // Answer everything received so far in one write, newest first
+            if requests.is_empty() {
+                continue;
+            }
+
+            let mut out = String::new();
+            for request in requests.iter().rev() {
+                out.push_str(
+                    &json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "result": mock_result(request["method"].as_str().unwrap()),
+                    })
+                    .to_string(),
+                );
+            }
+            stream.write_all(out.as_bytes()).await.unwrap();
+        }
+    });
+
+    path
+}
+
+#[tokio::test]
+async fn test_ipc_correlates_concurrent_requests() {
+    let path = spawn_ipc_server().await;
+    let client = RpcClient::new(IpcTransport::connect(&path).await.unwrap());
+
+    let (chain_id, block_number) = tokio::join!(client.get_chain_id(), client.get_block_number());
+    assert_eq!(chain_id.unwrap(), U64::from(11155111));
+    assert_eq!(block_number.unwrap(), U64::from(0x10));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn test_ipc_connect_error() {
+    let path = std::env::temp_dir().join("palantiri-missing.ipc");
+    assert!(IpcTransport::connect(&path).await.is_err());
+
+    let builder = TransportBuilder::new("http://127.0.0.1:1").timeout(Duration::from_secs(1));
+    assert!(builder.build_ipc(path).await.is_err());
+}
+
+/// One canned HTTP response: status, optional `Retry-After` seconds and body
+type MockResponse<'a> = (u16, Option<u64>, &'a str);
+
+/// Plain HTTP JSON-RPC server playing back `script` in order and repeating the
+/// last entry, counting how many requests reached it
+async fn spawn_scripted_http_server(script: Vec<MockResponse<'_>>) -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let script: Vec<_> = script
+        .into_iter()
+        .map(|(status, retry_after, body)| (status, retry_after, Bytes::from(body.to_owned())))
+        .collect();
+
+    let url = serve_http(move |_| {
+        let hit = counter.fetch_add(1, Ordering::SeqCst);
+        let (status, retry_after, body) = script[hit.min(script.len() - 1)].clone();
+
+        let mut response = ok(body);
+        *response.status_mut() = StatusCode::from_u16(status).unwrap();
+        if let Some(seconds) = retry_after {
+            response.headers_mut().insert("retry-after", seconds.into());
+        }
+        response
+    })
+    .await;
+
+    (url, hits)
+}
+
+async fn spawn_http_server(status: u16, body: &str) -> (String, Arc<AtomicUsize>) {
+    spawn_scripted_http_server(vec![(status, None, body)]).await
+}
+
+/// Answers every request with `body`, keeping the parsed requests it received
+async fn spawn_recording_http_server(body: &'static str) -> (String, Arc<Mutex<Vec<Value>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let url = serve_http(move |received| {
+        recorded.lock().unwrap().push(received.json());
+        ok(body)
+    })
+    .await;
+
+    (url, requests)
+}
+
+async fn closed_http_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    url
+}
+
+/// Answers `eth_blockNumber`, keeping the headers of every request
+async fn spawn_header_recording_http_server() -> (String, Arc<Mutex<Vec<HeaderMap>>>) {
+    let headers = Arc::new(Mutex::new(Vec::new()));
+    let recorded = headers.clone();
+    let url = serve_http(move |received| {
+        recorded.lock().unwrap().push(received.headers);
+        ok(BLOCK_NUMBER)
+    })
+    .await;
+
+    (url.trim_start_matches("http://").to_string(), headers)
+}
+
+const BLOCK_NUMBER: &str = r#"{"jsonrpc":"2.0","id":1,"result":"0x10"}"#;
+const REQUEST: &[u8] = br#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#;
+
+fn rpc_urls(urls: &[&String]) -> Vec<RpcUrl> {
+    urls.iter().map(|url| url.parse().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn test_fallback_skips_unreachable_endpoint() {
+    let dead = closed_http_url().await;
+    let (healthy, _) = spawn_http_server(200, BLOCK_NUMBER).await;
+
+    let transport = TransportBuilder::new(&dead)
+        .with_fallbacks(vec![&healthy])
+        .build_fallback()
+        .unwrap();
+    let (_, served_by) =
+        transport.execute_with_endpoint(Bytes::from_static(REQUEST)).await.unwrap();
+    assert_eq!(*served_by, healthy.parse().unwrap());
+    assert_eq!(*transport.active_url(), healthy.parse().unwrap());
+
+    let client = RpcClient::new(transport);
+    assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+
+    // Single endpoint transports refuse to silently drop the fallbacks
+    let single = TransportBuilder::new(&dead).with_fallbacks(vec![&healthy]).build_http_hyper();
+    assert!(matches!(single, Err(RpcError::Transport(_))));
+}
+
+#[tokio::test]
+async fn test_fallback_on_server_error() {
+    let (failing, _) = spawn_http_server(503, "unavailable").await;
+    let (healthy, _) = spawn_http_server(200, BLOCK_NUMBER).await;
+
+    let transport = FallbackTransport::new(rpc_urls(&[&failing, &healthy]));
+    let (response, served_by) =
+        transport.execute_with_endpoint(Bytes::from_static(REQUEST)).await.unwrap();
+    assert_eq!(*served_by, healthy.parse().unwrap());
+    assert_eq!(response, BLOCK_NUMBER.as_bytes());
+}
+
+#[tokio::test]
+async fn test_fallback_cools_down_lagging_node() {
+    let (lagging, lagging_hits) = spawn_http_server(
+        200,
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"header not found"}}"#,
+    )
+    .await;
+    let (healthy, healthy_hits) = spawn_http_server(200, BLOCK_NUMBER).await;
+
+    let transport = FallbackTransport::new(rpc_urls(&[&lagging, &healthy]))
+        .with_cooldown(Duration::from_secs(60));
+    for _ in 0..3 {
+        let (_, served_by) =
+            transport.execute_with_endpoint(Bytes::from_static(REQUEST)).await.unwrap();
+        assert_eq!(*served_by, healthy.parse().unwrap());
+    }
+
+    // The lagging node is not retried while cooling down
+    assert_eq!(lagging_hits.load(Ordering::SeqCst), 1);
+    assert_eq!(healthy_hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_fallback_returns_node_error_when_all_lag() {
+    let body = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"header not found"}}"#;
+    let (first, _) = spawn_http_server(200, body).await;
+    let (second, _) = spawn_http_server(200, body).await;
+
+    let transport = FallbackTransport::new(rpc_urls(&[&first, &second]));
+    let (response, _) = transport.execute_with_endpoint(Bytes::from_static(REQUEST)).await.unwrap();
+    assert_eq!(response, body.as_bytes());
+}
+
+#[tokio::test]
+async fn test_fallback_reads_lagging_errors_from_the_message() {
+    let (healthy, healthy_hits) = spawn_http_server(200, BLOCK_NUMBER).await;
+
+    // Only the error message counts, not results or error data mentioning it
+    for body in [
+        r#"{"jsonrpc":"2.0","id":1,"result":"header not found","error":null}"#,
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":3,"message":"execution reverted","data":"header not found"}}"#,
+    ] {
+        let (node, _) = spawn_http_server(200, body).await;
+        let transport = FallbackTransport::new(rpc_urls(&[&node, &healthy]));
+        let (response, served_by) =
+            transport.execute_with_endpoint(Bytes::from_static(REQUEST)).await.unwrap();
+        assert_eq!(*served_by, node.parse().unwrap());
+        assert_eq!(response, body.as_bytes());
+    }
+    assert_eq!(healthy_hits.load(Ordering::SeqCst), 0);
+
+    // A batch fails over when any of its responses comes from a lagging node
+    let batch = r#"[{"jsonrpc":"2.0","id":1,"result":"0x10"},{"jsonrpc":"2.0","id":2,"error":{"code":-32000,"message":"missing trie node abc"}}]"#;
+    let (lagging, _) = spawn_http_server(200, batch).await;
+    let transport = FallbackTransport::new(rpc_urls(&[&lagging, &healthy]));
+    let (_, served_by) =
+        transport.execute_with_endpoint(Bytes::from_static(REQUEST)).await.unwrap();
+    assert_eq!(*served_by, healthy.parse().unwrap());
+}
+
+#[tokio::test]
+async fn test_fallback_does_not_rotate_on_client_error() {
+    let (unauthorized, _) = spawn_http_server(401, "unauthorized").await;
+    let (healthy, healthy_hits) = spawn_http_server(200, BLOCK_NUMBER).await;
+
+    let transport = FallbackTransport::new(rpc_urls(&[&unauthorized, &healthy]));
+    assert!(transport.execute_with_endpoint(Bytes::from_static(REQUEST)).await.is_err());
+    assert_eq!(healthy_hits.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_fallback_retries_when_every_endpoint_fails() {
+    let (first, first_hits) =
+        spawn_scripted_http_server(vec![(503, None, "busy"), (200, None, BLOCK_NUMBER)]).await;
+    let (second, second_hits) = spawn_http_server(503, "busy").await;
+
+    let client = RpcClient::new(
+        TransportBuilder::new(&first)
+            .with_fallbacks(vec![&second])
+            .retry_policy(fast_retries())
+            .pool_max_idle(4)
+            .build_fallback()
+            .unwrap(),
+    );
+    assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+    assert_eq!(first_hits.load(Ordering::SeqCst), 2);
+    assert_eq!(second_hits.load(Ordering::SeqCst), 1);
+
+    // Without a retry policy a failed round is final
+    let (url, hits) = spawn_http_server(503, "busy").await;
+    let transport = FallbackTransport::new(rpc_urls(&[&url]));
+    assert!(transport.execute_with_endpoint(Bytes::from_static(REQUEST)).await.is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+fn http_client(builder: TransportBuilder, transport: usize) -> RpcClient {
+    match transport {
+        0 => RpcClient::new(builder.build_http_hyper().unwrap()),
+        1 => RpcClient::new(builder.build_reqwest().unwrap()),
+        2 => RpcClient::new(builder.build_tower().unwrap()),
+        3 => RpcClient::new(builder.build_direct().unwrap()),
+        _ => RpcClient::new(builder.build_http().unwrap()),
+    }
+}
+
+#[tokio::test]
+async fn test_set_endpoint_moves_every_clone() {
+    for transport in 0..5 {
+        let (old, old_hits) = spawn_http_server(200, BLOCK_NUMBER).await;
+        let (new, new_hits) = spawn_http_server(200, BLOCK_NUMBER).await;
+
+        let client = http_client(TransportBuilder::new(old), transport);
+        let clone = client.clone();
+        assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+
+        clone.set_endpoint(&new).unwrap();
+        assert_eq!(client.endpoint().unwrap(), new.parse().unwrap());
+        assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+        assert_eq!(old_hits.load(Ordering::SeqCst), 1, "transport {}", transport);
+        assert_eq!(new_hits.load(Ordering::SeqCst), 1, "transport {}", transport);
+
+        // A bad url leaves the current one in place
+        assert!(matches!(client.set_endpoint("ws://127.0.0.1:1"), Err(RpcError::InvalidUrl(_))));
+        assert!(matches!(client.set_endpoint("not a url"), Err(RpcError::InvalidUrl(_))));
+        assert_eq!(client.endpoint().unwrap(), new.parse().unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_set_endpoint_unsupported() {
+    let url = spawn_ws_server(1).await;
+    let client = RpcClient::new(TransportBuilder::new(&url).build_ws().await.unwrap());
+    assert_eq!(client.endpoint().unwrap(), url.parse().unwrap());
+    assert!(client.set_endpoint(&url).is_err());
+
+    let (a, _) = spawn_http_server(200, BLOCK_NUMBER).await;
+    let (b, _) = spawn_http_server(200, BLOCK_NUMBER).await;
+    let client = RpcClient::new(FallbackTransport::new(rpc_urls(&[&a, &b])));
+    assert!(client.set_endpoint(&b).is_err());
+}
+
+#[tokio::test]
+async fn test_invalid_urls_fail_at_build() {
+    let invalid = |result: Result<_, RpcError>| matches!(result, Err(RpcError::InvalidUrl(_)));
+
+    assert!(invalid(TransportBuilder::new("localhost:8545").build_http_hyper().map(drop)));
+    assert!(invalid(TransportBuilder::new("not a url").build_reqwest().map(drop)));
+    assert!(invalid(TransportBuilder::new("ws://127.0.0.1:8546").build_tower().map(drop)));
+    assert!(invalid(
+        TransportBuilder::new("http://127.0.0.1:8545").build_ws().await.map(drop)
+    ));
+    assert!(invalid(
+        TransportBuilder::new("http://127.0.0.1:8545")
+            .with_fallbacks(["http://127.0.0.1:8546", "127.0.0.1:8547"])
+            .build_fallback()
+            .map(drop)
+    ));
+}
+
+#[tokio::test]
+async fn test_url_credentials_sent_as_basic_auth() {
+    for transport in 0..5 {
+        let (addr, headers) = spawn_header_recording_http_server().await;
+        let url = format!("http://user:s3cret@{}/rpc", addr);
+
+        let client = http_client(TransportBuilder::new(&url), transport);
+        assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+        // base64("user:s3cret")
+        let authorization = headers.lock().unwrap()[0][AUTHORIZATION].clone();
+        assert_eq!(authorization, "Basic dXNlcjpzM2NyZXQ=", "transport {}", transport);
+
+        let endpoint = client.endpoint().unwrap();
+        assert_eq!(endpoint.to_string(), format!("http://***@{}/rpc", addr));
+        assert!(!format!("{:?}", client).contains("s3cret"));
+
+        // Dropped along with the url they came with
+        client.set_endpoint(format!("http://{}/rpc", addr)).unwrap();
+        client.get_block_number().await.unwrap();
+        assert!(!headers.lock().unwrap()[1].contains_key(AUTHORIZATION));
+    }
+}
+
+#[tokio::test]
+async fn test_connection_errors_leave_out_the_key() {
+    let key = "0123456789abcdef0123456789abcdef";
+    let url = format!("{}/v3/{}", closed_http_url().await, key);
+
+    let clients = [
+        RpcClient::new(TransportBuilder::new(&url).max_retries(0).build_reqwest().unwrap()),
+        RpcClient::new(TransportBuilder::new(&url).build_direct_reqwest().unwrap()),
+        RpcClient::new(TransportBuilder::new(&url).build_http().unwrap()),
+    ];
+    for client in clients {
+        let error = client.get_block_number().await.unwrap_err();
+        assert!(matches!(error, RpcError::Connection(_)));
+        assert!(!error.to_string().contains(key), "{}", error);
+    }
+}
+
+#[tokio::test]
+async fn test_auth_providers_on_http_transports() {
+    let path = std::env::temp_dir().join(format!("palantiri-jwt-{}.hex", std::process::id()));
+    std::fs::write(&path, format!("0x{}\n", "ab".repeat(32))).unwrap();
+
+    for transport in 0..4 {
+        let (addr, headers) = spawn_header_recording_http_server().await;
+        let url = format!("http://user:s3cret@{}", addr);
+        let client = |builder: TransportBuilder| match transport {
+            3 => RpcClient::new(builder.build_fallback().unwrap()),
+            _ => http_client(builder, transport),
+        };
+
+        let api_key = StaticHeaders::new().header("x-api-key", "k3y").unwrap();
+        client(TransportBuilder::new(&url).auth(api_key))
+            .get_block_number()
+            .await
+            .unwrap();
+        let bearer = BearerToken::new("t0ken").unwrap();
+        client(TransportBuilder::new(&url).auth(bearer))
+            .get_block_number()
+            .await
+            .unwrap();
+        let jwt = JwtAuth::from_file(&path).unwrap();
+        client(TransportBuilder::new(&url).auth(jwt)).get_block_number().await.unwrap();
+
+        let headers = headers.lock().unwrap();
+        assert_eq!(headers[0]["x-api-key"], "k3y", "transport {}", transport);
+        assert_eq!(headers[0][AUTHORIZATION], "Basic dXNlcjpzM2NyZXQ=");
+        // Replaces the credentials from the url
+        assert_eq!(headers[1].get_all(AUTHORIZATION).iter().count(), 1);
+        assert_eq!(headers[1][AUTHORIZATION], "Bearer t0ken", "transport {}", transport);
+
+        let jwt = headers[2][AUTHORIZATION].to_str().unwrap();
+        let token = jwt.strip_prefix("Bearer ").unwrap();
+        assert_eq!(token.split('.').count(), 3, "transport {}", transport);
+        assert!(token.starts_with("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9."));
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+/// Gzips the response whenever the request accepts gzip, keeps the headers
+/// and raw body of every request
+async fn spawn_gzip_http_server() -> (String, Arc<Mutex<Vec<(HeaderMap, Bytes)>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let url = serve_http(move |Received { headers, body }| {
+        let gzip = headers
+            .get(ACCEPT_ENCODING)
+            .is_some_and(|accepted| accepted.to_str().unwrap().contains("gzip"));
+        recorded.lock().unwrap().push((headers, body));
+
+        match gzip {
+            true => {
+                let mut encoder = GzEncoder::new(Vec::new(), Default::default());
+                encoder.write_all(BLOCK_NUMBER.as_bytes()).unwrap();
+                Response::builder()
+                    .header(CONTENT_ENCODING, "gzip")
+                    .body(encoder.finish().unwrap().into())
+                    .unwrap()
+            },
+            false => ok(BLOCK_NUMBER),
+        }
+    })
+    .await;
+
+    (url, requests)
+}
+
+fn compressing_client(builder: TransportBuilder, transport: usize) -> RpcClient {
+    match transport {
+        0 => RpcClient::new(builder.build_http_hyper().unwrap()),
+        1 => RpcClient::new(builder.build_tower().unwrap()),
+        _ => RpcClient::new(builder.build_fallback().unwrap()),
+    }
+}
+
+#[tokio::test]
+async fn test_compressed_responses_are_decoded() {
+    for transport in 0..3 {
+        let (url, requests) = spawn_gzip_http_server().await;
+
+        let client = compressing_client(TransportBuilder::new(&url), transport);
+        assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+        let disabled = TransportBuilder::new(&url).compression(Compression::DISABLED);
+        let client = compressing_client(disabled, transport);
+        assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+
+        let requests = requests.lock().unwrap();
+        let accepted = requests[0].0[ACCEPT_ENCODING].to_str().unwrap();
+        for encoding in ["gzip", "deflate", "br", "zstd"] {
+            assert!(accepted.contains(encoding), "transport {} accepts {}", transport, accepted);
+        }
+        assert!(!requests[1].0.contains_key(ACCEPT_ENCODING), "transport {}", transport);
+    }
+}
+
+#[tokio::test]
+async fn test_large_requests_are_gzipped() {
+    let batch = format!("[{}]", vec![String::from_utf8_lossy(REQUEST); 20].join(","));
+
+    for transport in 0..2 {
+        let (url, requests) = spawn_gzip_http_server().await;
+        let builder = TransportBuilder::new(&url)
+            .compression(Compression::default().compress_requests_over(REQUEST.len() + 1));
+        let transport: Box<dyn Transport> = match transport {
+            0 => Box::new(builder.build_http_hyper().unwrap()),
+            _ => Box::new(builder.build_tower().unwrap()),
+        };
+
+        transport.request(Bytes::from_static(REQUEST)).await.unwrap();
+        let response = transport.request(Bytes::from(batch.clone())).await.unwrap();
+        assert_eq!(response, BLOCK_NUMBER.as_bytes());
+
+        let requests = requests.lock().unwrap();
+        assert!(!requests[0].0.contains_key(CONTENT_ENCODING));
+        assert_eq!(requests[0].1, REQUEST);
+
+        let (headers, body) = &requests[1];
+        assert_eq!(headers[CONTENT_ENCODING], "gzip");
+        assert!(body.len() < batch.len());
+        let mut decoded = String::new();
+        GzDecoder::new(&body[..]).read_to_string(&mut decoded).unwrap();
+        assert_eq!(decoded, batch);
+    }
+}
+
+fn fast_retries() -> RetryPolicy {
+    RetryPolicy::default().backoff(Duration::from_millis(1), Duration::from_millis(5))
+}
+
+#[tokio::test]
+async fn test_retries_server_errors_on_every_http_transport() {
+    for transport in 0..4 {
+        let (url, hits) = spawn_scripted_http_server(vec![
+            (503, None, "busy"),
+            (502, None, "bad gateway"),
+            (200, None, BLOCK_NUMBER),
+        ])
+        .await;
+        let builder = TransportBuilder::new(url).retry_policy(fast_retries());
+        let client = match transport {
+            0 => RpcClient::new(builder.build_http_hyper().unwrap()),
+            1 => RpcClient::new(builder.build_reqwest().unwrap()),
+            2 => RpcClient::new(builder.build_http().unwrap()),
+            _ => RpcClient::new(builder.build_tower().unwrap()),
+        };
+
+        assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_retries() {
+    let (url, hits) = spawn_http_server(503, "busy").await;
+    let client = RpcClient::new(
+        TransportBuilder::new(url)
+            .retry_policy(fast_retries())
+            .max_retries(2)
+            .build_reqwest()
+            .unwrap(),
+    );
+
+    assert!(client.get_block_number().await.is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_client_errors_are_not_retried() {
+    let (url, hits) = spawn_http_server(400, "bad request").await;
+    let client = RpcClient::new(
+        TransportBuilder::new(url)
+            .retry_policy(fast_retries())
+            .build_http_hyper()
+            .unwrap(),
+    );
+
+    assert!(client.get_block_number().await.is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_honors_retry_after() {
+    let (url, hits) = spawn_scripted_http_server(vec![
+        (429, Some(1), "slow down"),
+        (
+            200,
+            Some(1),
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"limit exceeded"}}"#,
+        ),
+        (200, None, BLOCK_NUMBER),
+    ])
+    .await;
+    // Retry-After is honored up to the max backoff
+    let policy = RetryPolicy::default().backoff(Duration::from_millis(1), Duration::from_secs(2));
+    let client =
+        RpcClient::new(TransportBuilder::new(url).retry_policy(policy).build_tower().unwrap());
+
+    let start = Instant::now();
+    assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+    assert!(start.elapsed() >= Duration::from_secs(2));
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_retry_policy_error_class_override() {
+    let (url, hits) = spawn_http_server(503, "busy").await;
+    let policy = fast_retries().retry_if(|class| class == ErrorClass::RateLimited);
+    let client =
+        RpcClient::new(TransportBuilder::new(url).retry_policy(policy).build_reqwest().unwrap());
+
+    assert!(client.get_block_number().await.is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_retry_deadline_bounds_retry_after() {
+    let (url, hits) = spawn_scripted_http_server(vec![(429, Some(5), "slow down")]).await;
+    let policy = fast_retries().max_retries(1000).deadline(Duration::from_millis(200));
+    let client =
+        RpcClient::new(TransportBuilder::new(url).retry_policy(policy).build_http_hyper().unwrap());
+
+    // Retry-After is capped at the max backoff, the deadline ends the retries
+    let start = Instant::now();
+    assert!(client.get_block_number().await.is_err());
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(hits.load(Ordering::SeqCst) > 1);
+}
+
+#[tokio::test]
+async fn test_request_timeout_applies_per_attempt() {
+    // Accepts connections but never answers
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            held.push(stream);
+        }
+    });
+
+    let client = RpcClient::new(
+        TransportBuilder::new(url)
+            .timeout(Duration::from_millis(100))
+            .retry_policy(fast_retries().max_retries(1))
+            .build_http_hyper()
+            .unwrap(),
+    );
+
+    let start = Instant::now();
+    assert!(client.get_block_number().await.is_err());
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(200) && elapsed < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn test_json_rpc_error_is_surfaced() {
+    let (url, _) = spawn_http_server(
+        200,
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"invalid argument 0","data":"0x01"}}"#,
+    )
+    .await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    match client.get_balance(Default::default(), "latest").await {
+        Err(RpcError::JsonRpc {
+            code,
+            message,
+            data,
+        }) => {
+            assert_eq!(code, -32602);
+            assert_eq!(message, "invalid argument 0");
+            assert_eq!(data, Some(json!("0x01")));
+        },
+        other => panic!("expected a JSON-RPC error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_error_variants_and_retryability() {
+    let (url, _) = spawn_http_server(503, "busy").await;
+    let client = RpcClient::new(TransportBuilder::new(url).max_retries(0).build_reqwest().unwrap());
+    let error = client.get_block_number().await.unwrap_err();
+    assert!(matches!(&error, RpcError::Http { status: 503, body } if body == "busy"));
+    assert!(error.is_retryable());
+
+    let (url, _) = spawn_http_server(401, "unauthorized").await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_tower().unwrap());
+    let error = client.get_block_number().await.unwrap_err();
+    assert!(matches!(error, RpcError::Http { status: 401, .. }));
+    assert!(!error.is_retryable());
+
+    let client = RpcClient::new(
+        TransportBuilder::new(closed_http_url().await)
+            .max_retries(0)
+            .build_http_hyper()
+            .unwrap(),
+    );
+    let error = client.get_block_number().await.unwrap_err();
+    assert!(matches!(error, RpcError::Connection(_)));
+    assert!(error.is_retryable());
+
+    let rate_limited = RpcError::JsonRpc {
+        code: -32005,
+        message: "limit exceeded".into(),
+        data: None,
+    };
+    assert!(rate_limited.is_retryable());
+    let reverted = RpcError::JsonRpc {
+        code: 3,
+        message: "execution reverted".into(),
+        data: None,
+    };
+    assert!(!reverted.is_retryable());
+}
+
+#[tokio::test]
+async fn test_ws_timeout_and_rejected_subscription() {
+    // Accepts the socket, answers nothing and rejects subscriptions
+    let url = serve_ws(|_, _, request| match request["method"] == "eth_subscribe" {
+        true => WsReply::send(vec![json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": {"code": -32601, "message": "notifications not supported"},
+        })]),
+        false => WsReply::default(),
+    })
+    .await;
+
+    let client = RpcClient::new(
+        TransportBuilder::new(url)
+            .timeout(Duration::from_millis(100))
+            .build_ws()
+            .await
+            .unwrap(),
+    );
+
+    let error = client.get_block_number().await.unwrap_err();
+    assert!(matches!(error, RpcError::Timeout(t) if t == Duration::from_millis(100)));
+
+    match client.subscribe_new_heads().await {
+        Err(RpcError::JsonRpc { code, .. }) => assert_eq!(code, -32601),
+        Err(other) => panic!("expected a JSON-RPC error, got {:?}", other),
+        Ok(_) => panic!("subscription should be rejected"),
+    }
+}
+
+#[tokio::test]
+async fn test_estimate_gas_surfaces_revert_reason() {
+    // Error(string) with reason "not owner"
+    let (url, _) = spawn_http_server(
+        200,
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":3,"message":"execution reverted: not owner","data":"0x08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000096e6f74206f776e65720000000000000000000000000000000000000000000000"}}"#,
+    )
+    .await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    let error = client.estimate_gas(&TransactionRequest::default(), None).await.unwrap_err();
+    assert_eq!(error.revert_reason(), Some(RevertReason::Error("not owner".into())));
+}
+
+#[tokio::test]
+async fn test_call_with_state_and_block_overrides() {
+    let (url, requests) =
+        spawn_recording_http_server(r#"{"jsonrpc":"2.0","id":1,"result":"0x000000000000000000000000000000000000000000000000000000000000002a"}"#)
+            .await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    let token = address!("0x00000000000000000000000000000000000000aa");
+    let slot = b256!("0x0000000000000000000000000000000000000000000000000000000000000001");
+    let tx = TransactionRequest {
+        to: Some(token),
+        input: Some(bytes!("0x70a08231")),
+        max_fee_per_gas: Some(U256::from(100)),
+        max_priority_fee_per_gas: Some(U256::from(2)),
+        ..Default::default()
+    };
+    let overrides = CallOverrides {
+        state: Some(
+            [(
+                token,
+                AccountOverride {
+                    balance: Some(U256::from(1)),
+                    state_diff: Some([(slot, B256::with_last_byte(0x2a))].into()),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+        ),
+        block: Some(BlockOverrides {
+            number: Some(U64::from(20)),
+            ..Default::default()
+        }),
+    };
+
+    let result = client.call(&tx, Some(16), Some(&overrides)).await.unwrap();
+    assert_eq!(result, AlloyBytes::from(B256::with_last_byte(0x2a).to_vec()));
+
+    let params = requests.lock().unwrap()[0]["params"].clone();
+    assert_eq!(
+        params,
+        json!([
+            {
+                "to": token,
+                "input": "0x70a08231",
+                "maxFeePerGas": "0x64",
+                "maxPriorityFeePerGas": "0x2",
+            },
+            "0x10",
+            { "0x00000000000000000000000000000000000000aa": { "balance": "0x1", "stateDiff": { slot.to_string(): B256::with_last_byte(0x2a) } } },
+            { "number": "0x14" },
+        ])
+    );
+
+    // Without overrides only the call and block are sent
+    client.call(&tx, None, None).await.unwrap();
+    let params = requests.lock().unwrap()[1]["params"].clone();
+    assert_eq!(params.as_array().unwrap().len(), 2);
+    assert_eq!(params[1], "latest");
+}
+
+#[tokio::test]
+async fn test_send_raw_transaction_rejections() {
+    let cases = [
+        ("nonce too low: next nonce 5, tx nonce 3", TxRejection::NonceTooLow),
+        ("replacement transaction underpriced", TxRejection::ReplacementUnderpriced),
+        ("transaction underpriced", TxRejection::Underpriced),
+        ("insufficient funds for gas * price + value", TxRejection::InsufficientFunds),
+    ];
+
+    for (message, expected) in cases {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"error":{{"code":-32000,"message":"{}"}}}}"#,
+            message
+        );
+        let (url, _) = spawn_http_server(200, &body).await;
+        let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+        match client.send_raw_transaction(AlloyBytes::from_static(&[0x02])).await {
+            Err(RpcError::TxRejected { reason, message: m }) => {
+                assert_eq!(reason, expected);
+                assert_eq!(m, message);
+            },
+            other => panic!("expected a rejection, got {:?}", other),
+        }
+    }
+
+    // Unrelated node errors are passed through untouched
+    let (url, _) = spawn_http_server(
+        200,
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"invalid argument 0"}}"#,
+    )
+    .await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+    let error = client.send_raw_transaction(AlloyBytes::new()).await.unwrap_err();
+    assert!(matches!(error, RpcError::JsonRpc { code: -32602, .. }));
+}
+
+const TX_HASH: &str = r#"{"jsonrpc":"2.0","id":1,"result":"0x00000000000000000000000000000000000000000000000000000000000000bb"}"#;
+const PENDING_RECEIPT: &str = r#"{"jsonrpc":"2.0","id":1,"result":null}"#;
+const RECEIPT: &str = r#"{"jsonrpc":"2.0","id":1,"result":{"blockHash":"0x00000000000000000000000000000000000000000000000000000000000000aa","blockNumber":"0x10","contractAddress":null,"cumulativeGasUsed":"0x5208","effectiveGasPrice":"0x7","from":"0x1111111111111111111111111111111111111111","gasUsed":"0x5208","logs":[],"logsBloom":"0x00","status":"0x1","to":"0x2222222222222222222222222222222222222222","transactionHash":"0x00000000000000000000000000000000000000000000000000000000000000bb","transactionIndex":"0x0","type":"0x2"}}"#;
+
+#[tokio::test]
+async fn test_send_raw_transaction_sync_waits_for_receipt() {
+    let (url, hits) = spawn_scripted_http_server(vec![
+        (200, None, TX_HASH),
+        (200, None, PENDING_RECEIPT),
+        (200, None, RECEIPT),
+    ])
+    .await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    let receipt = client
+        .send_raw_transaction_sync(AlloyBytes::from_static(&[0x02]), Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(receipt.transaction_hash, B256::with_last_byte(0xbb));
+    assert_eq!(receipt.block_number, U64::from(16));
+    assert!(receipt.is_success());
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+    let (url, _) =
+        spawn_scripted_http_server(vec![(200, None, TX_HASH), (200, None, PENDING_RECEIPT)]).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+    let error = client
+        .send_raw_transaction_sync(AlloyBytes::new(), Duration::from_millis(250))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, RpcError::Timeout(t) if t == Duration::from_millis(250)));
+}
+
+/// Canonical chain served by `spawn_chain_server`, blocks indexed by number
+type Chain = Arc<Mutex<Vec<Value>>>;
+
+fn fork_hash(fork: u8, number: u64) -> B256 {
+    B256::left_padding_from(&[fork, number as u8])
+}
+
+fn chain_block(fork: u8, parent_fork: u8, number: u64) -> Value {
+    json!({
+        "baseFeePerGas": "0x7",
+        "difficulty": "0x0",
+        "extraData": "0x",
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x0",
+        "hash": fork_hash(fork, number),
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "miner": format!("0x{}", "12".repeat(20)),
+        "mixHash": B256::ZERO,
+        "nonce": "0x0000000000000000",
+        "number": format!("0x{:x}", number),
+        "parentHash": fork_hash(parent_fork, number.saturating_sub(1)),
+        "receiptsRoot": B256::ZERO,
+        "sha3Uncles": B256::ZERO,
+        "stateRoot": B256::ZERO,
+        "timestamp": format!("0x{:x}", number * 12),
+        "transactions": [],
+        "transactionsRoot": B256::ZERO,
+        "uncles": []
+    })
+}
+
+/// Blocks the `finalized` tag trails the head by in `spawn_chain_server`
+const FINALITY_LAG: usize = 100;
+
+/// Answers `eth_blockNumber`, `eth_getBlockByHash` and `eth_getBlockByNumber`,
+/// single or batched, from whatever `chain` holds at the time
+async fn spawn_chain_server(chain: Chain) -> String {
+    serve_rpc(move |request| {
+        let chain = chain.lock().unwrap();
+        let param = request["params"][0].as_str().unwrap_or_default();
+        match request["method"].as_str() {
+            Some("eth_blockNumber") => json!(format!("0x{:x}", chain.len() - 1)),
+            Some("eth_getBlockByHash") => chain
+                .iter()
+                .find(|block| block["hash"] == param)
+                .cloned()
+                .unwrap_or(Value::Null),
+            Some("eth_getBlockByNumber") if param == "finalized" => chain
+                .len()
+                .checked_sub(FINALITY_LAG + 1)
+                .map_or(Value::Null, |number| chain[number].clone()),
+            Some("eth_getBlockByNumber") => u64::from_str_radix(param.trim_start_matches("0x"), 16)
+                .ok()
+                .and_then(|number| chain.get(number as usize).cloned())
+                .unwrap_or(Value::Null),
+            _ => Value::Null,
+        }
+    })
+    .await
+}
+
+async fn next_event(
+    events: &mut (impl Stream<Item = Result<BlockEvent, RpcError>> + Unpin),
+) -> BlockEvent {
+    tokio::time::timeout(Duration::from_secs(5), events.next())
+        .await
+        .expect("no event within 5s")
+        .expect("stream ended")
+        .expect("stream errored")
+}
+
+#[tokio::test]
+async fn test_block_stream_follows_reorgs() {
+    let chain: Chain = Arc::new(Mutex::new((0..=2).map(|n| chain_block(0, 0, n)).collect()));
+    let url = spawn_chain_server(chain.clone()).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    let events = BlockStream::new(client)
+        .poll_interval(Duration::from_millis(10))
+        .confirmation(Confirmation::Depth(1))
+        .start_block(1)
+        .into_stream()
+        .await;
+    let mut events = Box::pin(events);
+
+    for number in 1..=2 {
+        match next_event(&mut events).await {
+            BlockEvent::NewBlock(block) => assert_eq!(block.hash, Some(fork_hash(0, number))),
+            other => panic!("expected block {}, got {:?}", number, other),
+        }
+    }
+    match next_event(&mut events).await {
+        BlockEvent::Finalized(header) => assert_eq!(header.number, 1),
+        other => panic!("expected block 1 to finalize, got {:?}", other),
+    }
+
+    // Block 2 is replaced by a sibling and the new fork grows on top of it
+    {
+        let mut chain = chain.lock().unwrap();
+        chain[2] = chain_block(1, 0, 2);
+        chain.push(chain_block(1, 1, 3));
+    }
+
+    match next_event(&mut events).await {
+        BlockEvent::Reorg { dropped, added } => {
+            assert_eq!(dropped.len(), 1);
+            assert_eq!(dropped[0].hash, Some(fork_hash(0, 2)));
+            assert_eq!(added.len(), 1);
+            assert_eq!(added[0].hash, Some(fork_hash(1, 2)));
+        },
+        other => panic!("expected a reorg, got {:?}", other),
+    }
+    match next_event(&mut events).await {
+        BlockEvent::NewBlock(block) => assert_eq!(block.hash, Some(fork_hash(1, 3))),
+        other => panic!("expected block 3, got {:?}", other),
+    }
+    match next_event(&mut events).await {
+        BlockEvent::Finalized(header) => assert_eq!(header.hash, Some(fork_hash(1, 2))),
+        other => panic!("expected block 2 to finalize, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_block_stream_walks_back_to_common_ancestor() {
+    let chain: Chain = Arc::new(Mutex::new((0..=3).map(|n| chain_block(0, 0, n)).collect()));
+    let url = spawn_chain_server(chain.clone()).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    let events = BlockStream::new(client)
+        .poll_interval(Duration::from_millis(10))
+        .confirmation(Confirmation::Depth(10))
+        .start_block(1)
+        .into_stream()
+        .await;
+    let mut events = Box::pin(events);
+    for _ in 1..=3 {
+        assert!(matches!(next_event(&mut events).await, BlockEvent::NewBlock(_)));
+    }
+
+    // Blocks 2 and 3 are replaced and the new fork is one block longer, the
+    // stream only sees the new tip link to an unknown parent
+    {
+        let mut chain = chain.lock().unwrap();
+        chain[2] = chain_block(1, 0, 2);
+        chain[3] = chain_block(1, 1, 3);
+        chain.push(chain_block(1, 1, 4));
+    }
+
+    match next_event(&mut events).await {
+        BlockEvent::Reorg { dropped, added } => {
+            let dropped: Vec<_> = dropped.iter().map(|header| header.hash).collect();
+            let added: Vec<_> = added.iter().map(|block| block.hash).collect();
+            assert_eq!(dropped, vec![Some(fork_hash(0, 2)), Some(fork_hash(0, 3))]);
+            assert_eq!(added, vec![Some(fork_hash(1, 2)), Some(fork_hash(1, 3))]);
+        },
+        other => panic!("expected a reorg, got {:?}", other),
+    }
+    match next_event(&mut events).await {
+        BlockEvent::NewBlock(block) => assert_eq!(block.hash, Some(fork_hash(1, 4))),
+        other => panic!("expected block 4, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_block_stream_reports_forks_deeper_than_max_reorg_depth() {
+    let chain: Chain = Arc::new(Mutex::new((0..=4).map(|n| chain_block(0, 0, n)).collect()));
+    let url = spawn_chain_server(chain.clone()).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    let events = BlockStream::new(client)
+        .poll_interval(Duration::from_millis(10))
+        .confirmation(Confirmation::Depth(10))
+        .max_reorg_depth(2)
+        .start_block(1)
+        .into_stream()
+        .await;
+    let mut events = Box::pin(events);
+    for _ in 1..=4 {
+        assert!(matches!(next_event(&mut events).await, BlockEvent::NewBlock(_)));
+    }
+
+    // Blocks 2 to 4 are replaced, more than the stream looks back for
+    {
+        let mut chain = chain.lock().unwrap();
+        chain[2] = chain_block(1, 0, 2);
+        chain[3] = chain_block(1, 1, 3);
+        chain[4] = chain_block(1, 1, 4);
+        chain.push(chain_block(1, 1, 5));
+    }
+
+    match next_event(&mut events).await {
+        BlockEvent::Reorg { dropped, added } => {
+            let dropped: Vec<_> = dropped.iter().map(|header| header.number).collect();
+            let added: Vec<_> = added.iter().map(|block| block.hash).collect();
+            assert_eq!(dropped, vec![1, 2, 3, 4]);
+            assert_eq!(added, vec![Some(fork_hash(1, 3)), Some(fork_hash(1, 4))]);
+        },
+        other => panic!("expected a reorg, got {:?}", other),
+    }
+
+    // The stream carries on from the new fork
+    match next_event(&mut events).await {
+        BlockEvent::NewBlock(block) => assert_eq!(block.hash, Some(fork_hash(1, 5))),
+        other => panic!("expected block 5, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_block_stream_finalizes_blocks_beyond_max_reorg_depth() {
+    // Finality trails the head by far more than the stream looks back for
+    let chain: Chain = Arc::new(Mutex::new((0..=250).map(|n| chain_block(0, 0, n)).collect()));
+    let url = spawn_chain_server(chain.clone()).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    let events = BlockStream::new(client)
+        .poll_interval(Duration::from_millis(10))
+        .confirmation(Confirmation::Finalized)
+        .max_reorg_depth(8)
+        .start_block(1)
+        .into_stream()
+        .await;
+    let mut events = Box::pin(events);
+
+    let (mut new_blocks, mut finalized) = (Vec::new(), Vec::new());
+    while new_blocks.len() < 250 || finalized.len() < 150 {
+        match next_event(&mut events).await {
+            BlockEvent::NewBlock(block) => new_blocks.push(block.number.to::<u64>()),
+            BlockEvent::Finalized(header) => finalized.push(header.number),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert_eq!(new_blocks, (1..=250).collect::<Vec<_>>());
+    assert_eq!(finalized, (1..=150).collect::<Vec<_>>());
+}
+
+/// Serves a chain whose head is `head`, paying `n`, `2n` and `3n` wei tips at
+/// the three percentiles of block `n` and charging `n` wei per blob gas in
+/// block `n`. Blocks of fork `fork` cost 100 wei more on each count. Keeps
+/// the `eth_feeHistory` block counts asked for
+async fn spawn_fee_server(
+    head: Arc<AtomicUsize>,
+    fork: Arc<AtomicUsize>,
+) -> (String, Arc<Mutex<Vec<u64>>>) {
+    let counts: Arc<Mutex<Vec<u64>>> = Arc::default();
+    let recorded = counts.clone();
+    let quantity = |value: &Value| u64::from_str_radix(&value.as_str().unwrap()[2..], 16).unwrap();
+
+    let url = serve_rpc(move |request| {
+        let head = head.load(Ordering::SeqCst) as u64;
+        let fork = fork.load(Ordering::SeqCst) as u64;
+        match request["method"].as_str() {
+            Some("eth_getBlockByNumber") => {
+                let mut block = chain_block(fork as u8, 0, head);
+                block["baseFeePerGas"] = json!("0x3b9aca00");
+                // Right at the target, the base fee holds
+                block["gasUsed"] = json!("0xe4e1c0");
+                block["blobGasUsed"] = json!("0x0");
+                block["excessBlobGas"] = json!("0x0");
+                block
+            },
+            Some("eth_maxPriorityFeePerGas") => json!("0x5"),
+            Some("eth_feeHistory") => {
+                let count = quantity(&request["params"][0]);
+                let newest = quantity(&request["params"][1]);
+                recorded.lock().unwrap().push(count);
+                let blocks = newest + 1 - count..=newest;
+                json!({
+                    "oldestBlock": format!("0x{:x}", newest + 1 - count),
+                    "baseFeePerGas": vec!["0x3b9aca00"; count as usize + 1],
+                    "baseFeePerBlobGas": (newest + 1 - count..=newest + 1)
+                        .map(|n| format!("0x{:x}", n + 100 * fork))
+                        .collect::<Vec<_>>(),
+                    "gasUsedRatio": vec![0.5; count as usize],
+                    "reward": blocks
+                        .map(|n| [n, 2 * n, 3 * n].map(|tip| format!("0x{:x}", tip + 100 * fork)))
+                        .collect::<Vec<_>>(),
+                })
+            },
+            _ => Value::Null,
+        }
+    })
+    .await;
+
+    (url, counts)
+}
+
+#[tokio::test]
+async fn test_fee_oracle_suggestions_and_sample_cache() {
+    let head = Arc::new(AtomicUsize::new(10));
+    let (url, counts) = spawn_fee_server(head.clone(), Arc::default()).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+    let oracle = FeeOracle::new(client).block_count(4).base_fee_headroom(50);
+
+    let gwei = U256::from(1_000_000_000u64);
+    let estimate = oracle.estimate().await.unwrap();
+    assert_eq!(estimate.block, 10);
+    assert_eq!(estimate.base_fee_per_gas, gwei);
+    // Taken from the node rather than recomputed from fork constants
+    assert_eq!(estimate.base_fee_per_blob_gas, Some(U256::from(11)));
+    // Blocks 7 to 10, median of each percentile
+    assert_eq!(estimate.slow.max_priority_fee_per_gas, U256::from(9));
+    assert_eq!(estimate.normal.max_priority_fee_per_gas, U256::from(18));
+    assert_eq!(estimate.fast.max_priority_fee_per_gas, U256::from(27));
+    assert_eq!(
+        estimate.fast.max_fee_per_gas,
+        gwei * U256::from(3) / U256::from(2) + U256::from(27)
+    );
+    assert_eq!(estimate.fast.max_fee_per_blob_gas, Some(U256::from(16)));
+
+    // Only the two new blocks are fetched, nothing at all without a new head
+    head.store(12, Ordering::SeqCst);
+    let estimate = oracle.estimate().await.unwrap();
+    assert_eq!(estimate.slow.max_priority_fee_per_gas, U256::from(11));
+    assert_eq!(estimate.base_fee_per_blob_gas, Some(U256::from(13)));
+    let estimate = oracle.estimate().await.unwrap();
+    assert_eq!(estimate.base_fee_per_blob_gas, Some(U256::from(13)));
+    assert_eq!(*counts.lock().unwrap(), vec![4, 2]);
+}
+
+#[tokio::test]
+async fn test_fee_oracle_resamples_after_reorg_at_same_height() {
+    let (head, fork) = (Arc::new(AtomicUsize::new(10)), Arc::new(AtomicUsize::new(0)));
+    let (url, counts) = spawn_fee_server(head.clone(), fork.clone()).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+    let oracle = FeeOracle::new(client).block_count(4);
+
+    let estimate = oracle.estimate().await.unwrap();
+    assert_eq!(estimate.slow.max_priority_fee_per_gas, U256::from(9));
+    assert_eq!(estimate.base_fee_per_blob_gas, Some(U256::from(11)));
+
+    // Another block 10 replaces the head, none of the samples can be trusted
+    fork.store(1, Ordering::SeqCst);
+    let estimate = oracle.estimate().await.unwrap();
+    assert_eq!(estimate.block, 10);
+    assert_eq!(estimate.slow.max_priority_fee_per_gas, U256::from(109));
+    assert_eq!(estimate.base_fee_per_blob_gas, Some(U256::from(111)));
+    assert_eq!(*counts.lock().unwrap(), vec![4, 4]);
+}
+
+/// In-memory transport answering every request with block `0x10`, keeping the
+/// payloads it was sent
+#[derive(Debug, Default)]
+struct MockTransport {
+    batch: bool,
+    payloads: Arc<Mutex<Vec<Bytes>>>,
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    async fn request(&self, request: Bytes) -> Result<Bytes, RpcError> {
+        self.payloads.lock().unwrap().push(request.clone());
+        let request: Value = serde_json::from_slice(&request).unwrap();
+        let answer =
+            |request: &Value| json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x10"});
+        let response = match request.as_array() {
+            Some(batch) => batch.iter().map(answer).collect(),
+            None => answer(&request),
+        };
+        Ok(response.to_string().into())
+    }
+
+    fn supports_batch(&self) -> bool {
+        self.batch
+    }
+}
+
+fn block_number_requests() -> Vec<RpcRequest> {
+    (1..=2)
+        .map(|id| RpcRequest {
+            jsonrpc: "2.0",
+            method: "eth_blockNumber",
+            params: json!([]),
+            id,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_batches_follow_transport_capabilities() {
+    // Without batch support the requests go out as one JSON-RPC array
+    let plain = MockTransport::default();
+    let payloads = plain.payloads.clone();
+    let client = RpcClient::new(plain);
+    assert_eq!(client.get_block_number().await.unwrap(), U64::from(0x10));
+    let response = client.execute_batch_raw(block_number_requests()).await.unwrap();
+    assert_eq!(serde_json::from_slice::<Value>(&response).unwrap().as_array().unwrap().len(), 2);
+    assert_eq!(payloads.lock().unwrap().len(), 2);
+    assert!(payloads.lock().unwrap()[1].starts_with(b"["));
+    assert!(client.subscribe_new_heads().await.is_err());
+
+    // Small batches are sent one request each when the transport multiplexes
+    let multiplexed = MockTransport {
+        batch: true,
+        ..Default::default()
+    };
+    let payloads = multiplexed.payloads.clone();
+    let client = RpcClient::new(multiplexed);
+    let response = client.execute_batch_raw(block_number_requests()).await.unwrap();
+    assert_eq!(serde_json::from_slice::<Value>(&response).unwrap().as_array().unwrap().len(), 2);
+    let payloads = payloads.lock().unwrap();
+    assert_eq!(payloads.len(), 2);
+    assert!(payloads.iter().all(|payload| payload.starts_with(b"{")));
+}
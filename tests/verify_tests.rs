@@ -0,0 +1,521 @@
+mod common;
+
+use alloy::primitives::{b256, bytes, keccak256, Address, Bytes as AlloyBytes, B256, U256, U64};
+use alloy_trie::{
+    proof::ProofRetainer, HashBuilder, Nibbles, TrieAccount, EMPTY_ROOT_HASH, KECCAK_EMPTY,
+};
+use common::{ok, serve_http};
+use hyper::body::Bytes;
+use palantiri::{
+    hyper_rpc::RpcClient,
+    parser::{
+        block_parser::parse_block, proof_parser::parse_proof, receipt_parser::parse_block_receipts,
+    },
+    transport::http::TransportBuilder,
+    verify::{HeaderChain, VerificationError},
+    RpcError,
+};
+use serde_json::json;
+
+/// Answers every request with `body`
+async fn spawn_http_server(body: &str) -> String {
+    let body = Bytes::from(body.to_owned());
+    serve_http(move |_| ok(body.clone())).await
+}
+
+fn block_response(block: &str) -> String {
+    format!(r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#, block)
+}
+
+/// Mainnet block 0
+const GENESIS: &str = r#"{"difficulty":"0x400000000","extraData":"0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa","gasLimit":"0x1388","gasUsed":"0x0","hash":"0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","miner":"0x0000000000000000000000000000000000000000","mixHash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0000000000000042","number":"0x0","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000000","receiptsRoot":"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421","sha3Uncles":"0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347","size":"0x21c","stateRoot":"0xd7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544","timestamp":"0x0","totalDifficulty":"0x400000000","transactions":[],"transactionsRoot":"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421","uncles":[]}"#;
+
+/// Header with every field up to Prague set
+fn prague_block(hash: B256) -> String {
+    format!(
+        r#"{{"baseFeePerGas":"0x7","blobGasUsed":"0x20000","difficulty":"0x0","excessBlobGas":"0x40000","extraData":"0x","gasLimit":"0x1c9c380","gasUsed":"0x0","hash":"{}","logsBloom":"0x{}","miner":"0x{}","mixHash":"0x{}","nonce":"0x0000000000000000","number":"0x10","parentBeaconBlockRoot":"0x{}","parentHash":"0x{}","receiptsRoot":"0x{}","requestsHash":"0x{}","sha3Uncles":"0x{}","stateRoot":"0x{}","timestamp":"0x5","transactions":[],"transactionsRoot":"0x{}","uncles":[],"withdrawals":[],"withdrawalsRoot":"0x{}"}}"#,
+        hash,
+        "00".repeat(256),
+        "12".repeat(20),
+        "b0".repeat(32),
+        "b1".repeat(32),
+        "ab".repeat(32),
+        "af".repeat(32),
+        "b2".repeat(32),
+        "ac".repeat(32),
+        "ad".repeat(32),
+        "ae".repeat(32),
+        "b3".repeat(32),
+    )
+}
+
+/// London header linked to `parent_hash`, with its hash filled in
+fn london_block(number: u64, parent_hash: B256) -> (String, B256) {
+    let block = |hash: B256| {
+        format!(
+            r#"{{"baseFeePerGas":"0x7","difficulty":"0x2000","extraData":"0x010203","gasLimit":"0x1c9c380","gasUsed":"0x0","hash":"{}","logsBloom":"0x{}","miner":"0x{}","mixHash":"0x{}","nonce":"0x0000000000000042","number":"0x{:x}","parentHash":"{}","receiptsRoot":"0x{}","sha3Uncles":"0x{}","stateRoot":"0x{}","timestamp":"0x{:x}","transactions":[],"transactionsRoot":"0x{}","uncles":[]}}"#,
+            hash,
+            "00".repeat(256),
+            "12".repeat(20),
+            "b0".repeat(32),
+            number,
+            parent_hash,
+            "af".repeat(32),
+            "ac".repeat(32),
+            "ad".repeat(32),
+            number * 12,
+            "ae".repeat(32),
+        )
+    };
+
+    let unhashed = parse_block(block_response(&block(B256::ZERO)).as_bytes()).unwrap();
+    let hash = unhashed.header().compute_hash().unwrap();
+    (block(hash), hash)
+}
+
+#[test]
+fn test_genesis_hash() {
+    let block = parse_block(block_response(GENESIS).as_bytes()).expect("block should parse");
+    assert_eq!(
+        block.verify_hash(),
+        Ok(b256!("0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"))
+    );
+}
+
+#[test]
+fn test_fork_field_sets() {
+    // Reference hashes computed with alloy-consensus
+    let prague = b256!("0x84f61068967a9a67fabda83d0d533fc01a907be5b2a7de5f8c8a1ee4707dd936");
+    let block = parse_block(block_response(&prague_block(prague)).as_bytes()).unwrap();
+    assert_eq!(block.verify_hash(), Ok(prague));
+
+    let london = b256!("0x0b3cda73aa2ad26f327a09c0a0789cc97af7fd4d77e3fc10908277eb8d36decd");
+    let mut header = block.header();
+    header.withdrawls_root = None;
+    header.blob_gas_used = None;
+    header.excess_blob_gas = None;
+    header.parent_beacon_block_root = None;
+    header.requests_hash = None;
+    header.difficulty = 0x2000;
+    header.nonce = 0x42;
+    header.extra_data = "0x010203".to_string();
+    assert_eq!(header.compute_hash(), Ok(london));
+}
+
+#[test]
+fn test_hash_mismatch() {
+    let claimed = B256::repeat_byte(0x01);
+    let block = parse_block(block_response(&prague_block(claimed)).as_bytes()).unwrap();
+
+    match block.verify_hash() {
+        Err(VerificationError::HashMismatch {
+            number,
+            claimed: got,
+            ..
+        }) => {
+            assert_eq!(number, 0x10);
+            assert_eq!(got, claimed);
+        },
+        other => panic!("expected a mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_missing_fork_field() {
+    let block = parse_block(block_response(&prague_block(B256::ZERO)).as_bytes()).unwrap();
+    let mut header = block.header();
+    header.blob_gas_used = None;
+
+    assert_eq!(
+        header.compute_hash(),
+        Err(VerificationError::MissingField {
+            number: 0x10,
+            field: "blobGasUsed",
+        })
+    );
+}
+
+#[test]
+fn test_header_chain_linkage() {
+    let (first, first_hash) = london_block(1, B256::repeat_byte(0x99));
+    let (second, _) = london_block(2, first_hash);
+    let (orphan, _) = london_block(2, B256::repeat_byte(0x98));
+    let (gap, _) = london_block(3, first_hash);
+    let header = |json: &str| parse_block(block_response(json).as_bytes()).unwrap().header();
+
+    let chain = HeaderChain::from_headers(vec![header(&first), header(&second)]).unwrap();
+    assert_eq!(chain.len(), 2);
+    assert_eq!(chain.tip().unwrap().number, 2);
+
+    assert_eq!(
+        HeaderChain::from_headers(vec![header(&first), header(&orphan)]).unwrap_err(),
+        VerificationError::BrokenLink {
+            number: 2,
+            parent_hash: B256::repeat_byte(0x98),
+            expected: first_hash,
+        }
+    );
+    assert_eq!(
+        HeaderChain::from_headers(vec![header(&first), header(&gap)]).unwrap_err(),
+        VerificationError::NotContiguous {
+            number: 3,
+            expected: 2,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_header_chain_fetch() {
+    let (first, first_hash) = london_block(1, B256::repeat_byte(0x99));
+    let (second, second_hash) = london_block(2, first_hash);
+    let (third, _) = london_block(3, second_hash);
+    // Batch responses are not required to keep the request order
+    let body = format!(
+        r#"[{{"jsonrpc":"2.0","id":3,"result":{}}},{{"jsonrpc":"2.0","id":1,"result":{}}},{{"jsonrpc":"2.0","id":2,"result":{}}}]"#,
+        third, first, second
+    );
+    let url = spawn_http_server(&body).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    let chain = HeaderChain::fetch(&client, 1..=3).await.unwrap();
+    assert_eq!(chain.len(), 3);
+    assert_eq!(chain.headers()[1].hash, Some(second_hash));
+    // A verified range lands in the client's header store
+    assert_eq!(client.header_store().hash_of(2), Some(second_hash));
+    assert_eq!(client.header_store().number_of(&first_hash), Some(1));
+
+    let broken = format!(
+        r#"[{{"jsonrpc":"2.0","id":1,"result":{}}},{{"jsonrpc":"2.0","id":2,"result":{}}},{{"jsonrpc":"2.0","id":3,"result":{}}}]"#,
+        first, third, third
+    );
+    let url = spawn_http_server(&broken).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    assert!(matches!(
+        HeaderChain::fetch(&client, 1..=3).await,
+        Err(RpcError::Verification(VerificationError::NotContiguous { .. }))
+    ));
+    // Nothing of a range that fails verification is stored
+    assert!(client.header_store().is_empty());
+
+    // Node errors come through as such rather than as missing blocks
+    let limited = r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32005,"message":"rate limited"}}"#;
+    let failed_item = format!(
+        r#"[{{"jsonrpc":"2.0","id":1,"result":{}}},{{"jsonrpc":"2.0","id":2,"error":{{"code":-32005,"message":"rate limited"}}}}]"#,
+        first
+    );
+    for body in [limited.to_string(), failed_item] {
+        let url = spawn_http_server(&body).await;
+        let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+        assert!(matches!(
+            HeaderChain::fetch(&client, 1..=3).await,
+            Err(RpcError::JsonRpc { code: -32005, .. })
+        ));
+    }
+}
+
+const SIG: &str = r#""r":"0x1111111111111111111111111111111111111111111111111111111111111111","s":"0x2222222222222222222222222222222222222222222222222222222222222222""#;
+
+/// One transaction of every type, as a node returns them in a full block
+fn full_transactions() -> String {
+    let common = |index: u64, nonce: u64| {
+        format!(
+            r#""blockHash":"0x{}","blockNumber":"0x10","from":"0x{}","hash":"0x{:064x}","nonce":"0x{:x}","transactionIndex":"0x{:x}",{}"#,
+            "aa".repeat(32),
+            "99".repeat(20),
+            index + 1,
+            nonce,
+            index,
+            SIG
+        )
+    };
+    let transactions = [
+        format!(
+            r#"{{{},"type":"0x0","chainId":"0x1","gas":"0x5208","gasPrice":"0x3b9aca00","input":"0x","to":"0x{}","value":"0x1","v":"0x25"}}"#,
+            common(0, 0),
+            "11".repeat(20)
+        ),
+        format!(
+            r#"{{{},"type":"0x1","chainId":"0x1","gas":"0x7530","gasPrice":"0x3b9aca00","input":"0xdeadbeef","to":"0x{}","value":"0x0","accessList":[{{"address":"0x{}","storageKeys":["0x{:064x}"]}}],"v":"0x1","yParity":"0x1"}}"#,
+            common(1, 1),
+            "22".repeat(20),
+            "33".repeat(20),
+            1
+        ),
+        format!(
+            r#"{{{},"type":"0x2","chainId":"0x1","gas":"0x186a0","gasPrice":"0x9","maxFeePerGas":"0x64","maxPriorityFeePerGas":"0x2","input":"0x6080","to":null,"value":"0x0","accessList":[],"v":"0x0","yParity":"0x0"}}"#,
+            common(2, 2)
+        ),
+        format!(
+            r#"{{{},"type":"0x3","chainId":"0x1","gas":"0x5208","gasPrice":"0x9","maxFeePerGas":"0x64","maxPriorityFeePerGas":"0x1","maxFeePerBlobGas":"0xa","blobVersionedHashes":["0x{:064x}"],"input":"0x","to":"0x{}","value":"0x0","accessList":[],"v":"0x1","yParity":"0x1"}}"#,
+            common(3, 3),
+            1,
+            "44".repeat(20)
+        ),
+        format!(
+            r#"{{{},"type":"0x4","chainId":"0x1","gas":"0xc350","gasPrice":"0x9","maxFeePerGas":"0x64","maxPriorityFeePerGas":"0x1","input":"0x","to":"0x{}","value":"0x0","accessList":[],"authorizationList":[{{"chainId":"0x1","address":"0x{}","nonce":"0x7","yParity":"0x0","r":"0x{}","s":"0x{}"}}],"v":"0x0","yParity":"0x0"}}"#,
+            common(4, 4),
+            "55".repeat(20),
+            "66".repeat(20),
+            "33".repeat(32),
+            "44".repeat(32)
+        ),
+    ];
+    format!("[{}]", transactions.join(","))
+}
+
+/// Receipts matching `full_transactions`, the second one with a log
+fn block_receipts() -> String {
+    let receipt = |index: u64, tx_type: u8, status: u8, cumulative: u64, logs: &str| {
+        format!(
+            r#"{{"blockHash":"0x{}","blockNumber":"0x10","contractAddress":null,"cumulativeGasUsed":"0x{:x}","effectiveGasPrice":"0x9","from":"0x{}","gasUsed":"0x5208","logs":[{}],"logsBloom":"0x{}","status":"0x{:x}","to":null,"transactionHash":"0x{:064x}","transactionIndex":"0x{:x}","type":"0x{:x}"}}"#,
+            "aa".repeat(32),
+            cumulative,
+            "99".repeat(20),
+            logs,
+            "00".repeat(256),
+            status,
+            index + 1,
+            index,
+            tx_type
+        )
+    };
+    let log = format!(
+        r#"{{"address":"0x{}","topics":["0x{}","0x{}"],"data":"0x0102","blockNumber":"0x10","transactionHash":"0x{:064x}","transactionIndex":"0x1","blockHash":"0x{}","logIndex":"0x0","removed":false}}"#,
+        "77".repeat(20),
+        "aa".repeat(32),
+        "bb".repeat(32),
+        2,
+        "aa".repeat(32)
+    );
+    let receipts = [
+        receipt(0, 0, 1, 21000, ""),
+        receipt(1, 1, 1, 51000, &log),
+        receipt(2, 2, 0, 151000, ""),
+        receipt(3, 3, 1, 172000, ""),
+        receipt(4, 4, 1, 222000, ""),
+    ];
+    format!(r#"{{"jsonrpc":"2.0","id":1,"result":[{}]}}"#, receipts.join(","))
+}
+
+fn block_with_transactions(transactions: &str, transactions_root: B256) -> String {
+    format!(
+        r#"{{"baseFeePerGas":"0x7","difficulty":"0x0","extraData":"0x","gasLimit":"0x1c9c380","gasUsed":"0x0","hash":"0x{}","logsBloom":"0x{}","miner":"0x{}","mixHash":"0x{}","nonce":"0x0000000000000000","number":"0x10","parentHash":"0x{}","receiptsRoot":"0x94e105fa97146fd9d66f19a70bfca86b05ffc77d54f40351f3baa53d58a312a6","sha3Uncles":"0x{}","stateRoot":"0x{}","timestamp":"0x5","transactions":{},"transactionsRoot":"{}","uncles":[]}}"#,
+        "aa".repeat(32),
+        "00".repeat(256),
+        "12".repeat(20),
+        "b0".repeat(32),
+        "ab".repeat(32),
+        "ac".repeat(32),
+        "ad".repeat(32),
+        transactions,
+        transactions_root,
+    )
+}
+
+#[test]
+fn test_transactions_root() {
+    // Reference roots computed with alloy-consensus
+    let root = b256!("0x3153b7e48852efff03bd76fe1f28cdaeaf9401705c9a0577f97b1f128a724d6a");
+    let json = block_response(&block_with_transactions(&full_transactions(), root));
+    let block = parse_block(json.as_bytes()).expect("block should parse");
+    assert_eq!(block.transactions.len(), 5);
+    assert_eq!(block.verify_transactions_root(), Ok(root));
+
+    let legacy = block.transactions.as_full().unwrap()[0].encoded_2718().unwrap();
+    assert_eq!(
+        AlloyBytes::from(legacy),
+        bytes!("f86380843b9aca00825208941111111111111111111111111111111111111111018025a01111111111111111111111111111111111111111111111111111111111111111a02222222222222222222222222222222222222222222222222222222222222222")
+    );
+
+    // A node altering a transaction no longer matches the header
+    let tampered = full_transactions().replacen(r#""value":"0x1""#, r#""value":"0x2""#, 1);
+    let json = block_response(&block_with_transactions(&tampered, root));
+    let block = parse_block(json.as_bytes()).unwrap();
+    assert!(matches!(
+        block.verify_transactions_root(),
+        Err(VerificationError::RootMismatch {
+            root: "transactionsRoot",
+            ..
+        })
+    ));
+
+    // Hashes only cannot be verified, an empty block can
+    let hashes = format!(r#"["0x{}"]"#, "01".repeat(32));
+    let json = block_response(&block_with_transactions(&hashes, root));
+    assert_eq!(
+        parse_block(json.as_bytes()).unwrap().verify_transactions_root(),
+        Err(VerificationError::MissingTransactions { number: 0x10 })
+    );
+    let empty = alloy_trie::EMPTY_ROOT_HASH;
+    let json = block_response(&block_with_transactions("[]", empty));
+    assert_eq!(parse_block(json.as_bytes()).unwrap().verify_transactions_root(), Ok(empty));
+}
+
+#[test]
+fn test_receipts_root() {
+    let root = b256!("0x94e105fa97146fd9d66f19a70bfca86b05ffc77d54f40351f3baa53d58a312a6");
+    let json = block_response(&block_with_transactions("[]", alloy_trie::EMPTY_ROOT_HASH));
+    let block = parse_block(json.as_bytes()).unwrap();
+
+    let mut receipts = parse_block_receipts(block_receipts().as_bytes());
+    assert_eq!(receipts.len(), 5);
+    assert_eq!(block.verify_receipts_root(&receipts), Ok(root));
+
+    receipts[2].status = Some(U64::from(1));
+    assert!(matches!(
+        block.verify_receipts_root(&receipts),
+        Err(VerificationError::RootMismatch {
+            root: "receiptsRoot",
+            ..
+        })
+    ));
+
+    // Before Byzantium the receipt carries the post state root instead
+    let mut receipt = receipts.swap_remove(0);
+    receipt.status = None;
+    receipt.root = Some(B256::repeat_byte(0x99));
+    let pre_byzantium = b256!("0x61563513c4ceeda12fd38bb45074074e2658548f7147827a35f304765e000980");
+    let computed = match block.verify_receipts_root(&[receipt]) {
+        Err(VerificationError::RootMismatch { computed, .. }) => computed,
+        other => panic!("expected a mismatch, got {:?}", other),
+    };
+    assert_eq!(computed, pre_byzantium);
+}
+
+/// Trie over `(key, value)` leaves keyed by `keccak(key)`, with the proof of
+/// every key in `targets`
+fn trie_with_proofs(
+    leaves: &[(&[u8], Vec<u8>)],
+    targets: &[&[u8]],
+) -> (B256, Vec<Vec<AlloyBytes>>) {
+    let mut leaves: Vec<(B256, &Vec<u8>)> =
+        leaves.iter().map(|(key, value)| (keccak256(key), value)).collect();
+    leaves.sort_by_key(|(key, _)| *key);
+    let targets: Vec<Nibbles> = targets.iter().map(|key| Nibbles::unpack(keccak256(key))).collect();
+
+    let mut builder =
+        HashBuilder::default().with_proof_retainer(ProofRetainer::new(targets.clone()));
+    for (key, value) in leaves {
+        builder.add_leaf(Nibbles::unpack(key), value);
+    }
+    let root = builder.root();
+    let nodes = builder.take_proof_nodes();
+    let proofs = targets
+        .iter()
+        .map(|target| {
+            nodes.matching_nodes_sorted(target).into_iter().map(|(_, node)| node).collect()
+        })
+        .collect();
+    (root, proofs)
+}
+
+#[tokio::test]
+async fn test_account_and_storage_proofs() {
+    let slot = B256::with_last_byte(1);
+    let unset_slot = B256::with_last_byte(9);
+    let (storage_hash, storage_proofs) = trie_with_proofs(
+        &[
+            (B256::ZERO.as_slice(), alloy::rlp::encode(U256::from(0x2a))),
+            (slot.as_slice(), alloy::rlp::encode(U256::from(0xdeadbeefu64))),
+        ],
+        &[slot.as_slice(), unset_slot.as_slice()],
+    );
+
+    let contract = Address::repeat_byte(0xc0);
+    let missing = Address::repeat_byte(0xab);
+    let code_hash = keccak256([0x60, 0x00]);
+    let account = |nonce: u64, balance: u64, storage_root: B256, code_hash: B256| {
+        alloy::rlp::encode(TrieAccount {
+            nonce,
+            balance: U256::from(balance),
+            storage_root,
+            code_hash,
+        })
+    };
+    let mut accounts = vec![(contract.to_vec(), account(1, 0, storage_hash, code_hash))];
+    for i in 1..=6u8 {
+        let other = account(i as u64, i as u64, EMPTY_ROOT_HASH, KECCAK_EMPTY);
+        accounts.push((Address::repeat_byte(i).to_vec(), other));
+    }
+    let leaves: Vec<(&[u8], Vec<u8>)> =
+        accounts.iter().map(|(key, value)| (key.as_slice(), value.clone())).collect();
+    let (state_root, account_proofs) =
+        trie_with_proofs(&leaves, &[contract.as_slice(), missing.as_slice()]);
+
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": {
+            "address": contract,
+            "balance": "0x0",
+            "nonce": "0x1",
+            "codeHash": code_hash,
+            "storageHash": storage_hash,
+            "accountProof": account_proofs[0],
+            "storageProof": [
+                // Unpadded key, as some nodes echo it back
+                {"key": "0x1", "value": "0xdeadbeef", "proof": storage_proofs[0]},
+                {"key": unset_slot, "value": "0x0", "proof": storage_proofs[1]},
+            ]
+        }
+    });
+    let url = spawn_http_server(&body.to_string()).await;
+    let client = RpcClient::new(TransportBuilder::new(url).build_http_hyper().unwrap());
+
+    let proof = client.get_proof(contract, &[slot, unset_slot], 0x10).await.unwrap();
+    assert_eq!(proof.storage_proof[0].key, slot);
+    assert_eq!(proof.storage_value(slot), Some(U256::from(0xdeadbeefu64)));
+    assert_eq!(proof.storage_value(unset_slot), Some(U256::ZERO));
+    assert_eq!(proof.storage_value(B256::ZERO), None);
+    assert_eq!(proof.verify_against(state_root), Ok(()));
+
+    // A valid proof of another account or of other slots answers nothing asked
+    for (address, slots) in [
+        (missing, vec![slot, unset_slot]),
+        (contract, vec![unset_slot, slot]),
+        (contract, vec![slot]),
+        (contract, vec![slot, unset_slot, B256::ZERO]),
+    ] {
+        assert!(matches!(
+            client.get_proof(address, &slots, 0x10).await,
+            Err(RpcError::Verification(VerificationError::InvalidProof { .. }))
+        ));
+    }
+
+    // A node lying about a slot or the balance is caught
+    let mut forged = proof.clone();
+    forged.storage_proof[0].value = U256::from(1);
+    assert!(matches!(
+        forged.verify_against(state_root),
+        Err(VerificationError::InvalidProof { slot: Some(s), .. }) if s == slot
+    ));
+    let mut forged = proof.clone();
+    forged.balance = U256::from(1);
+    assert!(matches!(
+        forged.verify_against(state_root),
+        Err(VerificationError::InvalidProof { slot: None, .. })
+    ));
+
+    // Absent accounts are proven by exclusion, reported with zeroed fields
+    let absent = parse_proof(
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "address": missing,
+                "balance": "0x0",
+                "nonce": "0x0",
+                "codeHash": B256::ZERO,
+                "storageHash": B256::ZERO,
+                "accountProof": account_proofs[1],
+                "storageProof": []
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    )
+    .expect("proof should parse");
+    assert_eq!(absent.verify_against(state_root), Ok(()));
+    assert!(absent.verify_against(B256::repeat_byte(1)).is_err());
+}